@@ -0,0 +1,477 @@
+//! Contains [`AnvilImporter`], which imports the chunks of Minecraft Java
+//! Edition worlds, stored in Anvil region files (`.mca`), into
+//! [`ChunkColumn`]s. Only chunks saved by Minecraft 1.18 or later are
+//! supported, since their height matches the height of columns.
+//!
+//! An Anvil region file stores 32×32 chunks. It starts with a table of where
+//! each chunk is in the file, in 4 KiB sectors, followed by a table of when
+//! each chunk was last saved. Each chunk is an NBT document, compressed on its
+//! own and prefixed with its length and how it is compressed.
+//!
+//! Blocks are mapped by name alone, ignoring their block state properties:
+//! `minecraft:stone` becomes `mycraft:stone` if such a block is registered,
+//! and other names can be mapped in [`AnvilBlockMappings`]. Blocks that cannot
+//! be mapped become air, and are listed in the [`AnvilImportReport`].
+
+#![expect(clippy::big_endian_bytes, reason = "Anvil region files are big-endian.")]
+
+use assets_manager::*;
+use assets_manager::asset::*;
+use flate2::read::{GzDecoder, ZlibDecoder};
+use glam::*;
+use log::*;
+use serde::*;
+use std::borrow::*;
+use std::collections::*;
+use std::io::{self, Read as _};
+use thiserror::*;
+use crate::biome::*;
+use crate::block::*;
+use crate::block_registry::*;
+use crate::nbt::*;
+use crate::voxel_world::*;
+
+/// The ID of the file in the [`AssetCache`] that [`AnvilBlockMappings`] are
+/// loaded from.
+pub const ANVIL_BLOCK_MAPPINGS_ASSET_ID: &str = "anvil_block_mappings";
+
+/// The namespace of the blocks and biomes of Minecraft.
+const MINECRAFT_NAMESPACE: &str = "minecraft";
+
+/// The data version of Minecraft 1.18, the first version whose chunks are
+/// laid out the way this importer expects.
+const MIN_DATA_VERSION: i32 = 2860;
+
+/// The number of chunks along each side of a region.
+const ANVIL_REGION_SIZE: usize = 32;
+
+const ANVIL_SECTOR_SIZE: usize = 4096;
+
+/// The size of the location table and the timestamp table.
+const ANVIL_HEADER_SIZE: usize = ANVIL_SECTOR_SIZE * 2;
+
+/// The minimum number of bits per block in the packed block states of a
+/// section.
+const MIN_BLOCK_STATE_BITS: u32 = 4;
+
+/// The number of biomes along each side of a section. Minecraft stores a biome
+/// for every 4×4×4 blocks.
+const BIOMES_PER_SECTION_SIDE: u32 = 4;
+
+/// The Y of the section that the biomes of imported columns are taken from,
+/// since Minecraft stores biomes in 3D and columns only store them in 2D. It
+/// contains Y = 64, which is near the sea level of Minecraft.
+const BIOME_SECTION_Y: i32 = 4;
+
+/// Maps the names of Minecraft blocks that have no block with the same path in
+/// the `mycraft` namespace to similar blocks, such as all kinds of leaves to
+/// oak leaves. It is loaded from the `assets/anvil_block_mappings.ron` file.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AnvilBlockMappings {
+    /// Keyed by the names of Minecraft blocks, such as `minecraft:deepslate`.
+    pub blocks: HashMap<String, BlockName>
+}
+
+impl FileAsset for AnvilBlockMappings {
+    const EXTENSION: &'static str = "ron";
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Result<Self, BoxedError> {
+        load_ron(&bytes)
+    }
+}
+
+/// What happened while importing chunks with an [`AnvilImporter`].
+#[derive(Debug, Default)]
+pub struct AnvilImportReport {
+    pub imported_columns: usize,
+    /// Chunks that could not be read, or that Minecraft had not finished
+    /// generating.
+    pub skipped_columns: usize,
+    /// The names of the Minecraft blocks that could not be mapped, along with
+    /// how many of them were replaced with air.
+    pub unknown_blocks: BTreeMap<String, usize>,
+    /// The names of the Minecraft biomes that do not exist, along with how many
+    /// columns of blocks they were replaced in with the biome with the ID 0.
+    pub unknown_biomes: BTreeMap<String, usize>
+}
+
+impl AnvilImportReport {
+    /// Logs the numbers of columns, and warns about the unknown blocks and
+    /// biomes.
+    pub fn log(&self) {
+        info!("Imported {} columns and skipped {}.", self.imported_columns, self.skipped_columns);
+        for (name, count) in &self.unknown_blocks {
+            warn!("The Minecraft block {name} is unknown, so {count} of them were replaced with air.");
+        }
+        for (name, count) in &self.unknown_biomes {
+            warn!("The Minecraft biome {name} is unknown, so it was replaced in {count} columns of blocks.");
+        }
+    }
+}
+
+/// Converts the chunks of Anvil region files into [`ChunkColumn`]s with the
+/// blocks and biomes that are currently registered, and keeps track of what
+/// could not be converted in an [`AnvilImportReport`].
+pub struct AnvilImporter {
+    /// Keyed by the names of Minecraft blocks.
+    block_ids: HashMap<String, BlockId>,
+    /// Keyed by the names of Minecraft biomes.
+    biome_ids: HashMap<String, BiomeId>,
+    report: AnvilImportReport
+}
+
+impl AnvilImporter {
+    pub fn new(mappings: &AnvilBlockMappings, block_registry: &BlockRegistry, biome_definitions: &BiomeDefinitions) -> Self {
+        let mut block_ids: HashMap<_, _> = block_registry.iter()
+            .filter(|(_, block_type)| block_type.name.namespace() == BlockName::DEFAULT_NAMESPACE)
+            .map(|(id, block_type)| (format!("{MINECRAFT_NAMESPACE}:{}", block_type.name.path()), id))
+            .collect();
+        for (minecraft_name, name) in &mappings.blocks {
+            match block_registry.id(name) {
+                Some(id) => {
+                    block_ids.insert(minecraft_name.clone(), id);
+                }
+                None => warn!("The Minecraft block {minecraft_name} is mapped to {name}, which is not registered.")
+            }
+        }
+        let biome_ids = biome_definitions.iter().map(|(id, name, _)| (format!("{MINECRAFT_NAMESPACE}:{name}"), id)).collect();
+        Self { block_ids, biome_ids, report: AnvilImportReport::default() }
+    }
+
+    /// Imports every chunk in the Anvil region file made of `bytes`, along
+    /// with their column positions. Chunks that cannot be imported are logged
+    /// and skipped, and only a region file that cannot be read at all is an
+    /// error.
+    pub fn import_region(&mut self, bytes: &[u8]) -> Result<Vec<(IVec2, ChunkColumn)>, AnvilError> {
+        let mut columns = Vec::new();
+        for chunk in read_anvil_chunks(bytes)? {
+            match chunk.and_then(|compound| self.import_chunk(&compound)) {
+                Ok(Some(column)) => {
+                    columns.push(column);
+                    self.report.imported_columns += 1;
+                }
+                Ok(None) => self.report.skipped_columns += 1,
+                Err(err) => {
+                    error!("A chunk could not be imported. {err}");
+                    self.report.skipped_columns += 1;
+                }
+            }
+        }
+        Ok(columns)
+    }
+
+    /// Imports a chunk that has been read from a region file. Returns [`None`]
+    /// if Minecraft had not finished generating it, in which case Minecraft
+    /// would generate the rest of it when it is loaded.
+    pub fn import_chunk(&mut self, chunk: &NbtCompound) -> Result<Option<(IVec2, ChunkColumn)>, AnvilError> {
+        let data_version = chunk.get_int("DataVersion").ok_or(AnvilError::MissingTag("DataVersion"))?;
+        if data_version < MIN_DATA_VERSION {
+            return Err(AnvilError::UnsupportedDataVersion(data_version));
+        }
+        let column_position = IVec2::new(
+            chunk.get_int("xPos").ok_or(AnvilError::MissingTag("xPos"))?,
+            chunk.get_int("zPos").ok_or(AnvilError::MissingTag("zPos"))?
+        );
+        let status = chunk.get_string("Status").unwrap_or_default();
+        if !matches!(status, "minecraft:full" | "full") {
+            debug!("The chunk at {column_position} is skipped because its status is {status}.");
+            return Ok(None);
+        }
+        let mut column = ChunkColumn::default();
+        for section_tag in chunk.get_list("sections").ok_or(AnvilError::MissingTag("sections"))? {
+            let NbtTag::Compound(section) = section_tag else {
+                return Err(AnvilError::MissingTag("sections"));
+            };
+            let section_y = i32::from(section.get_byte("Y").ok_or(AnvilError::MissingTag("Y"))?);
+            if column.section(section_y).is_none() {
+                continue;
+            }
+            if let Some(block_states) = section.get_compound("block_states") {
+                let blocks = self.import_block_states(block_states)?;
+                if let Some(column_section) = column.section_mut(section_y) {
+                    *column_section = ChunkSection::new(blocks);
+                }
+            }
+            if section_y == BIOME_SECTION_Y && let Some(biomes) = section.get_compound("biomes") {
+                self.import_biomes(biomes, &mut column)?;
+            }
+        }
+        Ok(Some((column_position, column)))
+    }
+
+    /// Returns the report of everything imported so far.
+    pub fn finish(self) -> AnvilImportReport {
+        self.report
+    }
+
+    fn import_block_states(&mut self, block_states: &NbtCompound) -> Result<PalettedContainer, AnvilError> {
+        let palette = block_states.get_list("palette").ok_or(AnvilError::MissingTag("palette"))?;
+        let mut blocks = Vec::with_capacity(palette.len());
+        for block_state_tag in palette {
+            let NbtTag::Compound(block_state) = block_state_tag else {
+                return Err(AnvilError::MissingTag("palette"));
+            };
+            let name = block_state.get_string("Name").ok_or(AnvilError::MissingTag("Name"))?;
+            blocks.push((name, self.block_ids.get(name).copied()));
+        }
+        let indices = unpack_indices(block_states.get_long_array("data"), palette.len(), SECTION_VOLUME, MIN_BLOCK_STATE_BITS)?;
+        let mut container = PalettedContainer::new(BlockId::AIR);
+        // Minecraft orders blocks by Y, then Z, then X, like sections do.
+        for (index, palette_index) in indices.into_iter().enumerate() {
+            match blocks[palette_index] {
+                (_, Some(block)) => {
+                    container.set(index, block);
+                }
+                (name, None) => *self.report.unknown_blocks.entry(name.to_owned()).or_default() += 1
+            }
+        }
+        Ok(container)
+    }
+
+    #[expect(clippy::integer_division, reason = "Each biome covers 4×4 blocks.")]
+    fn import_biomes(&mut self, biomes: &NbtCompound, column: &mut ChunkColumn) -> Result<(), AnvilError> {
+        let palette = biomes.get_list("palette").ok_or(AnvilError::MissingTag("palette"))?;
+        let mut biome_ids = Vec::with_capacity(palette.len());
+        for biome in palette {
+            let NbtTag::String(name) = biome else {
+                return Err(AnvilError::MissingTag("palette"));
+            };
+            biome_ids.push((name, self.biome_ids.get(name).copied()));
+        }
+        let biome_count = BIOMES_PER_SECTION_SIDE.pow(3) as usize;
+        let indices = unpack_indices(biomes.get_long_array("data"), palette.len(), biome_count, 0)?;
+        // Like blocks, biomes are ordered by Y, then Z, then X, and the
+        // bottom layer is used.
+        let biome_size = SECTION_SIZE.cast_unsigned() / BIOMES_PER_SECTION_SIDE;
+        for z in 0..SECTION_SIZE.cast_unsigned() {
+            for x in 0..SECTION_SIZE.cast_unsigned() {
+                let index = (z / biome_size * BIOMES_PER_SECTION_SIDE + x / biome_size) as usize;
+                let biome = match biome_ids[indices[index]] {
+                    (_, Some(biome)) => biome,
+                    (name, None) => {
+                        *self.report.unknown_biomes.entry(name.clone()).or_default() += 1;
+                        BiomeId::default()
+                    }
+                };
+                column.set_biome(UVec2::new(x, z), biome);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads the chunks of the Anvil region file made of `bytes` in the order of
+/// the location table. A chunk that cannot be read is an error in its place,
+/// so that the other chunks can still be read.
+pub fn read_anvil_chunks(bytes: &[u8]) -> Result<Vec<Result<NbtCompound, AnvilError>>, AnvilError> {
+    let header = bytes.get(..ANVIL_HEADER_SIZE).ok_or(AnvilError::Truncated)?;
+    let locations = &header[..ANVIL_REGION_SIZE * ANVIL_REGION_SIZE * 4];
+    Ok(locations.as_chunks::<4>().0.iter()
+        .filter(|location| **location != [0; 4])
+        .map(|&[offset_high, offset_middle, offset_low, _]| {
+            let sector = u32::from_be_bytes([0, offset_high, offset_middle, offset_low]) as usize;
+            read_anvil_chunk(bytes, sector * ANVIL_SECTOR_SIZE)
+        })
+        .collect())
+}
+
+/// Reads the chunk that starts at `offset` in the region file made of `bytes`.
+fn read_anvil_chunk(bytes: &[u8], offset: usize) -> Result<NbtCompound, AnvilError> {
+    let (length_bytes, rest) = bytes.get(offset..).and_then(<[u8]>::split_first_chunk::<4>).ok_or(AnvilError::Truncated)?;
+    let length = u32::from_be_bytes(*length_bytes) as usize;
+    // The length includes the byte that says how the chunk is compressed.
+    let (compression, compressed_chunk) = rest.get(..length).and_then(<[u8]>::split_first).ok_or(AnvilError::Truncated)?;
+    let mut chunk = Vec::new();
+    match compression {
+        1 => GzDecoder::new(compressed_chunk).read_to_end(&mut chunk)?,
+        2 => ZlibDecoder::new(compressed_chunk).read_to_end(&mut chunk)?,
+        3 => {
+            chunk.extend_from_slice(compressed_chunk);
+            chunk.len()
+        }
+        _ => return Err(AnvilError::UnsupportedCompression(*compression))
+    };
+    Ok(read_nbt(&chunk)?.1)
+}
+
+/// Unpacks `entry_count` palette indices from `data`, where each `u64` holds
+/// as many indices as fit in it whole, starting from its least significant
+/// bits. Each index takes as many bits as the largest one needs, but at least
+/// `min_bits`. If the palette only has one entry, `data` may be missing.
+#[expect(clippy::integer_division, reason = "Entries never span two u64s, so the leftover bits are unused.")]
+fn unpack_indices(data: Option<&[i64]>, palette_length: usize, entry_count: usize, min_bits: u32) -> Result<Vec<usize>, AnvilError> {
+    if palette_length == 0 {
+        return Err(AnvilError::InvalidPalette);
+    }
+    if palette_length == 1 {
+        return Ok(vec![0; entry_count]);
+    }
+    let bits = (usize::BITS - (palette_length - 1).leading_zeros()).max(min_bits);
+    let entries_per_long = u64::BITS / bits;
+    let longs = data.ok_or(AnvilError::MissingTag("data"))?;
+    if longs.len() != entry_count.div_ceil(entries_per_long as usize) {
+        return Err(AnvilError::InvalidPalette);
+    }
+    let mask = (1 << bits) - 1;
+    #[expect(clippy::cast_possible_truncation, reason = "Indices take no more bits than the length of the palette.")]
+    let indices: Vec<_> = longs.iter()
+        .flat_map(|long| (0..entries_per_long).map(move |entry| ((long.cast_unsigned() >> (entry * bits)) & mask) as usize))
+        .take(entry_count)
+        .collect();
+    if indices.iter().any(|index| *index >= palette_length) {
+        return Err(AnvilError::InvalidPalette);
+    }
+    Ok(indices)
+}
+
+#[derive(Debug, Error)]
+pub enum AnvilError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    #[error(transparent)]
+    Nbt(#[from] NbtError),
+
+    #[error("The region file ends before one of its chunks.")]
+    Truncated,
+
+    #[error("A chunk is compressed with the unsupported compression type {0}.")]
+    UnsupportedCompression(u8),
+
+    #[error("A chunk was saved with the data version {0}, but only chunks saved by Minecraft 1.18 or later, whose data version is at least {MIN_DATA_VERSION}, are supported.")]
+    UnsupportedDataVersion(i32),
+
+    #[error("A chunk is missing the tag {0}, or it has the wrong type.")]
+    MissingTag(&'static str),
+
+    #[error("A palette is empty, or the indices into it are invalid.")]
+    InvalidPalette
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::Compression;
+    use flate2::write::{GzEncoder, ZlibEncoder};
+    use std::io::Write as _;
+    use crate::constants::*;
+
+    /// Packs `indices` the way [`unpack_indices`] unpacks them.
+    #[expect(clippy::integer_division, reason = "Entries never span two u64s, so the leftover bits are unused.")]
+    fn pack_indices(indices: &[u64], bits: u32) -> Vec<i64> {
+        let entries_per_long = (u64::BITS / bits) as usize;
+        indices.chunks(entries_per_long)
+            .map(|entries| (0..).zip(entries).fold(0, |long, (entry, index)| long | (index << (entry * bits))).cast_signed())
+            .collect()
+    }
+
+    fn palette_container(palette: Vec<NbtTag>, data: Option<Vec<i64>>) -> NbtTag {
+        let mut container = NbtCompound::default();
+        container.insert("palette", NbtTag::List(palette));
+        if let Some(longs) = data {
+            container.insert("data", NbtTag::LongArray(longs));
+        }
+        NbtTag::Compound(container)
+    }
+
+    fn block_state(name: &str) -> NbtTag {
+        let mut block_state = NbtCompound::default();
+        block_state.insert("Name", NbtTag::String(name.to_owned()));
+        NbtTag::Compound(block_state)
+    }
+
+    /// A chunk with bedrock at the bottom, a section of layered stone,
+    /// deepslate and lava, which is unknown, and biomes that are plains in
+    /// the west half and desert and cherry groves, which are unknown, in the
+    /// east half.
+    #[expect(clippy::integer_division, reason = "The quotients are layers of blocks and rows of biomes.")]
+    fn test_chunk(column_position: IVec2, data_version: i32) -> NbtCompound {
+        let mut bottom_section = NbtCompound::default();
+        bottom_section.insert("Y", NbtTag::Byte(-4));
+        bottom_section.insert("block_states", palette_container(vec![block_state("minecraft:bedrock")], None));
+        let mut layered_section = NbtCompound::default();
+        layered_section.insert("Y", NbtTag::Byte(4));
+        let layers: Vec<_> = (0..SECTION_VOLUME as u64).map(|index| index / 256 % 4).collect();
+        let palette = ["minecraft:air", "minecraft:stone", "minecraft:deepslate", "minecraft:lava"].map(block_state).to_vec();
+        layered_section.insert("block_states", palette_container(palette, Some(pack_indices(&layers, 4))));
+        let biomes: Vec<_> = (0..64).map(|index| if index % 4 < 2 { 0 } else { 1 + index / 4 % 2 }).collect();
+        let biome_palette = ["minecraft:plains", "minecraft:desert", "minecraft:cherry_grove"].map(|name| NbtTag::String(name.to_owned())).to_vec();
+        layered_section.insert("biomes", palette_container(biome_palette, Some(pack_indices(&biomes, 2))));
+        let mut chunk = NbtCompound::default();
+        chunk.insert("DataVersion", NbtTag::Int(data_version));
+        chunk.insert("xPos", NbtTag::Int(column_position.x));
+        chunk.insert("zPos", NbtTag::Int(column_position.y));
+        chunk.insert("Status", NbtTag::String("minecraft:full".to_owned()));
+        chunk.insert("sections", NbtTag::List(vec![NbtTag::Compound(bottom_section), NbtTag::Compound(layered_section)]));
+        chunk
+    }
+
+    /// Makes a region file with `chunks`, each compressed the way its
+    /// compression type says.
+    #[expect(clippy::integer_division, reason = "Chunks start at whole sectors.")]
+    fn region_file(chunks: &[(IVec2, u8, NbtCompound)]) -> Vec<u8> {
+        let mut bytes = vec![0; ANVIL_HEADER_SIZE];
+        for (column_position, compression, chunk) in chunks {
+            let nbt = write_nbt("", chunk);
+            let compressed = match compression {
+                1 => {
+                    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+                    encoder.write_all(&nbt).expect("The chunk should be compressed.");
+                    encoder.finish().expect("The chunk should be compressed.")
+                }
+                2 => {
+                    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+                    encoder.write_all(&nbt).expect("The chunk should be compressed.");
+                    encoder.finish().expect("The chunk should be compressed.")
+                }
+                _ => nbt
+            };
+            let sector = bytes.len() / ANVIL_SECTOR_SIZE;
+            let sector_count = (compressed.len() + 5).div_ceil(ANVIL_SECTOR_SIZE);
+            let location_index = (column_position.y & 31).cast_unsigned() as usize * ANVIL_REGION_SIZE + (column_position.x & 31).cast_unsigned() as usize;
+            #[expect(clippy::cast_possible_truncation, reason = "Test regions are small.")]
+            bytes[location_index * 4..location_index * 4 + 4].copy_from_slice(&(((sector as u32) << 8) | sector_count as u32).to_be_bytes());
+            #[expect(clippy::cast_possible_truncation, reason = "Test chunks are small.")]
+            bytes.extend_from_slice(&(compressed.len() as u32 + 1).to_be_bytes());
+            bytes.push(*compression);
+            bytes.extend_from_slice(&compressed);
+            bytes.resize(bytes.len().next_multiple_of(ANVIL_SECTOR_SIZE), 0);
+        }
+        bytes
+    }
+
+    #[test]
+    fn chunks_are_imported_with_their_blocks_mapped_by_name() {
+        let asset_cache = AssetCache::new(ASSETS_PATH).expect("The assets directory should exist.");
+        let block_definitions = asset_cache.load::<BlockDefinitions>(BLOCKS_ASSET_ID).expect("The block definitions should be loaded.");
+        let block_registry = BlockRegistry::new(&block_definitions.read()).expect("The block registry should be created.");
+        let biome_definitions = asset_cache.load::<BiomeDefinitions>(BIOMES_ASSET_ID).expect("The biome definitions should be loaded.").read();
+        let mappings = asset_cache.load::<AnvilBlockMappings>(ANVIL_BLOCK_MAPPINGS_ASSET_ID).expect("The block mappings should be loaded.").read();
+        let mut importer = AnvilImporter::new(&mappings, &block_registry, &biome_definitions);
+        drop(mappings);
+        let block = |path| block_registry.id(&BlockName::new(BlockName::DEFAULT_NAMESPACE, path).expect("The name should be valid.")).expect("The block should be registered.");
+        let biome = |name| biome_definitions.iter().find(|(_, biome_name, _)| *biome_name == name).map(|(id, _, _)| id).expect("The biome should exist.");
+        let region = region_file(&[
+            (IVec2::new(-32, 5), 2, test_chunk(IVec2::new(-32, 5), 3955)),
+            (IVec2::new(-1, 6), 1, test_chunk(IVec2::new(-1, 6), 3955)),
+            (IVec2::new(-2, 6), 3, test_chunk(IVec2::new(-2, 6), 1343))
+        ]);
+        let columns = importer.import_region(&region).expect("The region should be imported.");
+        let report = importer.finish();
+        assert_eq!(columns.iter().map(|(column_position, _)| *column_position).collect::<Vec<_>>(), [IVec2::new(-32, 5), IVec2::new(-1, 6)]);
+        assert_eq!((report.imported_columns, report.skipped_columns), (2, 1), "The chunk from before 1.18 should be skipped.");
+        for (_, column) in &columns {
+            assert_eq!(column.get_block(IVec3::new(3, COLUMN_MIN_Y, 9)), Some(block("bedrock")));
+            assert_eq!(column.get_block(IVec3::new(3, 64, 9)), Some(BlockId::AIR));
+            assert_eq!(column.get_block(IVec3::new(15, 65, 0)), Some(block("stone")));
+            assert_eq!(column.get_block(IVec3::new(0, 66, 15)), Some(block("stone")), "Deepslate should be mapped to stone.");
+            assert_eq!(column.get_block(IVec3::new(7, 67, 7)), Some(BlockId::AIR), "Unknown blocks should become air.");
+            assert_eq!(column.biome(UVec2::new(7, 0)), biome("plains"));
+            assert_eq!(column.biome(UVec2::new(8, 0)), biome("desert"));
+            assert_eq!(column.biome(UVec2::new(8, 4)), BiomeId::default(), "Unknown biomes should become the biome with the ID 0.");
+        }
+        assert_eq!(report.unknown_blocks, BTreeMap::from([("minecraft:lava".to_owned(), 2 * 4 * 256)]));
+        assert_eq!(report.unknown_biomes, BTreeMap::from([("minecraft:cherry_grove".to_owned(), 2 * 8 * 8)]));
+    }
+}
@@ -1,9 +1,7 @@
-use bevy_ecs::schedule::*;
 use bevy_ecs::system::*;
 use bevy_ecs::world::*;
 use egui_wgpu::*;
 use std::fmt::Debug;
-use std::iter::*;
 use std::sync::*;
 use thiserror::*;
 use futures::executor::*;
@@ -14,9 +12,14 @@ use winit::dpi::*;
 use winit::error::*;
 use winit::event_loop::*;
 use winit::window::*;
+use winit::event::ElementState;
 use winit::event::WindowEvent::{self, *};
+use winit::keyboard::*;
 use wgpu::*;
+use crate::constants::*;
+use crate::gpu::*;
 use crate::resources::egui::*;
+use crate::resources::screenshot::*;
 use crate::resources::wgpu::*;
 use crate::resources::winit::*;
 use crate::schedules::*;
@@ -25,10 +28,7 @@ use crate::world::*;
 /// An implementation of [`ApplicationHandler`] that manages the states of the app and the GPU.
 pub struct App {
     world: World,
-    startup_schedule: Schedule,
-    update_schedule: Schedule,
-    post_update_schedule: Schedule,
-    render_schedule: Schedule,
+    schedules: AppSchedules,
     is_initialized: bool
 }
 
@@ -36,10 +36,7 @@ impl App {
     pub fn new() -> Result<Self, WorldInitializationError> {
         Ok(Self {
             world: create_main_world()?,
-            startup_schedule: StartupSchedule::create_schedule(),
-            update_schedule: UpdateSchedule::create_schedule(),
-            post_update_schedule: PostUpdateSchedule::create_schedule(),
-            render_schedule: RenderSchedule::create_schedule(),
+            schedules: AppSchedules::new(),
             is_initialized: false
         })
     }
@@ -56,16 +53,22 @@ impl App {
             return Ok(());
         }
         let window = Self::create_window(event_loop)?;
-        let wgpu_instance = Self::create_instance();
+        let wgpu_instance = create_instance(Backends::PRIMARY);
         let surface = Self::create_surface(&wgpu_instance, Arc::clone(&window))?;
-        let adapter = Self::create_adapter(&wgpu_instance, &surface).await?;
-        let (device, command_queue) = Self::create_device_and_queue(&adapter).await?;
+        let adapter = create_adapter(&wgpu_instance, Some(&surface), false).await?;
+        let (device, command_queue) = create_device_and_queue(&adapter).await?;
         let surface_config = Self::create_surface_config(&surface, &adapter, &window)?;
         surface.configure(&device, &surface_config);
         info!("Surface configured.");
-        self.world.insert_resource(WgpuResource { command_queue, surface, surface_config, device });
+        let depth_texture = DepthTexture::new(&device, DEPTH_TEXTURE_FORMAT, surface_config.width, surface_config.height);
+        self.world.insert_resource(WgpuResource {
+            render_target: RenderTarget::Surface { surface, surface_config },
+            depth_texture,
+            device,
+            command_queue
+        });
         self.world.insert_resource(WinitResource { window });
-        self.startup_schedule.run(&mut self.world);
+        self.schedules.run_startup(&mut self.world);
         self.is_initialized = true;
         Ok(())
     }
@@ -84,14 +87,6 @@ impl App {
         }
     }
 
-    fn create_instance() -> Instance {
-        Instance::new(&InstanceDescriptor {
-            backends: Backends::PRIMARY,
-            flags: InstanceFlags::from_env_or_default(),
-            ..Default::default()
-        })
-    }
-
     fn create_surface<'window>(instance: &Instance, window: impl Into<SurfaceTarget<'window>>) -> Result<Surface<'window>, AppInitializationError> {
         match instance.create_surface(window) {
             Ok(surface) => {
@@ -105,42 +100,6 @@ impl App {
         }
     }
 
-    async fn create_adapter(instance: &Instance, surface: &Surface<'_>) -> Result<Adapter, AppInitializationError> {
-        match instance.request_adapter(&RequestAdapterOptions {
-            power_preference: PowerPreference::HighPerformance,
-            compatible_surface: Some(surface),
-            force_fallback_adapter: false
-        }).await {
-            Ok(adapter) => {
-                info!("The adapter has been created. {adapter:#?}");
-                info!("Adapter limits: {:#?}", adapter.limits());
-                info!("Adapter features: {:#?}", adapter.features());
-                Ok(adapter)
-            }
-            Err(err) => {
-                error!("The adapter could not be created. {err:#?}");
-                Err(AppInitializationError::RequestAdapter(err))
-            }
-        }
-    }
-
-    async fn create_device_and_queue(adapter: &Adapter) -> Result<(Device, Queue), AppInitializationError> {
-        match adapter.request_device(&DeviceDescriptor {
-            label: Some("default-device"),
-            required_limits: adapter.limits(),
-            ..Default::default()
-        }).await {
-            Ok(val) => {
-                info!("The device and command queue has been created. {:#?}, {:#?}", val.0, val.1);
-                Ok(val)
-            },
-            Err(err) => {
-                error!("The device and command queue could not be created. {err:#?}");
-                Err(AppInitializationError::RequestDevice(err))
-            }
-        }
-    }
-
     fn create_surface_config(surface: &Surface<'_>, adapter: &Adapter, window: &Window) -> Result<SurfaceConfiguration, AppInitializationError> {
         let surface_capabilities = surface.get_capabilities(adapter);
         if surface_capabilities.formats.is_empty() {
@@ -161,7 +120,9 @@ impl App {
         let alpha_mode = CompositeAlphaMode::Auto;
         info!("Supported alpha modes: {:#?}", surface_capabilities.alpha_modes);
         info!("The alpha mode {alpha_mode:#?} has been chosen.");
-        let surface_usages = TextureUsages::RENDER_ATTACHMENT;
+        // COPY_SRC is needed to take screenshots, but not every platform
+        // supports it.
+        let surface_usages = TextureUsages::RENDER_ATTACHMENT | (surface_capabilities.usages & TextureUsages::COPY_SRC);
         info!("Supported surface usages: {:#?}", surface_capabilities.usages);
         info!("The surface usages {surface_usages:#?} have been chosen.");
         Ok(SurfaceConfiguration {
@@ -180,10 +141,7 @@ impl App {
         if !self.is_initialized {
             return;
         }
-        let wgpu_resource: &mut WgpuResource = &mut self.world.resource_mut();
-        wgpu_resource.surface_config.width = new_size.width;
-        wgpu_resource.surface_config.height = new_size.height;
-        wgpu_resource.surface.configure(&wgpu_resource.device, &wgpu_resource.surface_config);
+        self.world.resource_mut::<WgpuResource>().resize(new_size.width, new_size.height);
         info!("Resized the window to {new_size:#?}");
     }
 
@@ -192,50 +150,25 @@ impl App {
             return Ok(());
         }
         let wgpu_resource: &WgpuResource = self.world.resource();
-        let output_surface_texture = match wgpu_resource.surface.get_current_texture() {
+        let RenderTarget::Surface { surface, .. } = &wgpu_resource.render_target else {
+            error!("The App is rendering to an offscreen texture, which cannot be presented to the window.");
+            return Err(RenderError::NoSurface);
+        };
+        let output_surface_texture = match surface.get_current_texture() {
             Ok(output_surface_texture) => output_surface_texture,
             Err(err) => {
                 error!("`get_current_texture` failed when rendering. {err:#?}");
                 return Err(RenderError::Surface(err))
             }
         };
-        let output_surface_texture_view = output_surface_texture.texture.create_view(&TextureViewDescriptor {
-            label: Some("main-surface-texture-view"),
-            ..Default::default()
-        });
-        let mut command_encoder = wgpu_resource.device.create_command_encoder(&CommandEncoderDescriptor {
-            label: Some("main-command-encoder")
-        });
-        let render_pass = command_encoder.begin_render_pass(&RenderPassDescriptor {
-            label: Some("main-render-pass"),
-            color_attachments: &[Some(RenderPassColorAttachment {
-                view: &output_surface_texture_view,
-                resolve_target: None,
-                ops: Operations {
-                    load: LoadOp::Clear(Color::default()),
-                    store: StoreOp::Store
-                }
-            })],
-            ..Default::default()
-        });
-        drop(render_pass); // Drops render_pass so command_encoder can be moved below.
-        // Acquires WgpuResource again. This somehow solves a double mutable
-        // reference error.
-        let wgpu_resource_2: &WgpuResource = self.world.resource();
-        wgpu_resource_2.command_queue.submit(once(command_encoder.finish()));
-        self.world.insert_resource(WgpuFrameResource { output_surface_texture });
-        self.update_schedule.run(&mut self.world);
-        self.post_update_schedule.run(&mut self.world);
-        self.render_schedule.run(&mut self.world);
+        self.world.insert_resource(WgpuFrameResource { output_texture: FrameOutputTexture::Surface(output_surface_texture) });
+        self.schedules.run_frame(&mut self.world);
         let winit_resource: &WinitResource = self.world.resource();
         winit_resource.window.pre_present_notify();
-        let returned_output_surface_texture = if let Some(wgpu_frame_resource) = self.world.remove_resource::<WgpuFrameResource>() {
-                wgpu_frame_resource.output_surface_texture
-            }
-            else {
-                error!("Could not retrieve the SurfaceTexture to present after MainSchedule finished.");
-                return Err(RenderError::CouldNotPresent)
-            };
+        let Some(WgpuFrameResource { output_texture: FrameOutputTexture::Surface(returned_output_surface_texture) }) = self.world.remove_resource::<WgpuFrameResource>() else {
+            error!("Could not retrieve the SurfaceTexture to present after MainSchedule finished.");
+            return Err(RenderError::CouldNotPresent)
+        };
         returned_output_surface_texture.present();
         Ok(())
     }
@@ -278,6 +211,9 @@ impl ApplicationHandler for App {
             Resized(new_size) => {
                 self.resize(new_size);
             }
+            KeyboardInput { event: key_event, .. } if key_event.physical_key == PhysicalKey::Code(SCREENSHOT_KEY) && key_event.state == ElementState::Pressed && !key_event.repeat => {
+                self.world.resource_mut::<ScreenshotResource>().request();
+            }
             RedrawRequested => {
                 winit_resource.window.request_redraw(); // Can't be below self.render because that would create two mutable references.
                 match self.render() {
@@ -296,6 +232,7 @@ impl ApplicationHandler for App {
     }
 
     fn exiting(&mut self, _: &ActiveEventLoop) {
+        self.schedules.run_shutdown(&mut self.world);
         // Removes these three resources in this very particular order to
         // prevent a segfault.
         if let Some(egui_renderer_resource) = self.world.remove_resource::<EguiRendererResource>() {
@@ -333,6 +270,9 @@ enum RenderError {
     #[error(transparent)]
     Surface(#[from] SurfaceError),
 
+    #[error("The App is rendering to an offscreen texture, which cannot be presented to the window.")]
+    NoSurface,
+
     #[error("Could not retrieve the SurfaceTexture to present after MainSchedule finished.")]
     CouldNotPresent
 }
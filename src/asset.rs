@@ -1,5 +1,6 @@
 use assets_manager::*;
 use derive_more::*;
+use std::ops::Deref;
 use std::sync::*;
 
 /// A handle to a asset so that one can be shared by multiple entities. "Static"
@@ -13,3 +14,97 @@ pub enum AssetHandle<T: ?Sized> {
     /// A handle to a dynamic asset that is stored on the heap.
     Dynamic(Arc<T>)
 }
+
+impl<T: ?Sized> AssetHandle<T> {
+    /// Locks the asset for reading. For [`AssetHandle::Static`], this may block
+    /// if the asset is being hot-reloaded.
+    pub fn read(&self) -> AssetHandleReadGuard<'_, T> {
+        match self {
+            Self::Static(handle) => AssetHandleReadGuard::Static(handle.read()),
+            Self::Dynamic(arc) => AssetHandleReadGuard::Dynamic(arc)
+        }
+    }
+
+    /// Returns the [`ReloadId`] of the last time the asset was hot-reloaded.
+    /// This is always [`ReloadId::NEVER`] for [`AssetHandle::Dynamic`],
+    /// because dynamic assets cannot be reloaded.
+    pub fn last_reload_id(&self) -> ReloadId {
+        match self {
+            Self::Static(handle) => handle.last_reload_id(),
+            Self::Dynamic(_) => ReloadId::NEVER
+        }
+    }
+
+    /// Creates a [`WeakAssetHandle`] that points to the same asset.
+    pub fn downgrade(&self) -> WeakAssetHandle<T> {
+        match self {
+            Self::Static(handle) => WeakAssetHandle::Static(handle.weak()),
+            Self::Dynamic(arc) => WeakAssetHandle::Dynamic(Arc::downgrade(arc))
+        }
+    }
+
+    /// Returns a key that identifies the asset this handle points to. Two
+    /// handles have the same key if and only if they point to the same asset,
+    /// so this can be used to share GPU resources created from the same asset.
+    pub fn key(&self) -> AssetHandleKey {
+        match self {
+            Self::Static(handle) => AssetHandleKey::Static(handle.id().clone()),
+            Self::Dynamic(arc) => AssetHandleKey::Dynamic(Arc::as_ptr(arc).cast::<()>() as usize)
+        }
+    }
+}
+
+impl<T: ?Sized> Clone for AssetHandle<T> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Static(handle) => Self::Static(handle.clone()),
+            Self::Dynamic(arc) => Self::Dynamic(Arc::clone(arc))
+        }
+    }
+}
+
+/// A non-owning version of [`AssetHandle`], used to find out if an asset is
+/// still alive without keeping it alive.
+pub enum WeakAssetHandle<T: ?Sized> {
+    Static(WeakHandle<T>),
+    Dynamic(Weak<T>)
+}
+
+impl<T: ?Sized> WeakAssetHandle<T> {
+    /// Returns `true` if there are still [`AssetHandle`]s pointing to the
+    /// asset. Static assets are kept alive by their [`AssetCache`], so they
+    /// are always alive as long as the [`AssetCache`] is.
+    pub fn is_alive(&self) -> bool {
+        match self {
+            Self::Static(handle) => handle.strong_count() != 0,
+            Self::Dynamic(weak) => weak.strong_count() != 0
+        }
+    }
+}
+
+/// The RAII guard returned by [`AssetHandle::read`].
+pub enum AssetHandleReadGuard<'a, T: ?Sized> {
+    Static(AssetReadGuard<'a, T>),
+    Dynamic(&'a T)
+}
+
+impl<T: ?Sized> Deref for AssetHandleReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::Static(guard) => guard,
+            Self::Dynamic(value) => value
+        }
+    }
+}
+
+/// Identifies the asset an [`AssetHandle`] points to. See [`AssetHandle::key`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum AssetHandleKey {
+    /// The ID of the asset in the [`AssetCache`].
+    Static(SharedString),
+    /// The address of the asset on the heap. This is only unique as long as
+    /// the asset is alive.
+    Dynamic(usize)
+}
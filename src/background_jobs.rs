@@ -0,0 +1,176 @@
+//! Contains [`BackgroundJobs`], which runs CPU-heavy or blocking work such as
+//! chunk meshing on the blocking thread pool of the tokio runtime that `main`
+//! runs under, so that it neither stalls frames nor starves async tasks.
+
+use futures::*;
+use log::*;
+use std::collections::*;
+use std::hash::*;
+use std::mem;
+use std::thread;
+use tokio::runtime::*;
+use tokio::task::{self, JoinHandle};
+
+/// How many jobs [`BackgroundJobs::with_default_limit`] allows per CPU core.
+/// More than 1 keeps every core busy between frames, even while some jobs wait
+/// on I/O, while still limiting how many jobs can be outdated by the time they
+/// run.
+const JOBS_PER_CPU_CORE: usize = 4;
+
+/// A set of jobs running on the blocking thread pool of a tokio [`Runtime`],
+/// each identified by a key such as the position of the section it works on.
+/// At most one job runs per key, and at most `max_in_flight_jobs` given to
+/// [`Self::new`] run at once, so that a burst of work cannot queue up more jobs
+/// than the runtime can finish in time. Cancelled jobs that had already started
+/// count towards that limit until they finish.
+///
+/// Jobs are meant to work on copies of the data they need, so that they never
+/// touch the [`bevy_ecs::world::World`]. Their results are collected with
+/// [`Self::take_finished`] by a system, which applies them to the
+/// [`bevy_ecs::world::World`].
+pub struct BackgroundJobs<K, T> {
+    runtime: Handle,
+    max_in_flight_jobs: usize,
+    jobs: HashMap<K, JoinHandle<T>>,
+    /// Jobs that have been cancelled but may still be running, since blocking
+    /// code cannot be interrupted.
+    cancelled_jobs: Vec<JoinHandle<T>>
+}
+
+impl<K: Copy + Eq + Hash, T: Send + 'static> BackgroundJobs<K, T> {
+    pub fn new(runtime: Handle, max_in_flight_jobs: usize) -> Self {
+        Self { runtime, max_in_flight_jobs, jobs: HashMap::new(), cancelled_jobs: Vec::new() }
+    }
+
+    /// Allows [`JOBS_PER_CPU_CORE`] jobs per CPU core.
+    pub fn with_default_limit(runtime: Handle) -> Self {
+        Self::new(runtime, thread::available_parallelism().map_or(1, usize::from) * JOBS_PER_CPU_CORE)
+    }
+
+    /// How many more jobs can be spawned before reaching the limit of jobs
+    /// that run at once.
+    pub fn free_slots(&self) -> usize {
+        let running_cancelled_job_count = self.cancelled_jobs.iter().filter(|job| !job.is_finished()).count();
+        self.max_in_flight_jobs.saturating_sub(self.jobs.len() + running_cancelled_job_count)
+    }
+
+    /// Returns `true` if no jobs are running or waiting to be collected, not
+    /// counting cancelled ones.
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+
+    /// Returns `true` if a job with `key` is running or waiting to be
+    /// collected.
+    pub fn contains(&self, key: &K) -> bool {
+        self.jobs.contains_key(key)
+    }
+
+    /// Starts running `job` on the blocking thread pool of the runtime, so that
+    /// long synchronous work such as generating terrain or writing regions
+    /// does not hold up the async tasks on the worker threads. If a job with
+    /// the same key is already running, it is cancelled first because its
+    /// result would be outdated. This does not check [`Self::free_slots`].
+    pub fn spawn(&mut self, key: K, job: impl FnOnce() -> T + Send + 'static) {
+        self.cancel(&key);
+        self.jobs.insert(key, self.runtime.spawn_blocking(job));
+    }
+
+    /// Cancels the job with `key`, if any. A job that has not started yet is
+    /// never run, while one that has runs to completion since blocking code
+    /// cannot be interrupted, and its result is discarded. Returns `true` if
+    /// there was a job to cancel.
+    pub fn cancel(&mut self, key: &K) -> bool {
+        let Some(job) = self.jobs.remove(key) else {
+            return false;
+        };
+        job.abort();
+        self.cancelled_jobs.push(job);
+        true
+    }
+
+    /// Cancels every job whose key does not satisfy `predicate`.
+    pub fn retain(&mut self, mut predicate: impl FnMut(&K) -> bool) {
+        for (_, job) in self.jobs.extract_if(|key, _| !predicate(key)) {
+            job.abort();
+            self.cancelled_jobs.push(job);
+        }
+    }
+
+    /// Removes the jobs that have finished and returns their results. Jobs
+    /// that panicked are logged and left out.
+    pub fn take_finished(&mut self) -> Vec<(K, T)> {
+        self.cancelled_jobs.retain(|job| !job.is_finished());
+        let mut results = Vec::new();
+        for (key, job) in self.jobs.extract_if(|_, job| job.is_finished()) {
+            match job.now_or_never() {
+                Some(Ok(result)) => results.push((key, result)),
+                Some(Err(err)) => error!("A background job panicked. {err}"),
+                None => error!("A background job finished, but its result is not available.")
+            }
+        }
+        results
+    }
+
+    /// Blocks until every job has finished, including cancelled ones that had
+    /// already started, and returns the results of the jobs that were not
+    /// cancelled like [`Self::take_finished`]. This stalls the calling thread,
+    /// so it should only be used when there is nothing else to do, such as
+    /// when exiting.
+    pub fn wait_all(&mut self) -> Vec<(K, T)> {
+        let jobs: Vec<_> = self.jobs.drain().collect();
+        let cancelled_jobs = mem::take(&mut self.cancelled_jobs);
+        task::block_in_place(|| executor::block_on(async {
+            // Their results and cancellation errors are not needed.
+            future::join_all(cancelled_jobs).await;
+            let mut results = Vec::new();
+            for (key, job) in jobs {
+                match job.await {
+                    Ok(result) => results.push((key, result)),
+                    Err(err) => error!("A background job panicked. {err}")
+                }
+            }
+            results
+        }))
+    }
+}
+
+impl<K, T> Drop for BackgroundJobs<K, T> {
+    fn drop(&mut self) {
+        for job in self.jobs.values() {
+            job.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::time::*;
+
+    #[test]
+    fn cancelled_jobs_keep_their_slot_until_they_finish() {
+        let runtime = Runtime::new().expect("The runtime should be created.");
+        let mut jobs = BackgroundJobs::new(runtime.handle().clone(), 1);
+        let (started_sender, started_receiver) = mpsc::channel();
+        let (finish_sender, finish_receiver) = mpsc::channel::<()>();
+        jobs.spawn(0, move || {
+            started_sender.send(()).expect("The test should be waiting for the job to start.");
+            assert!(matches!(finish_receiver.recv(), Err(mpsc::RecvError)), "The test should only drop the sender.");
+        });
+        started_receiver.recv().expect("The job should start.");
+        assert!(jobs.cancel(&0));
+        assert!(jobs.is_empty());
+        assert_eq!(jobs.free_slots(), 0, "A cancelled job that is still running should keep its slot.");
+
+        drop(finish_sender);
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while jobs.free_slots() == 0 {
+            assert!(Instant::now() < deadline, "The cancelled job should finish.");
+            thread::yield_now();
+        }
+        assert!(jobs.take_finished().is_empty(), "The result of a cancelled job should be discarded.");
+        assert_eq!(jobs.free_slots(), 1, "The slot should be free once the cancelled job has finished.");
+    }
+}
@@ -0,0 +1,160 @@
+//! Contains biome-related definitions. Biomes are loaded from RON files in the
+//! `assets/biomes` directory, and each [`crate::voxel_world::ChunkColumn`]
+//! stores the [`BiomeId`] of every column of blocks in it.
+
+use assets_manager::*;
+use assets_manager::asset::*;
+use log::*;
+use serde::*;
+use std::borrow::*;
+use thiserror::*;
+use crate::block::*;
+
+/// The ID of the directory in the [`AssetCache`] that [`BiomeDefinitions`] are
+/// loaded from.
+pub const BIOMES_ASSET_ID: &str = "biomes";
+
+/// Identifies a biome in chunk storage. Unlike [`BlockId`]s, biome IDs are
+/// simply the indices of the biomes in [`BiomeDefinitions`], which is fine as
+/// long as the same [`BiomeDefinitions`] are used for as long as any columns
+/// that store them are loaded.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BiomeId(pub u8);
+
+/// The properties of a biome. Each one is loaded from a RON file in the
+/// `assets/biomes` directory, and is named after the file.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BiomeDefinition {
+    /// How warm the biome is, from -1 to 1. Each place gets the biome whose
+    /// temperature and humidity are the closest to its own.
+    pub temperature: f64,
+    /// How wet the biome is, from -1 to 1.
+    pub humidity: f64,
+    /// The topmost block of land above sea level, such as grass.
+    pub surface_block: BlockName,
+    /// The blocks under [`Self::surface_block`], such as dirt.
+    pub filler_block: BlockName,
+    /// How many [`Self::filler_block`]s there are under the surface before the
+    /// terrain turns into stone.
+    pub filler_depth: u8,
+    /// Replaces both [`Self::surface_block`] and [`Self::filler_block`] on
+    /// beaches and sea floors, such as sand.
+    pub shore_block: BlockName,
+    /// Added to the height of the surface, in blocks.
+    #[serde(default)]
+    pub height_offset: f64,
+    /// Multiplies how high land rises above sea level and how rugged it is.
+    #[serde(default = "default_height_scale")]
+    pub height_scale: f64,
+    /// The structures, such as trees, that are placed on the surface of the
+    /// biome.
+    #[serde(default)]
+    pub features: Vec<BiomeFeature>
+}
+
+/// A structure that is placed on the surface of a biome.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BiomeFeature {
+    /// The name of a [`crate::structure::StructureTemplate`].
+    pub structure: String,
+    /// The average number of times the structure is placed in a column that
+    /// is entirely in the biome. The fractional part is the chance of one
+    /// more.
+    pub per_column: f64,
+    /// The blocks that the structure can stand on.
+    pub ground_blocks: Vec<BlockName>
+}
+
+const fn default_height_scale() -> f64 {
+    1.0
+}
+
+impl BiomeDefinition {
+    fn validate(&self) -> Result<(), BiomeDefinitionError> {
+        for climate in [self.temperature, self.humidity] {
+            if !(-1.0..=1.0).contains(&climate) {
+                return Err(BiomeDefinitionError::ClimateOutOfRange(climate));
+            }
+        }
+        if !self.height_offset.is_finite() {
+            return Err(BiomeDefinitionError::InvalidHeightOffset(self.height_offset));
+        }
+        if !self.height_scale.is_finite() || self.height_scale.is_sign_negative() {
+            return Err(BiomeDefinitionError::InvalidHeightScale(self.height_scale));
+        }
+        if let Some(feature) = self.features.iter().find(|feature| !feature.per_column.is_finite() || feature.per_column.is_sign_negative()) {
+            return Err(BiomeDefinitionError::InvalidFeatureFrequency(feature.per_column));
+        }
+        Ok(())
+    }
+}
+
+impl FileAsset for BiomeDefinition {
+    const EXTENSION: &'static str = "ron";
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Result<Self, BoxedError> {
+        let definition: Self = load_ron(&bytes)?;
+        if let Err(err) = definition.validate() {
+            error!("{err}");
+            return Err(err.into());
+        }
+        Ok(definition)
+    }
+}
+
+/// Every [`BiomeDefinition`] directly inside a directory, sorted by name and
+/// indexed by [`BiomeId`]. There must be at least one.
+pub struct BiomeDefinitions {
+    definitions: Vec<(String, BiomeDefinition)>
+}
+
+impl BiomeDefinitions {
+    /// Iterates over the definitions in order of their IDs, along with their
+    /// names.
+    pub fn iter(&self) -> impl Iterator<Item = (BiomeId, &str, &BiomeDefinition)> {
+        (0..=u8::MAX).map(BiomeId).zip(&self.definitions).map(|(id, (name, definition))| (id, name.as_str(), definition))
+    }
+}
+
+impl Asset for BiomeDefinitions {
+    fn load(cache: &AssetCache, id: &SharedString) -> Result<Self, BoxedError> {
+        info!("Loading biome definitions: {id}");
+        let directory = cache.load_dir::<BiomeDefinition>(id)?;
+        let mut definitions = Vec::new();
+        for asset_id in directory.read().ids() {
+            let name = asset_id.rsplit_once('.').map_or(asset_id.as_str(), |(_, name)| name);
+            definitions.push((name.to_owned(), cache.load::<BiomeDefinition>(asset_id)?.cloned()));
+        }
+        if definitions.is_empty() || definitions.len() > usize::from(u8::MAX) + 1 {
+            let err = BiomeDefinitionsError::InvalidBiomeCount(definitions.len());
+            error!("Error while loading biome definitions {id}: {err}");
+            return Err(err.into());
+        }
+        definitions.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        info!("Loaded {} biome definitions.", definitions.len());
+        Ok(Self { definitions })
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum BiomeDefinitionError {
+    #[error("The temperature or humidity {0} is not between -1 and 1.")]
+    ClimateOutOfRange(f64),
+
+    #[error("The height offset {0} is not a finite number.")]
+    InvalidHeightOffset(f64),
+
+    #[error("The height scale {0} is not a finite non-negative number.")]
+    InvalidHeightScale(f64),
+
+    #[error("The number of features per column {0} is not a finite non-negative number.")]
+    InvalidFeatureFrequency(f64)
+}
+
+#[derive(Debug, Error)]
+pub enum BiomeDefinitionsError {
+    #[error("There are {0} biome definitions, but there must be between 1 and 256.")]
+    InvalidBiomeCount(usize)
+}
@@ -0,0 +1,322 @@
+//! Contains block-related definitions.
+
+use assets_manager::*;
+use assets_manager::asset::*;
+use glam::*;
+use log::*;
+use serde::*;
+use std::borrow::*;
+use std::fmt::Display;
+use std::fmt;
+use std::str::*;
+use strum::*;
+use thiserror::*;
+
+/// Identifies a kind of block, such as stone or dirt, in chunk storage. See
+/// [`crate::voxel_world`]. Which kind of block an ID refers to is decided by
+/// [`crate::block_registry::BlockRegistry`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BlockId(pub u16);
+
+impl BlockId {
+    /// Air is always 0 so that newly created sections are empty.
+    pub const AIR: Self = Self(0);
+
+    pub const fn is_air(self) -> bool {
+        self.0 == Self::AIR.0
+    }
+}
+
+/// A namespaced name of a kind of block, such as `mycraft:stone`. The namespace
+/// lets mods add blocks without their names conflicting with each other. The
+/// namespace may only contain lowercase ASCII letters, digits and underscores,
+/// and the path may additionally contain slashes.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct BlockName(String);
+
+impl BlockName {
+    /// The namespace of the blocks that come with the game.
+    pub const DEFAULT_NAMESPACE: &str = "mycraft";
+
+    pub fn new(namespace: &str, path: &str) -> Result<Self, BlockNameParseError> {
+        format!("{namespace}:{path}").parse()
+    }
+
+    /// The name of [`BlockId::AIR`].
+    pub fn air() -> Self {
+        Self(format!("{}:air", Self::DEFAULT_NAMESPACE))
+    }
+
+    pub fn namespace(&self) -> &str {
+        self.0.split_once(':').unwrap_or_default().0
+    }
+
+    pub fn path(&self) -> &str {
+        self.0.split_once(':').unwrap_or_default().1
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for BlockName {
+    type Err = BlockNameParseError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let Some((namespace, path)) = name.split_once(':') else {
+            return Err(BlockNameParseError::MissingNamespace(name.to_owned()));
+        };
+        let is_valid_namespace_char = |char: char| char.is_ascii_lowercase() || char.is_ascii_digit() || char == '_';
+        if namespace.is_empty() || !namespace.chars().all(is_valid_namespace_char) {
+            return Err(BlockNameParseError::InvalidNamespace(name.to_owned()));
+        }
+        if path.is_empty() || path.starts_with('/') || path.ends_with('/') || !path.chars().all(|char| is_valid_namespace_char(char) || char == '/') {
+            return Err(BlockNameParseError::InvalidPath(name.to_owned()));
+        }
+        Ok(Self(name.to_owned()))
+    }
+}
+
+impl TryFrom<String> for BlockName {
+    type Error = BlockNameParseError;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        name.parse()
+    }
+}
+
+impl From<BlockName> for String {
+    #[inline]
+    fn from(name: BlockName) -> Self {
+        name.0
+    }
+}
+
+impl Display for BlockName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// The properties of a kind of block. Each one is loaded from a RON file in the
+/// `assets/blocks` directory; see [`crate::block_registry::BlockDefinitions`].
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BlockDefinition {
+    pub textures: BlockTextures,
+    pub opacity: BlockOpacity,
+    /// Whether entities collide with the block.
+    #[expect(dead_code, reason = "There are no entities to collide with blocks yet.")]
+    pub is_solid: bool,
+    /// The level of light the block emits, from 0 to [`MAX_LIGHT_LEVEL`].
+    #[serde(default)]
+    pub light_emission: u8,
+    /// How long the block takes to break. `inf` makes it unbreakable.
+    pub hardness: f32
+}
+
+/// The highest level of light a block can emit or be lit by.
+pub const MAX_LIGHT_LEVEL: u8 = 15;
+
+impl BlockDefinition {
+    /// The definition of [`BlockId::AIR`], which cannot be changed by files.
+    pub const fn air() -> Self {
+        Self {
+            textures: BlockTextures::None,
+            opacity: BlockOpacity::Invisible,
+            is_solid: false,
+            light_emission: 0,
+            hardness: 0.0
+        }
+    }
+
+    const fn validate(&self) -> Result<(), BlockDefinitionError> {
+        if self.light_emission > MAX_LIGHT_LEVEL {
+            return Err(BlockDefinitionError::LightEmissionTooHigh(self.light_emission));
+        }
+        if self.hardness.is_nan() || self.hardness.is_sign_negative() {
+            return Err(BlockDefinitionError::InvalidHardness(self.hardness));
+        }
+        if matches!(self.textures, BlockTextures::None) != matches!(self.opacity, BlockOpacity::Invisible) {
+            return Err(BlockDefinitionError::TexturesMismatchOpacity);
+        }
+        Ok(())
+    }
+}
+
+impl FileAsset for BlockDefinition {
+    const EXTENSION: &'static str = "ron";
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Result<Self, BoxedError> {
+        let definition: Self = load_ron(&bytes)?;
+        if let Err(err) = definition.validate() {
+            error!("{err}");
+            return Err(err.into());
+        }
+        Ok(definition)
+    }
+}
+
+/// How a block affects the visibility of its neighbours and how light passes
+/// through it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum BlockOpacity {
+    /// Hides the faces of neighbouring blocks that touch it and blocks light.
+    Opaque,
+    /// Rendered, but the faces of neighbouring blocks behind it are still
+    /// visible and light passes through it, such as glass or leaves.
+    Transparent,
+    /// Not rendered at all, such as air.
+    Invisible
+}
+
+/// The names of the textures used by each face of a block. How texture names
+/// are resolved is up to the mesher.
+#[derive(Clone, Debug, Deserialize)]
+pub enum BlockTextures {
+    /// For [`BlockOpacity::Invisible`] blocks.
+    None,
+    /// Every face uses the same texture.
+    All(String),
+    /// Used for blocks like logs.
+    TopBottomSides {
+        top: String,
+        bottom: String,
+        sides: String
+    },
+    PerFace {
+        positive_x: String,
+        negative_x: String,
+        positive_y: String,
+        negative_y: String,
+        positive_z: String,
+        negative_z: String
+    }
+}
+
+impl BlockTextures {
+    /// Returns [`None`] for [`BlockTextures::None`].
+    pub fn face(&self, face: BlockFace) -> Option<&str> {
+        match self {
+            Self::None => None,
+            Self::All(texture) => Some(texture),
+            Self::TopBottomSides { top, bottom, sides } => Some(match face {
+                BlockFace::PositiveY => top,
+                BlockFace::NegativeY => bottom,
+                _ => sides
+            }),
+            Self::PerFace { positive_x, negative_x, positive_y, negative_y, positive_z, negative_z } => Some(match face {
+                BlockFace::PositiveX => positive_x,
+                BlockFace::NegativeX => negative_x,
+                BlockFace::PositiveY => positive_y,
+                BlockFace::NegativeY => negative_y,
+                BlockFace::PositiveZ => positive_z,
+                BlockFace::NegativeZ => negative_z
+            })
+        }
+    }
+
+    /// Iterates over the distinct texture names, in no particular order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        let mut names: Vec<&str> = BlockFace::iter().filter_map(|face| self.face(face)).collect();
+        names.sort_unstable();
+        names.dedup();
+        names.into_iter()
+    }
+}
+
+/// One of the six faces of a block, named after the direction it faces.
+#[derive(EnumCount, EnumIter, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BlockFace {
+    PositiveX,
+    NegativeX,
+    PositiveY,
+    NegativeY,
+    PositiveZ,
+    NegativeZ
+}
+
+impl BlockFace {
+    pub const fn normal(self) -> IVec3 {
+        match self {
+            Self::PositiveX => IVec3::X,
+            Self::NegativeX => IVec3::NEG_X,
+            Self::PositiveY => IVec3::Y,
+            Self::NegativeY => IVec3::NEG_Y,
+            Self::PositiveZ => IVec3::Z,
+            Self::NegativeZ => IVec3::NEG_Z
+        }
+    }
+
+    #[expect(dead_code, reason = "Nothing needs the face that a face is against yet.")]
+    pub const fn opposite(self) -> Self {
+        match self {
+            Self::PositiveX => Self::NegativeX,
+            Self::NegativeX => Self::PositiveX,
+            Self::PositiveY => Self::NegativeY,
+            Self::NegativeY => Self::PositiveY,
+            Self::PositiveZ => Self::NegativeZ,
+            Self::NegativeZ => Self::PositiveZ
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum BlockNameParseError {
+    #[error("The block name \"{0}\" does not have a namespace. Block names must look like \"namespace:path\".")]
+    MissingNamespace(String),
+
+    #[error("The namespace of the block name \"{0}\" is empty or contains characters other than lowercase ASCII letters, digits and underscores.")]
+    InvalidNamespace(String),
+
+    #[error("The path of the block name \"{0}\" is empty or contains characters other than lowercase ASCII letters, digits, underscores and slashes.")]
+    InvalidPath(String)
+}
+
+#[derive(Debug, Error)]
+pub enum BlockDefinitionError {
+    #[error("The light emission {0} is higher than the maximum of {MAX_LIGHT_LEVEL}.")]
+    LightEmissionTooHigh(u8),
+
+    #[error("The hardness {0} is not a non-negative number.")]
+    InvalidHardness(f32),
+
+    #[error("Blocks must have textures if and only if they are not invisible.")]
+    TexturesMismatchOpacity
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_names_are_namespaced() {
+        let stone: BlockName = "mycraft:stone".parse().expect("mycraft:stone should be a valid block name.");
+        assert_eq!(stone.namespace(), "mycraft");
+        assert_eq!(stone.path(), "stone");
+        assert_eq!(BlockName::new(BlockName::DEFAULT_NAMESPACE, "stone").expect("mycraft:stone should be a valid block name."), stone);
+        assert_eq!(BlockName::air().as_str(), "mycraft:air");
+
+        let coal = BlockName::new("some_mod2", "ores/coal").expect("some_mod2:ores/coal should be a valid block name.");
+        assert_eq!(coal.as_str(), "some_mod2:ores/coal");
+        assert_eq!(coal.namespace(), "some_mod2");
+        assert_eq!(coal.path(), "ores/coal");
+        assert_eq!(coal.to_string(), "some_mod2:ores/coal");
+    }
+
+    #[test]
+    fn invalid_block_names_are_rejected() {
+        // There is no implied default namespace, so names without one are
+        // rejected rather than guessed.
+        assert!(matches!("stone".parse::<BlockName>(), Err(BlockNameParseError::MissingNamespace(_))));
+        for name in [":stone", "Mycraft:stone", "my-craft:stone", "my/craft:stone"] {
+            assert!(matches!(name.parse::<BlockName>(), Err(BlockNameParseError::InvalidNamespace(_))), "{name} should have an invalid namespace.");
+        }
+        for name in ["mycraft:", "mycraft:Stone", "mycraft:/stone", "mycraft:ores/", "mycraft:stone.ron", "mycraft:stone:1"] {
+            assert!(matches!(name.parse::<BlockName>(), Err(BlockNameParseError::InvalidPath(_))), "{name} should have an invalid path.");
+        }
+        assert!(matches!(BlockName::try_from("mycraft:stone block".to_owned()), Err(BlockNameParseError::InvalidPath(_))));
+    }
+}
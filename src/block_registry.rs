@@ -0,0 +1,225 @@
+//! Contains [`BlockRegistry`], which maps [`BlockId`]s used in chunk storage
+//! to the [`BlockDefinition`]s loaded from the `assets/blocks` directory.
+
+use assets_manager::*;
+use assets_manager::Error as AssetCacheError;
+use log::*;
+use std::collections::*;
+use thiserror::*;
+use crate::block::*;
+
+/// The ID of the directory in the [`AssetCache`] that [`BlockDefinitions`] are
+/// loaded from.
+pub const BLOCKS_ASSET_ID: &str = "blocks";
+
+/// Every [`BlockDefinition`] in a directory, recursively. The subdirectories
+/// directly inside it are namespaces, so `blocks/mycraft/ores/coal.ron` defines
+/// the block `mycraft:ores/coal`. Since this loads each file through the
+/// [`AssetCache`], adding, removing or editing any of them hot-reloads this.
+pub struct BlockDefinitions {
+    /// Sorted by name.
+    definitions: Vec<(BlockName, BlockDefinition)>
+}
+
+impl BlockDefinitions {
+    /// Iterates over the definitions in order of their names.
+    pub fn iter(&self) -> impl Iterator<Item = (&BlockName, &BlockDefinition)> {
+        self.definitions.iter().map(|(name, definition)| (name, definition))
+    }
+}
+
+impl Asset for BlockDefinitions {
+    fn load(cache: &AssetCache, id: &SharedString) -> Result<Self, BoxedError> {
+        info!("Loading block definitions: {id}");
+        let directory = cache.load_rec_dir::<BlockDefinition>(id)?;
+        let mut definitions = Vec::new();
+        for asset_id in directory.read().ids() {
+            let name = match asset_id_to_block_name(id, asset_id) {
+                Ok(name) => name,
+                Err(err) => {
+                    error!("Error while loading block definitions {id}: {err}");
+                    return Err(err.into());
+                }
+            };
+            if name == BlockName::air() {
+                let err = BlockRegistryError::AirRedefined;
+                error!("Error while loading block definitions {id}: {err}");
+                return Err(err.into());
+            }
+            definitions.push((name, cache.load::<BlockDefinition>(asset_id)?.cloned()));
+        }
+        definitions.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        info!("Loaded {} block definitions.", definitions.len());
+        Ok(Self { definitions })
+    }
+}
+
+/// Converts an ID such as `blocks.mycraft.ores.coal` inside the directory
+/// `blocks` into `mycraft:ores/coal`.
+fn asset_id_to_block_name(directory_id: &str, asset_id: &str) -> Result<BlockName, BlockRegistryError> {
+    let relative_id = asset_id.strip_prefix(directory_id).and_then(|relative_id| relative_id.strip_prefix('.')).unwrap_or(asset_id);
+    let Some((namespace, path)) = relative_id.split_once('.') else {
+        return Err(BlockRegistryError::NotInNamespace(asset_id.to_owned()));
+    };
+    Ok(BlockName::new(namespace, &path.replace('.', "/"))?)
+}
+
+/// A kind of block that has been given a [`BlockId`].
+#[derive(Debug)]
+pub struct BlockType {
+    pub name: BlockName,
+    pub definition: BlockDefinition
+}
+
+/// Gives every [`BlockDefinition`] a [`BlockId`]. IDs are first handed out in
+/// order of the names of the blocks, and once a name has an ID, it keeps it for
+/// as long as the registry lives, even if its definition is removed and added
+/// back by hot-reloading. This means that IDs are only stable for the duration
+/// of a run, so anything that persists blocks must store their names instead.
+#[derive(Debug)]
+pub struct BlockRegistry {
+    /// Indexed by [`BlockId`]. [`None`] for blocks that have been removed, so
+    /// that their IDs are not reused.
+    block_types: Vec<Option<BlockType>>,
+    ids: HashMap<BlockName, BlockId>
+}
+
+impl BlockRegistry {
+    pub fn new(definitions: &BlockDefinitions) -> Result<Self, BlockRegistryError> {
+        let air_name = BlockName::air();
+        let mut registry = Self {
+            block_types: vec![Some(BlockType { name: air_name.clone(), definition: BlockDefinition::air() })],
+            ids: HashMap::from([(air_name, BlockId::AIR)])
+        };
+        registry.update(definitions)?;
+        Ok(registry)
+    }
+
+    /// Replaces the definitions with `definitions`, keeping the IDs of the
+    /// blocks that were already registered. Blocks in chunk storage whose
+    /// definitions have been removed are treated as unknown by
+    /// [`Self::get`]. If this fails, the registry is left unchanged.
+    pub fn update(&mut self, definitions: &BlockDefinitions) -> Result<(), BlockRegistryError> {
+        let new_block_count = definitions.iter().filter(|(name, _)| !self.ids.contains_key(*name)).count();
+        if self.block_types.len() + new_block_count > usize::from(u16::MAX) + 1 {
+            let err = BlockRegistryError::TooManyBlocks;
+            error!("{err}");
+            return Err(err);
+        }
+        for (id, block_type) in self.block_types.iter_mut().enumerate() {
+            if id != usize::from(BlockId::AIR.0) {
+                *block_type = None;
+            }
+        }
+        for (name, definition) in definitions.iter() {
+            let block_type = Some(BlockType { name: name.clone(), definition: definition.clone() });
+            if let Some(id) = self.ids.get(name) {
+                self.block_types[usize::from(id.0)] = block_type;
+            }
+            else {
+                #[expect(clippy::cast_possible_truncation, reason = "It has been checked above that there are at most 65536 blocks.")]
+                let id = BlockId(self.block_types.len() as u16);
+                self.ids.insert(name.clone(), id);
+                self.block_types.push(block_type);
+            }
+        }
+        info!("The block registry has been updated. {} blocks are defined.", self.block_types.iter().flatten().count());
+        Ok(())
+    }
+
+    /// Returns [`None`] if `id` has never been registered or if its definition
+    /// has been removed.
+    pub fn get(&self, id: BlockId) -> Option<&BlockType> {
+        self.block_types.get(usize::from(id.0))?.as_ref()
+    }
+
+    /// Returns the ID of the block called `name` if it is currently defined.
+    pub fn id(&self, name: &BlockName) -> Option<BlockId> {
+        self.ids.get(name).copied().filter(|id| self.get(*id).is_some())
+    }
+
+    /// Iterates over the blocks that are currently defined, in order of their
+    /// IDs.
+    pub fn iter(&self) -> impl Iterator<Item = (BlockId, &BlockType)> {
+        (0..=u16::MAX).map(BlockId).zip(&self.block_types).filter_map(|(id, block_type)| Some((id, block_type.as_ref()?)))
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum BlockRegistryError {
+    #[error("The block definition {0} is not inside a namespace directory.")]
+    NotInNamespace(String),
+
+    #[error(transparent)]
+    InvalidName(#[from] BlockNameParseError),
+
+    #[error("Air cannot be redefined.")]
+    AirRedefined,
+
+    #[error("There are more kinds of blocks than can be given a BlockId.")]
+    TooManyBlocks,
+
+    #[error(transparent)]
+    AssetCache(#[from] AssetCacheError)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn definitions(names: &[&str]) -> BlockDefinitions {
+        let mut definitions: Vec<(BlockName, BlockDefinition)> = names
+            .iter()
+            .map(|name| {
+                let definition = BlockDefinition {
+                    textures: BlockTextures::All((*name).to_owned()),
+                    opacity: BlockOpacity::Opaque,
+                    is_solid: true,
+                    light_emission: 0,
+                    hardness: 1.0
+                };
+                (name.parse().expect("The test block names should be valid."), definition)
+            })
+            .collect();
+        definitions.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        BlockDefinitions { definitions }
+    }
+
+    fn id(registry: &BlockRegistry, name: &str) -> Option<BlockId> {
+        registry.id(&name.parse().expect("The test block names should be valid."))
+    }
+
+    #[test]
+    fn ids_are_handed_out_in_order_of_names() {
+        let registry = BlockRegistry::new(&definitions(&["mycraft:stone", "mycraft:dirt", "mycraft:grass"])).expect("The registry should be created.");
+        assert_eq!(id(&registry, "mycraft:air"), Some(BlockId::AIR));
+        assert_eq!(id(&registry, "mycraft:dirt"), Some(BlockId(1)));
+        assert_eq!(id(&registry, "mycraft:grass"), Some(BlockId(2)));
+        assert_eq!(id(&registry, "mycraft:stone"), Some(BlockId(3)));
+        let names: Vec<_> = registry.iter().map(|(_, block_type)| block_type.name.as_str()).collect();
+        assert_eq!(names, ["mycraft:air", "mycraft:dirt", "mycraft:grass", "mycraft:stone"]);
+    }
+
+    #[test]
+    fn ids_are_stable_across_updates() {
+        let mut registry = BlockRegistry::new(&definitions(&["mycraft:dirt", "mycraft:stone"])).expect("The registry should be created.");
+        let dirt = id(&registry, "mycraft:dirt").expect("Dirt should be defined.");
+        let stone = id(&registry, "mycraft:stone").expect("Stone should be defined.");
+
+        // A block whose name sorts before the others is added, and dirt is
+        // removed.
+        registry.update(&definitions(&["mycraft:clay", "mycraft:stone"])).expect("The registry should be updated.");
+        assert_eq!(id(&registry, "mycraft:stone"), Some(stone));
+        assert_eq!(id(&registry, "mycraft:dirt"), None);
+        assert!(registry.get(dirt).is_none(), "A removed block should be unknown.");
+        let clay = id(&registry, "mycraft:clay").expect("Clay should be defined.");
+        assert!(![BlockId::AIR, dirt, stone].contains(&clay), "A new block should not reuse the ID of another block, even a removed one.");
+
+        // Dirt comes back with the ID it had before.
+        registry.update(&definitions(&["mycraft:clay", "mycraft:dirt", "mycraft:stone"])).expect("The registry should be updated.");
+        assert_eq!(id(&registry, "mycraft:dirt"), Some(dirt));
+        assert_eq!(id(&registry, "mycraft:stone"), Some(stone));
+        assert_eq!(id(&registry, "mycraft:clay"), Some(clay));
+        assert_eq!(registry.get(dirt).map(|block_type| block_type.name.as_str()), Some("mycraft:dirt"));
+    }
+}
@@ -0,0 +1,141 @@
+//! Contains [`BlockTextureAtlas`], which packs the textures of every block into
+//! a single image so that all chunks can be drawn with the same
+//! [`crate::material::Material`].
+
+use assets_manager::*;
+use glam::*;
+use image::*;
+use log::*;
+use std::borrow::*;
+use std::collections::*;
+use crate::block_registry::*;
+
+/// The width and height of every block texture in pixels.
+pub const BLOCK_TEXTURE_SIZE: u32 = 16;
+
+/// A block texture loaded from a PNG file in `assets/textures/blocks`. See
+/// [`texture_name_to_asset_id`].
+pub struct BlockTextureImage {
+    pub image: RgbaImage
+}
+
+impl FileAsset for BlockTextureImage {
+    const EXTENSION: &'static str = "png";
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Result<Self, BoxedError> {
+        Ok(Self { image: load_from_memory_with_format(&bytes, ImageFormat::Png)?.into_rgba8() })
+    }
+}
+
+/// Converts a texture name such as `mycraft:ores/coal` into the ID of the asset
+/// `textures/blocks/mycraft/ores/coal.png`. Returns [`None`] if the name has
+/// no namespace.
+pub fn texture_name_to_asset_id(texture_name: &str) -> Option<String> {
+    let (namespace, path) = texture_name.split_once(':')?;
+    Some(format!("textures.blocks.{namespace}.{}", path.replace('/', ".")))
+}
+
+/// The layout of the atlas and the pixels to upload to the GPU. The atlas is a
+/// square grid of [`BLOCK_TEXTURE_SIZE`]-sized tiles. Tile 0 is always
+/// [`missing_texture`], which is used for textures that cannot be loaded.
+pub struct BlockTextureAtlas {
+    /// Keyed by texture name.
+    tile_indices: HashMap<String, u32>,
+    /// The number of tiles in each row and column.
+    tiles_per_side: u32,
+    image: RgbaImage
+}
+
+impl BlockTextureAtlas {
+    /// Index of the tile that is used for textures that cannot be loaded.
+    pub const MISSING_TILE_INDEX: u32 = 0;
+
+    /// Loads every texture used by the blocks in `block_registry` from
+    /// `asset_cache` and packs them into an atlas. Textures that cannot be
+    /// loaded or have the wrong size are logged and replaced with
+    /// [`missing_texture`].
+    pub fn new(asset_cache: &AssetCache, block_registry: &BlockRegistry) -> Self {
+        let texture_names: BTreeSet<&str> = block_registry.iter()
+            .flat_map(|(_, block_type)| block_type.definition.textures.names())
+            .collect();
+        #[expect(clippy::cast_possible_truncation, reason = "There are never more than u32::MAX textures.")]
+        let tile_count = texture_names.len() as u32 + 1;
+        // The smallest power of 2 whose square is at least tile_count.
+        let tiles_per_side = 1 << tile_count.next_power_of_two().trailing_zeros().div_ceil(2);
+        let mut image = RgbaImage::new(tiles_per_side * BLOCK_TEXTURE_SIZE, tiles_per_side * BLOCK_TEXTURE_SIZE);
+        let mut tile_indices = HashMap::new();
+        let missing_texture = missing_texture();
+        copy_tile(&mut image, tiles_per_side, Self::MISSING_TILE_INDEX, &missing_texture);
+        for (tile_index, texture_name) in (1..).zip(texture_names) {
+            match load_block_texture(asset_cache, texture_name) {
+                Some(texture) => copy_tile(&mut image, tiles_per_side, tile_index, &texture),
+                None => copy_tile(&mut image, tiles_per_side, tile_index, &missing_texture)
+            }
+            tile_indices.insert(texture_name.to_owned(), tile_index);
+        }
+        info!("The block texture atlas has been created with {tile_count} tiles.");
+        Self { tile_indices, tiles_per_side, image }
+    }
+
+    /// Returns [`Self::MISSING_TILE_INDEX`] if `texture_name` is not in the
+    /// atlas.
+    pub fn tile_index(&self, texture_name: &str) -> u32 {
+        self.tile_indices.get(texture_name).copied().unwrap_or(Self::MISSING_TILE_INDEX)
+    }
+
+    /// Returns the column and row of the tile at `tile_index`.
+    #[expect(clippy::integer_division, reason = "The quotient is the row of the tile.")]
+    pub const fn tile_position(&self, tile_index: u32) -> UVec2 {
+        UVec2::new(tile_index % self.tiles_per_side, tile_index / self.tiles_per_side)
+    }
+
+    pub const fn tiles_per_side(&self) -> u32 {
+        self.tiles_per_side
+    }
+
+    pub const fn image(&self) -> &RgbaImage {
+        &self.image
+    }
+}
+
+fn load_block_texture(asset_cache: &AssetCache, texture_name: &str) -> Option<RgbaImage> {
+    let Some(asset_id) = texture_name_to_asset_id(texture_name) else {
+        warn!("The block texture name \"{texture_name}\" has no namespace.");
+        return None;
+    };
+    let handle = match asset_cache.load::<BlockTextureImage>(&asset_id) {
+        Ok(handle) => handle,
+        Err(err) => {
+            warn!("The block texture \"{texture_name}\" could not be loaded. {err:#?}");
+            return None;
+        }
+    };
+    let texture = handle.read().image.clone();
+    if texture.dimensions() != (BLOCK_TEXTURE_SIZE, BLOCK_TEXTURE_SIZE) {
+        warn!("The block texture \"{texture_name}\" is {}x{}, but block textures must be {BLOCK_TEXTURE_SIZE}x{BLOCK_TEXTURE_SIZE}.", texture.width(), texture.height());
+        return None;
+    }
+    Some(texture)
+}
+
+#[expect(clippy::integer_division, reason = "The quotient is the row of the tile.")]
+fn copy_tile(atlas: &mut RgbaImage, tiles_per_side: u32, tile_index: u32, texture: &RgbaImage) {
+    let x = (tile_index % tiles_per_side) * BLOCK_TEXTURE_SIZE;
+    let y = (tile_index / tiles_per_side) * BLOCK_TEXTURE_SIZE;
+    for (texture_x, texture_y, pixel) in texture.enumerate_pixels() {
+        atlas.put_pixel(x + texture_x, y + texture_y, *pixel);
+    }
+}
+
+/// A magenta and black checkerboard that stands out.
+pub fn missing_texture() -> RgbaImage {
+    let half_size = BLOCK_TEXTURE_SIZE >> 1;
+    RgbaImage::from_fn(BLOCK_TEXTURE_SIZE, BLOCK_TEXTURE_SIZE, |x, y| {
+        if (x < half_size) == (y < half_size) {
+            Rgba([255, 0, 255, 255])
+        }
+        else {
+            Rgba([0, 0, 0, 255])
+        }
+    })
+}
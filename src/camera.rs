@@ -1,8 +1,42 @@
+use glam::*;
+use wgpu::*;
+
 pub enum ProjectionMode {
+    #[expect(dead_code, reason = "No camera uses an orthographic projection yet.")]
     Orthographic(OrthographicProjectionConfig),
     Perspective(PerspectiveProjectionConfig)
 }
 
+impl ProjectionMode {
+    /// Creates the projection matrix that maps the view space into the clip
+    /// space of [`wgpu`], whose depth range is \[0, 1\]. Like the rest of the
+    /// world, the view space is left-handed, with the camera looking towards
+    /// +Z and +Y being up.
+    ///
+    /// * `aspect_ratio`: The width of the render target divided by its height.
+    /// * `reverse_z`: If `true`, the near clipping plane is mapped to a depth
+    ///   of 1 and the far clipping plane to 0 instead, which distributes the
+    ///   precision of floating point depth buffers much more evenly.
+    pub fn projection_matrix(&self, aspect_ratio: f32, reverse_z: bool) -> Mat4 {
+        // Swapping the clipping planes is all it takes to reverse the depth
+        // range, since both planes are mapped linearly in view space depth (or
+        // its reciprocal for perspective projections).
+        let (near, far) = match self {
+            Self::Orthographic(config) => (config.near_clipping_plane_distance, config.far_clipping_plane_distance),
+            Self::Perspective(config) => (config.near_clipping_plane_distance, config.far_clipping_plane_distance)
+        };
+        let (depth_0_distance, depth_1_distance) = if reverse_z { (far, near) } else { (near, far) };
+        match self {
+            Self::Orthographic(config) => {
+                let max = Vec2::new(config.width, config.height) * 0.5;
+                let min = -max;
+                Mat4::orthographic_lh(min.x, max.x, min.y, max.y, depth_0_distance, depth_1_distance)
+            }
+            Self::Perspective(config) => Mat4::perspective_lh(config.vertical_fov(aspect_ratio), aspect_ratio, depth_0_distance, depth_1_distance)
+        }
+    }
+}
+
 pub struct OrthographicProjectionConfig {
     pub width: f32,
     pub height: f32,
@@ -16,3 +50,25 @@ pub struct PerspectiveProjectionConfig {
     pub near_clipping_plane_distance: f32,
     pub far_clipping_plane_distance: f32
 }
+
+impl PerspectiveProjectionConfig {
+    /// Converts [`Self::horizontal_fov`] into the vertical FOV in radians so
+    /// that the horizontal FOV stays the same no matter the aspect ratio.
+    #[expect(clippy::float_arithmetic, reason = "Converting between FOVs requires trigonometry.")]
+    pub fn vertical_fov(&self, aspect_ratio: f32) -> f32 {
+        2.0 * ((self.horizontal_fov * 0.5).tan() / aspect_ratio).atan()
+    }
+}
+
+/// The [`CompareFunction`] that lets fragments closer to the camera pass the
+/// depth test, given whether the depth range is reversed. See
+/// [`ProjectionMode::projection_matrix`].
+pub const fn depth_compare_function(reverse_z: bool) -> CompareFunction {
+    if reverse_z { CompareFunction::Greater } else { CompareFunction::Less }
+}
+
+/// The value to clear depth attachments to, which is the depth of the far
+/// clipping plane. See [`ProjectionMode::projection_matrix`].
+pub const fn depth_clear_value(reverse_z: bool) -> f32 {
+    if reverse_z { 0.0 } else { 1.0 }
+}
@@ -0,0 +1,791 @@
+//! Contains the code that turns [`ChunkSection`]s into [`Mesh`]es. Only the
+//! faces of blocks that can be seen are emitted, which are those that touch an
+//! air or a transparent block, including blocks in neighbouring sections. See
+//! [`MeshingMode`] for the algorithms that can be used.
+//!
+//! Each quad is laid out like the corresponding face of
+//! [`Cuboid::as_mesh_builder`]. Since a quad can cover several blocks, its UVs
+//! are not atlas UVs, but are encoded so that the texture repeats once per
+//! block. See [`TILE_UV_STRIDE`].
+//!
+//! Faces are smoothly lit: each corner gets the average light of the blocks in
+//! front of the face that touch it, which is stored in its vertex colors. See
+//! [`light_color`]. Each corner is also darkened by ambient occlusion from the
+//! opaque blocks around it, as described in
+//! <https://0fps.net/2013/07/03/ambient-occlusion-for-minecraft-like-worlds/>.
+
+use glam::*;
+use strum::*;
+use crate::block::*;
+use crate::block_registry::*;
+use crate::block_texture_atlas::*;
+use crate::mesh::*;
+use crate::shapes::*;
+use crate::voxel_world::*;
+
+/// The number of blocks along each side of a [`SectionSnapshot`], which
+/// includes a layer of blocks from the neighbouring sections on every side.
+const SNAPSHOT_SIZE: i32 = SECTION_SIZE + 2;
+
+/// The ambient occlusion of a [`FaceCorner`] that no opaque block touches.
+const MAX_AMBIENT_OCCLUSION: u8 = 3;
+
+/// A copy of the blocks in a [`ChunkSection`] and the blocks around it, which
+/// is everything needed to mesh the section. Being a copy, it can be meshed
+/// without borrowing the [`VoxelWorld`].
+pub struct SectionSnapshot {
+    /// Indexed by [`snapshot_index`].
+    blocks: Vec<BlockId>,
+    /// The light of each block, indexed like [`Self::blocks`], and then by
+    /// [`LightKind`].
+    light: Vec<[u8; LightKind::COUNT]>,
+    /// Whether the section itself only contains air.
+    is_empty: bool
+}
+
+impl SectionSnapshot {
+    /// Returns [`None`] if the section at `section_position` is not loaded.
+    /// Blocks in neighbouring sections that are not loaded are treated as air
+    /// lit by the sky, so that the boundary faces are visible until the
+    /// neighbours are loaded, which marks this section dirty again.
+    pub fn new(voxel_world: &VoxelWorld, section_position: IVec3) -> Option<Self> {
+        let section = voxel_world.section(section_position)?;
+        let section_origin = section_to_block_position(section_position);
+        let snapshot_volume = (SNAPSHOT_SIZE * SNAPSHOT_SIZE * SNAPSHOT_SIZE) as usize;
+        let mut blocks = Vec::with_capacity(snapshot_volume);
+        let mut light = Vec::with_capacity(snapshot_volume);
+        for y in -1..=SECTION_SIZE {
+            for z in -1..=SECTION_SIZE {
+                for x in -1..=SECTION_SIZE {
+                    let local_position = IVec3::new(x, y, z);
+                    if local_position.cmpge(IVec3::ZERO).all() && local_position.cmplt(IVec3::splat(SECTION_SIZE)).all() {
+                        blocks.push(section.get_block(local_position.as_uvec3()));
+                        light.push([section.get_light(LightKind::Sky, local_position.as_uvec3()), section.get_light(LightKind::Block, local_position.as_uvec3())]);
+                    }
+                    else {
+                        let block_position = section_origin + local_position;
+                        blocks.push(voxel_world.get_block(block_position).unwrap_or(BlockId::AIR));
+                        light.push([
+                            voxel_world.get_light(LightKind::Sky, block_position).unwrap_or(MAX_LIGHT_LEVEL),
+                            voxel_world.get_light(LightKind::Block, block_position).unwrap_or(0)
+                        ]);
+                    }
+                }
+            }
+        }
+        Some(Self { blocks, light, is_empty: section.is_empty() })
+    }
+
+    /// * `local_position`: Relative to the origin of the section. Each
+    ///   component must be from -1 to [`SECTION_SIZE`].
+    fn get_block(&self, local_position: IVec3) -> BlockId {
+        self.blocks[snapshot_index(local_position)]
+    }
+
+    /// The light of the block at `local_position`, which is the same as in
+    /// [`Self::get_block`], indexed by [`LightKind`].
+    fn get_light(&self, local_position: IVec3) -> [u8; LightKind::COUNT] {
+        self.light[snapshot_index(local_position)]
+    }
+}
+
+#[expect(clippy::cast_sign_loss, reason = "The position is offset so that every component is non-negative.")]
+const fn snapshot_index(local_position: IVec3) -> usize {
+    (((local_position.y + 1) * SNAPSHOT_SIZE + local_position.z + 1) * SNAPSHOT_SIZE + local_position.x + 1) as usize
+}
+
+/// Quads emitted by the mesher have UVs of `tile_position * TILE_UV_STRIDE +
+/// block_uv`, where `tile_position` is the column and row of the tile in the
+/// [`BlockTextureAtlas`], and `block_uv` goes from 0 to the size of the quad
+/// in blocks. `shaders/block.wesl` recovers `tile_position` by flooring and
+/// repeats the tile by taking the fractional part of `block_uv`, which is what
+/// lets [`MeshingMode::Greedy`] quads share a tile of the atlas. It must be
+/// larger than [`SECTION_SIZE`] and match `TILE_UV_STRIDE` in the shader.
+pub const TILE_UV_STRIDE: f32 = 32.0;
+
+/// The algorithm used to turn sections into [`Mesh`]es.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MeshingMode {
+    /// Emits a quad for every visible face. It is the simplest, but flat
+    /// terrain ends up with a huge number of vertices.
+    #[cfg_attr(not(test), expect(dead_code, reason = "Only tests and benchmarks use it until there is a setting for it."))]
+    Naive,
+    /// Merges adjacent coplanar visible faces of the same block into
+    /// rectangles, which produces far fewer vertices at the cost of a slower
+    /// meshing.
+    #[default]
+    Greedy
+}
+
+/// What the mesher needs to know about a kind of block.
+#[derive(Clone, Copy, Debug)]
+pub struct BlockMeshingInfo {
+    pub opacity: BlockOpacity,
+    /// The column and row of the tile in the [`BlockTextureAtlas`] used by
+    /// each face, indexed by [`BlockFace`].
+    pub face_tiles: [UVec2; BlockFace::COUNT]
+}
+
+/// [`BlockMeshingInfo`] of every kind of block, indexed by [`BlockId`], along
+/// with the faces of a unit cube that every quad is copied from. It needs to
+/// be recreated whenever the [`BlockRegistry`] or the [`BlockTextureAtlas`]
+/// changes.
+pub struct BlockMeshingTable {
+    blocks: Vec<BlockMeshingInfo>,
+    /// Used for blocks that are not in the [`BlockRegistry`], such as those
+    /// whose definitions have been removed.
+    unknown_block: BlockMeshingInfo,
+    face_templates: [FaceTemplate; BlockFace::COUNT],
+    /// The offsets from the block in front of a face to the two blocks beside
+    /// each of its corners, indexed by [`BlockFace`], and then like the
+    /// vertices of the [`FaceTemplate`].
+    face_corner_offsets: [[[IVec3; 2]; 4]; BlockFace::COUNT]
+}
+
+/// The positions and UVs of the 4 vertices of a face of a unit cube centered
+/// at the origin.
+type FaceTemplate = [(Vec3, Vec2); 4];
+
+impl BlockMeshingTable {
+    pub fn new(block_registry: &BlockRegistry, atlas: &BlockTextureAtlas) -> Self {
+        let missing_tile = atlas.tile_position(BlockTextureAtlas::MISSING_TILE_INDEX);
+        let unknown_block = BlockMeshingInfo {
+            opacity: BlockOpacity::Opaque,
+            face_tiles: [missing_tile; BlockFace::COUNT]
+        };
+        let mut blocks = Vec::new();
+        for (id, block_type) in block_registry.iter() {
+            let index = usize::from(id.0);
+            if blocks.len() <= index {
+                blocks.resize(index + 1, unknown_block);
+            }
+            let mut face_tiles = [missing_tile; BlockFace::COUNT];
+            for (face, face_tile) in BlockFace::iter().zip(&mut face_tiles) {
+                if let Some(texture_name) = block_type.definition.textures.face(face) {
+                    *face_tile = atlas.tile_position(atlas.tile_index(texture_name));
+                }
+            }
+            blocks[index] = BlockMeshingInfo { opacity: block_type.definition.opacity, face_tiles };
+        }
+        let face_templates = cube_face_templates();
+        let face_corner_offsets = face_corner_offsets(&face_templates);
+        Self { blocks, unknown_block, face_templates, face_corner_offsets }
+    }
+
+    pub fn get(&self, id: BlockId) -> &BlockMeshingInfo {
+        self.blocks.get(usize::from(id.0)).unwrap_or(&self.unknown_block)
+    }
+}
+
+/// Takes the faces of a unit [`Cuboid`] so that block faces are laid out the
+/// same way. [`BlockFace`] is in the same order as the faces of the
+/// [`Cuboid`].
+fn cube_face_templates() -> [FaceTemplate; BlockFace::COUNT] {
+    let cube = Cuboid { dimensions: Vec3::ONE }.as_mesh_builder().build().expect("A cuboid should always be a valid mesh.");
+    let mut face_templates = [[(Vec3::ZERO, Vec2::ZERO); 4]; BlockFace::COUNT];
+    for (face_index, face_template) in face_templates.iter_mut().enumerate() {
+        for (vertex_index, vertex) in face_template.iter_mut().enumerate() {
+            let cube_vertex_index = face_index * 4 + vertex_index;
+            *vertex = (cube.vertices()[cube_vertex_index], cube.uv()[cube_vertex_index]);
+        }
+    }
+    face_templates
+}
+
+/// Finds the blocks beside each corner of the faces from the signs of the
+/// template positions along the two axes of the face.
+fn face_corner_offsets(face_templates: &[FaceTemplate; BlockFace::COUNT]) -> [[[IVec3; 2]; 4]; BlockFace::COUNT] {
+    let mut face_corner_offsets = [[[IVec3::ZERO; 2]; 4]; BlockFace::COUNT];
+    for ((face, face_template), corner_offsets) in BlockFace::iter().zip(face_templates).zip(&mut face_corner_offsets) {
+        let normal_axis = face.normal().abs().max_position();
+        for ((template_position, _), side_offsets) in face_template.iter().zip(corner_offsets) {
+            let corner_direction = template_position.signum().as_ivec3();
+            for (axis_offset, side_offset) in [1, 2].into_iter().zip(side_offsets) {
+                let axis = (normal_axis + axis_offset) % 3;
+                side_offset[axis] = corner_direction[axis];
+            }
+        }
+    }
+    face_corner_offsets
+}
+
+/// Returns `true` if the face of `block` that touches `neighbour` can be seen.
+fn is_face_visible(block: BlockId, neighbour: BlockId, meshing_table: &BlockMeshingTable) -> bool {
+    match meshing_table.get(neighbour).opacity {
+        BlockOpacity::Opaque => false,
+        BlockOpacity::Invisible => true,
+        // Faces between two blocks of the same transparent kind, such as
+        // water next to water, are hidden.
+        BlockOpacity::Transparent => neighbour != block
+    }
+}
+
+/// A face of a block that can be seen. [`MeshingMode::Greedy`] only merges
+/// faces that are equal, so merged quads are shaded the same as the faces
+/// they replace.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct VisibleFace {
+    block: BlockId,
+    /// Indexed like the vertices of the [`FaceTemplate`].
+    corners: [FaceCorner; 4]
+}
+
+/// How a corner of a [`VisibleFace`] is shaded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct FaceCorner {
+    /// The average light of the blocks in front of the face that touch the
+    /// corner and let light through, as returned by [`light_color`].
+    color: U8Vec4,
+    /// From 0 when the corner is between two opaque blocks to
+    /// [`MAX_AMBIENT_OCCLUSION`] when no opaque block touches it.
+    ambient_occlusion: u8
+}
+
+/// Returns the `face` of the block at `local_position` if it can be seen.
+fn visible_face(snapshot: &SectionSnapshot, meshing_table: &BlockMeshingTable, local_position: IVec3, face: BlockFace) -> Option<VisibleFace> {
+    let block = snapshot.get_block(local_position);
+    let front_position = local_position + face.normal();
+    let is_visible = meshing_table.get(block).opacity != BlockOpacity::Invisible
+        && is_face_visible(block, snapshot.get_block(front_position), meshing_table);
+    is_visible.then(|| VisibleFace {
+        block,
+        corners: meshing_table.face_corner_offsets[face as usize].map(|side_offsets| face_corner(snapshot, meshing_table, front_position, side_offsets))
+    })
+}
+
+/// Shades the corner of a face whose block in front is at `front_position`
+/// from the two blocks beside the corner at `side_offsets` from it, and the
+/// block diagonal to it.
+fn face_corner(snapshot: &SectionSnapshot, meshing_table: &BlockMeshingTable, front_position: IVec3, side_offsets: [IVec3; 2]) -> FaceCorner {
+    let is_opaque = |position| meshing_table.get(snapshot.get_block(position)).opacity == BlockOpacity::Opaque;
+    let [first_side, second_side] = side_offsets.map(|offset| front_position + offset);
+    let diagonal = first_side + side_offsets[1];
+    let (first_side_is_opaque, second_side_is_opaque, diagonal_is_opaque) = (is_opaque(first_side), is_opaque(second_side), is_opaque(diagonal));
+    // The diagonal block can neither be seen nor occlude any further when both
+    // sides are opaque.
+    let is_enclosed = first_side_is_opaque && second_side_is_opaque;
+    let ambient_occlusion = if is_enclosed {
+        0
+    }
+    else {
+        MAX_AMBIENT_OCCLUSION - u8::from(first_side_is_opaque) - u8::from(second_side_is_opaque) - u8::from(diagonal_is_opaque)
+    };
+    // Opaque blocks are always dark, so they are left out of the average
+    // instead of darkening the corner further.
+    let mut lights = [[0; LightKind::COUNT]; 4];
+    let mut light_count = 0;
+    for (position, lets_light_through) in [
+        (front_position, true),
+        (first_side, !first_side_is_opaque),
+        (second_side, !second_side_is_opaque),
+        (diagonal, !is_enclosed && !diagonal_is_opaque)
+    ] {
+        if lets_light_through {
+            lights[light_count] = snapshot.get_light(position);
+            light_count += 1;
+        }
+    }
+    FaceCorner { color: light_color(&lights[..light_count]), ambient_occlusion }
+}
+
+/// The vertex color that carries the average of `lights` to
+/// `shaders/block.wesl`: sky light in red and block light in green, each
+/// scaled from 0 to [`MAX_LIGHT_LEVEL`] up to 0 to 255 and rounded. `lights`
+/// must not be empty.
+#[expect(clippy::integer_division, reason = "The average is rounded on purpose.")]
+#[expect(clippy::cast_possible_truncation, reason = "At most 4 light levels are averaged, and the average is at most u8::MAX.")]
+pub fn light_color(lights: &[[u8; LightKind::COUNT]]) -> U8Vec4 {
+    const SCALE: u16 = (u8::MAX / MAX_LIGHT_LEVEL) as u16;
+    let count = lights.len() as u16;
+    let average = |kind: LightKind| {
+        let sum: u16 = lights.iter().map(|light| u16::from(light[kind as usize])).sum();
+        ((sum * SCALE + count / 2) / count) as u8
+    };
+    U8Vec4::new(average(LightKind::Sky), average(LightKind::Block), 0, u8::MAX)
+}
+
+/// Accumulates the vertex data of the quads of a section.
+#[derive(Default)]
+struct QuadMeshBuilder {
+    vertices: Vec<Vec3>,
+    uv: Vec<Vec2>,
+    colors: Vec<U8Vec4>,
+    ambient_occlusion: Vec<f32>,
+    indices: Vec<usize>
+}
+
+impl QuadMeshBuilder {
+    /// Adds a quad of `face` that covers `size` blocks starting at the block at
+    /// `local_position`. The component of `size` along the normal of the face
+    /// must be 1.
+    ///
+    /// The quad is split into triangles along the diagonal whose corners are
+    /// less occluded, so that the occlusion of a single corner does not spread
+    /// along the diagonal and make identical corners look different depending
+    /// on which way they face.
+    #[expect(clippy::float_arithmetic, reason = "Vertex positions, UVs and ambient occlusion are calculated from the face template.")]
+    fn push_quad(&mut self, face_template: &FaceTemplate, local_position: IVec3, size: Vec3, tile: UVec2, face: VisibleFace) {
+        let first_index = self.vertices.len();
+        // The directions in which the U and V of the template increase, which
+        // are used to find how many blocks the quad spans along each.
+        let u_direction = (face_template[1].0 - face_template[0].0).abs();
+        let v_direction = (face_template[3].0 - face_template[0].0).abs();
+        let size_in_uv = Vec2::new(u_direction.dot(size), v_direction.dot(size));
+        for ((template_position, template_uv), corner) in face_template.iter().zip(face.corners) {
+            self.vertices.push(local_position.as_vec3() + (*template_position + Vec3::splat(0.5)) * size);
+            // Cuboid UVs have (0, 0) at the bottom left, while textures have
+            // it at the top left.
+            let block_uv = Vec2::new(template_uv.x, 1.0 - template_uv.y) * size_in_uv;
+            self.uv.push(tile.as_vec2() * TILE_UV_STRIDE + block_uv);
+            self.colors.push(corner.color);
+            self.ambient_occlusion.push(f32::from(corner.ambient_occlusion) / f32::from(MAX_AMBIENT_OCCLUSION));
+        }
+        let [first, second, third, fourth] = face.corners.map(|corner| corner.ambient_occlusion);
+        let offsets = if first + third >= second + fourth {
+            [0, 1, 2, 2, 3, 0]
+        }
+        else {
+            [1, 2, 3, 3, 0, 1]
+        };
+        self.indices.extend(offsets.map(|offset| first_index + offset));
+    }
+
+    fn build(self) -> Result<Mesh, MeshCreationError> {
+        Mesh::builder()
+            .vertices(self.vertices)
+            .uv(self.uv)
+            .colors(self.colors)
+            .ambient_occlusion(self.ambient_occlusion)
+            .indices(self.indices)
+            .build()
+    }
+}
+
+/// Creates a [`Mesh`] with [`Mesh::vertices`], [`Mesh::uv`], [`Mesh::colors`],
+/// [`Mesh::ambient_occlusion`] and [`Mesh::indices`] from `snapshot` using
+/// `meshing_mode`. The vertices are relative to the origin of the section, the
+/// UVs are encoded as described in [`TILE_UV_STRIDE`], and the colors as
+/// described in [`light_color`]. The [`Mesh`] is empty if no faces are
+/// visible.
+pub fn mesh_section(snapshot: &SectionSnapshot, meshing_table: &BlockMeshingTable, meshing_mode: MeshingMode) -> Result<Mesh, MeshCreationError> {
+    if snapshot.is_empty {
+        return Mesh::builder().build();
+    }
+    match meshing_mode {
+        MeshingMode::Naive => mesh_section_naive(snapshot, meshing_table),
+        MeshingMode::Greedy => mesh_section_greedy(snapshot, meshing_table)
+    }
+}
+
+fn mesh_section_naive(snapshot: &SectionSnapshot, meshing_table: &BlockMeshingTable) -> Result<Mesh, MeshCreationError> {
+    let mut builder = QuadMeshBuilder::default();
+    for y in 0..SECTION_SIZE {
+        for z in 0..SECTION_SIZE {
+            for x in 0..SECTION_SIZE {
+                let local_position = IVec3::new(x, y, z);
+                for (face, face_template) in BlockFace::iter().zip(&meshing_table.face_templates) {
+                    if let Some(visible_face) = visible_face(snapshot, meshing_table, local_position, face) {
+                        builder.push_quad(face_template, local_position, Vec3::ONE, meshing_table.get(visible_face.block).face_tiles[face as usize], visible_face);
+                    }
+                }
+            }
+        }
+    }
+    builder.build()
+}
+
+/// For each face direction, goes through the section one slice at a time.
+/// The visible faces in a slice are collected into a mask, which is then
+/// covered with rectangles by repeatedly taking the first face left in the
+/// mask, extending it as far as possible along the first axis of the slice,
+/// then extending that row as far as possible along the second axis. Faces
+/// are only merged if their corners are shaded the same.
+#[expect(clippy::cast_precision_loss, reason = "Rectangles are at most SECTION_SIZE blocks long.")]
+#[expect(clippy::cast_possible_truncation, clippy::cast_possible_wrap, reason = "Positions in a slice are less than SECTION_SIZE.")]
+fn mesh_section_greedy(snapshot: &SectionSnapshot, meshing_table: &BlockMeshingTable) -> Result<Mesh, MeshCreationError> {
+    const SLICE_SIZE: usize = SECTION_SIZE as usize;
+    let mut builder = QuadMeshBuilder::default();
+    let mut mask = [None; SLICE_SIZE * SLICE_SIZE];
+    for (face, face_template) in BlockFace::iter().zip(&meshing_table.face_templates) {
+        // The axis along the normal of the face, and the two axes of the
+        // slices, as indices into vectors.
+        let normal_axis = face.normal().abs().max_position();
+        let first_axis = (normal_axis + 1) % 3;
+        let second_axis = (normal_axis + 2) % 3;
+        let to_local_position = |depth: i32, first: usize, second: usize| {
+            let mut local_position = IVec3::ZERO;
+            local_position[normal_axis] = depth;
+            local_position[first_axis] = first as i32;
+            local_position[second_axis] = second as i32;
+            local_position
+        };
+        for depth in 0..SECTION_SIZE {
+            for second in 0..SLICE_SIZE {
+                for first in 0..SLICE_SIZE {
+                    mask[second * SLICE_SIZE + first] = visible_face(snapshot, meshing_table, to_local_position(depth, first, second), face);
+                }
+            }
+            for second in 0..SLICE_SIZE {
+                for first in 0..SLICE_SIZE {
+                    let Some(visible_face) = mask[second * SLICE_SIZE + first] else {
+                        continue;
+                    };
+                    let width = mask[second * SLICE_SIZE + first..(second + 1) * SLICE_SIZE]
+                        .iter()
+                        .take_while(|other| **other == Some(visible_face))
+                        .count();
+                    let height = (second..SLICE_SIZE)
+                        .take_while(|row| mask[row * SLICE_SIZE + first..row * SLICE_SIZE + first + width].iter().all(|other| *other == Some(visible_face)))
+                        .count();
+                    for row in second..second + height {
+                        mask[row * SLICE_SIZE + first..row * SLICE_SIZE + first + width].fill(None);
+                    }
+                    let mut size = Vec3::ONE;
+                    size[first_axis] = width as f32;
+                    size[second_axis] = height as f32;
+                    builder.push_quad(face_template, to_local_position(depth, first, second), size, meshing_table.get(visible_face.block).face_tiles[face as usize], visible_face);
+                }
+            }
+        }
+    }
+    builder.build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assets_manager::*;
+    use crate::constants::*;
+    use crate::lighting::*;
+    use std::array;
+    use std::collections::*;
+
+    struct TestBlocks {
+        registry: BlockRegistry,
+        atlas: BlockTextureAtlas,
+        meshing_table: BlockMeshingTable
+    }
+
+    impl TestBlocks {
+        fn id(&self, path: &str) -> BlockId {
+            self.registry.id(&BlockName::new(BlockName::DEFAULT_NAMESPACE, path).expect("The name should be valid.")).expect("The block should be registered.")
+        }
+    }
+
+    fn test_blocks() -> TestBlocks {
+        let asset_cache = AssetCache::new(ASSETS_PATH).expect("The assets directory should exist.");
+        let block_definitions = asset_cache.load::<BlockDefinitions>(BLOCKS_ASSET_ID).expect("The block definitions should be loaded.");
+        let registry = BlockRegistry::new(&block_definitions.read()).expect("The block registry should be created.");
+        let atlas = BlockTextureAtlas::new(&asset_cache, &registry);
+        let meshing_table = BlockMeshingTable::new(&registry, &atlas);
+        TestBlocks { registry, atlas, meshing_table }
+    }
+
+    fn mesh_at(voxel_world: &VoxelWorld, blocks: &TestBlocks, section_position: IVec3, meshing_mode: MeshingMode) -> Mesh {
+        let snapshot = SectionSnapshot::new(voxel_world, section_position).expect("The section should be loaded.");
+        mesh_section(&snapshot, &blocks.meshing_table, meshing_mode).expect("The section should be meshed.")
+    }
+
+    /// A quad of a meshed section, decoded from its 4 vertices, which are in
+    /// the order of the [`FaceTemplate`].
+    struct MeshedQuad {
+        vertices: [Vec3; 4],
+        normal: IVec3,
+        tile: UVec2,
+        /// The UVs with the tile taken out, which go from 0 to the size of the
+        /// quad in blocks.
+        block_uv: [Vec2; 4],
+        colors: [U8Vec4; 4],
+        ambient_occlusion: [f32; 4]
+    }
+
+    impl MeshedQuad {
+        /// The smallest and largest corners of the quad.
+        fn bounds(&self) -> (Vec3, Vec3) {
+            (self.vertices.into_iter().reduce(Vec3::min).unwrap_or_default(), self.vertices.into_iter().reduce(Vec3::max).unwrap_or_default())
+        }
+    }
+
+    fn meshed_quads(mesh: &Mesh) -> Vec<MeshedQuad> {
+        (0..mesh.vertices().len()).step_by(4)
+            .map(|first| {
+                let vertices: [Vec3; 4] = array::from_fn(|corner| mesh.vertices()[first + corner]);
+                let uv: [Vec2; 4] = array::from_fn(|corner| mesh.uv()[first + corner]);
+                let tile = (uv[0] / TILE_UV_STRIDE).floor();
+                MeshedQuad {
+                    vertices,
+                    // The first 3 vertices of a face template are wound
+                    // counterclockwise from the front in the left-handed world.
+                    normal: (vertices[2] - vertices[0]).cross(vertices[1] - vertices[0]).normalize().round().as_ivec3(),
+                    tile: tile.as_uvec2(),
+                    block_uv: uv.map(|corner_uv| corner_uv - tile * TILE_UV_STRIDE),
+                    colors: array::from_fn(|corner| mesh.colors()[first + corner]),
+                    ambient_occlusion: array::from_fn(|corner| mesh.ambient_occlusion()[first + corner])
+                }
+            })
+            .collect()
+    }
+
+    /// The number of quads in `mesh` of the `face` of the block at
+    /// `block_position`, which are meshed with [`MeshingMode::Naive`].
+    fn face_count(mesh: &Mesh, section_position: IVec3, block_position: IVec3, face: BlockFace) -> usize {
+        let local_position = block_position - section_to_block_position(section_position);
+        let center = local_position.as_vec3() + Vec3::splat(0.5) + face.normal().as_vec3() * 0.5;
+        meshed_quads(mesh).iter()
+            .filter(|quad| {
+                let (min, max) = quad.bounds();
+                quad.normal == face.normal() && min.midpoint(max).abs_diff_eq(center, 1e-6)
+            })
+            .count()
+    }
+
+    #[test]
+    fn faces_between_opaque_blocks_are_culled() {
+        let blocks = test_blocks();
+        let stone = blocks.id("stone");
+        let mut voxel_world = VoxelWorld::default();
+        voxel_world.insert_column(IVec2::ZERO, ChunkColumn::default());
+        for position in [IVec3::new(5, 5, 5), IVec3::new(6, 5, 5)] {
+            voxel_world.set_block(position, stone).expect("The column should be loaded.");
+        }
+        let mesh = mesh_at(&voxel_world, &blocks, IVec3::ZERO, MeshingMode::Naive);
+        assert_eq!(meshed_quads(&mesh).len(), 10);
+        assert_eq!(face_count(&mesh, IVec3::ZERO, IVec3::new(5, 5, 5), BlockFace::PositiveX), 0);
+        assert_eq!(face_count(&mesh, IVec3::ZERO, IVec3::new(6, 5, 5), BlockFace::NegativeX), 0);
+        assert_eq!(face_count(&mesh, IVec3::ZERO, IVec3::new(5, 5, 5), BlockFace::NegativeX), 1);
+        assert_eq!(face_count(&mesh, IVec3::ZERO, IVec3::new(6, 5, 5), BlockFace::PositiveX), 1);
+    }
+
+    #[test]
+    fn faces_against_transparent_blocks_are_visible() {
+        let blocks = test_blocks();
+        let mut voxel_world = VoxelWorld::default();
+        voxel_world.insert_column(IVec2::ZERO, ChunkColumn::default());
+        let (water, stone, glass) = (IVec3::new(4, 5, 5), IVec3::new(5, 5, 5), IVec3::new(6, 5, 5));
+        let more_water = IVec3::new(3, 5, 5);
+        for (position, path) in [(more_water, "water"), (water, "water"), (stone, "stone"), (glass, "glass")] {
+            voxel_world.set_block(position, blocks.id(path)).expect("The column should be loaded.");
+        }
+        let mesh = mesh_at(&voxel_world, &blocks, IVec3::ZERO, MeshingMode::Naive);
+        assert_eq!(face_count(&mesh, IVec3::ZERO, stone, BlockFace::PositiveX), 1, "The stone should be visible through the glass.");
+        assert_eq!(face_count(&mesh, IVec3::ZERO, stone, BlockFace::NegativeX), 1, "The stone should be visible through the water.");
+        assert_eq!(face_count(&mesh, IVec3::ZERO, glass, BlockFace::NegativeX), 0, "The glass should be hidden by the stone.");
+        assert_eq!(face_count(&mesh, IVec3::ZERO, water, BlockFace::PositiveX), 0, "The water should be hidden by the stone.");
+        assert_eq!(face_count(&mesh, IVec3::ZERO, water, BlockFace::NegativeX), 0, "Water next to water should be hidden.");
+        assert_eq!(face_count(&mesh, IVec3::ZERO, more_water, BlockFace::PositiveX), 0, "Water next to water should be hidden.");
+        assert_eq!(face_count(&mesh, IVec3::ZERO, glass, BlockFace::PositiveY), 1);
+        assert_eq!(meshed_quads(&mesh).len(), 6 + 5 + 4 + 5);
+    }
+
+    #[test]
+    fn faces_against_neighbouring_sections_are_culled_unless_unloaded() {
+        let blocks = test_blocks();
+        let stone = blocks.id("stone");
+        let mut voxel_world = VoxelWorld::default();
+        voxel_world.insert_column(IVec2::ZERO, ChunkColumn::default());
+        voxel_world.insert_column(IVec2::X, ChunkColumn::default());
+        let positions = [
+            // Across the boundary with the column at X = 1.
+            IVec3::new(15, 5, 5), IVec3::new(16, 5, 5),
+            // Across the boundary with the section above.
+            IVec3::new(5, 15, 5), IVec3::new(5, 16, 5),
+            // Against the unloaded columns at X = -1 and Z = 1.
+            IVec3::new(0, 5, 8), IVec3::new(8, 5, 15),
+            // Against the top of the world.
+            IVec3::new(5, COLUMN_MAX_Y, 5)
+        ];
+        for position in positions {
+            voxel_world.set_block(position, stone).expect("The column should be loaded.");
+        }
+        let mesh = mesh_at(&voxel_world, &blocks, IVec3::ZERO, MeshingMode::Naive);
+        assert_eq!(face_count(&mesh, IVec3::ZERO, IVec3::new(15, 5, 5), BlockFace::PositiveX), 0);
+        assert_eq!(face_count(&mesh, IVec3::ZERO, IVec3::new(5, 15, 5), BlockFace::PositiveY), 0);
+        // Unloaded blocks are treated as air, so that the boundary is not left
+        // open until the neighbour is loaded and this section is meshed again.
+        assert_eq!(face_count(&mesh, IVec3::ZERO, IVec3::new(0, 5, 8), BlockFace::NegativeX), 1);
+        assert_eq!(face_count(&mesh, IVec3::ZERO, IVec3::new(8, 5, 15), BlockFace::PositiveZ), 1);
+        let neighbour_mesh = mesh_at(&voxel_world, &blocks, IVec3::X, MeshingMode::Naive);
+        assert_eq!(face_count(&neighbour_mesh, IVec3::X, IVec3::new(16, 5, 5), BlockFace::NegativeX), 0);
+        let top_section_position = IVec3::new(0, COLUMN_MAX_Y >> 4, 0);
+        let top_mesh = mesh_at(&voxel_world, &blocks, top_section_position, MeshingMode::Naive);
+        assert_eq!(face_count(&top_mesh, top_section_position, IVec3::new(5, COLUMN_MAX_Y, 5), BlockFace::PositiveY), 1);
+    }
+
+    /// Every face of a block is laid out like the same face of a [`Cuboid`],
+    /// with the tile of the texture of that face in the atlas, and V flipped
+    /// since textures have (0, 0) in the top left corner.
+    #[test]
+    fn atlas_uvs_follow_the_cuboid_face_layout() {
+        let blocks = test_blocks();
+        let grass_block = blocks.id("grass_block");
+        let textures = &blocks.registry.get(grass_block).expect("The block should be registered.").definition.textures;
+        let mut voxel_world = VoxelWorld::default();
+        voxel_world.insert_column(IVec2::ZERO, ChunkColumn::default());
+        let position = IVec3::new(5, 5, 5);
+        voxel_world.set_block(position, grass_block).expect("The column should be loaded.");
+        let cube = Cuboid { dimensions: Vec3::ONE }.as_mesh_builder().build().expect("A cuboid should always be a valid mesh.");
+        for meshing_mode in [MeshingMode::Naive, MeshingMode::Greedy] {
+            let quads = meshed_quads(&mesh_at(&voxel_world, &blocks, IVec3::ZERO, meshing_mode));
+            assert_eq!(quads.len(), BlockFace::COUNT);
+            for face in BlockFace::iter() {
+                let quad = quads.iter().find(|quad| quad.normal == face.normal()).expect("Every face of the block should be meshed.");
+                let texture_name = textures.face(face).expect("Grass blocks should have textures.");
+                assert_eq!(quad.tile, blocks.atlas.tile_position(blocks.atlas.tile_index(texture_name)), "The {face:?} face uses the wrong tile.");
+                for corner in 0..4 {
+                    let cube_vertex_index = face as usize * 4 + corner;
+                    assert_eq!(quad.vertices[corner], cube.vertices()[cube_vertex_index] + position.as_vec3() + Vec3::splat(0.5));
+                    let cube_uv = cube.uv()[cube_vertex_index];
+                    assert_eq!(quad.block_uv[corner], Vec2::new(cube_uv.x, 1.0 - cube_uv.y), "Corner {corner} of the {face:?} face has the wrong UV.");
+                }
+            }
+        }
+    }
+
+    /// How a single block face is covered by the quads of a mesh: the tile,
+    /// and the color and ambient occlusion of each corner.
+    type CoveredFace = (UVec2, [U8Vec4; 4], [f32; 4]);
+
+    /// Splits the quads of `mesh` into the faces of the blocks they cover,
+    /// keyed by the smallest corner of the face and its normal. Greedy quads
+    /// only merge faces that are shaded the same, so every face covered by a
+    /// quad has the corners of the quad.
+    fn covered_faces(mesh: &Mesh) -> HashMap<(IVec3, IVec3), CoveredFace> {
+        let mut covered_faces = HashMap::new();
+        for quad in meshed_quads(mesh) {
+            let (min, max) = quad.bounds();
+            let (quad_min, size) = (min.round().as_ivec3(), (max - min).round().as_ivec3());
+            let normal_axis = quad.normal.abs().max_position();
+            let [first_axis, second_axis] = [(normal_axis + 1) % 3, (normal_axis + 2) % 3];
+            for first in 0..size[first_axis] {
+                for second in 0..size[second_axis] {
+                    let mut face_min = quad_min;
+                    face_min[first_axis] += first;
+                    face_min[second_axis] += second;
+                    let covered_face = (quad.tile, quad.colors, quad.ambient_occlusion);
+                    assert!(covered_faces.insert((face_min, quad.normal), covered_face).is_none(), "The face at {face_min} facing {} is covered twice.", quad.normal);
+                }
+            }
+        }
+        covered_faces
+    }
+
+    fn light_world(voxel_world: &mut VoxelWorld, blocks: &TestBlocks) {
+        let updates = voxel_world.take_light_updates();
+        update_light(voxel_world, &BlockLightingTable::new(&blocks.registry), &updates);
+    }
+
+    /// Meshes a section with steps of different blocks, water, glass and a
+    /// light source in both modes, which should cover exactly the same faces
+    /// with the same tiles and shading.
+    #[test]
+    fn greedy_meshing_covers_the_same_faces_as_naive_meshing() {
+        let blocks = test_blocks();
+        let [stone, grass_block, water, glass, glowstone] = ["stone", "grass_block", "water", "glass", "glowstone"].map(|path| blocks.id(path));
+        let mut voxel_world = VoxelWorld::default();
+        voxel_world.insert_column(IVec2::ZERO, ChunkColumn::default());
+        for x in 0..SECTION_SIZE {
+            for z in 0..SECTION_SIZE {
+                // Terraces that are 4 blocks wide along X and 5 along Z.
+                let height = 3 + ((x >> 2) + ((z * 13) >> 6)).rem_euclid(3);
+                for y in 0..height {
+                    voxel_world.set_block(IVec3::new(x, y, z), stone).expect("The column should be loaded.");
+                }
+                voxel_world.set_block(IVec3::new(x, height, z), grass_block).expect("The column should be loaded.");
+                if (2..6).contains(&x) && (9..13).contains(&z) {
+                    voxel_world.set_block(IVec3::new(x, height + 1, z), water).expect("The column should be loaded.");
+                }
+            }
+        }
+        for y in 6..10 {
+            voxel_world.set_block(IVec3::new(8, y, 8), glass).expect("The column should be loaded.");
+        }
+        voxel_world.set_block(IVec3::new(12, 7, 3), glowstone).expect("The column should be loaded.");
+        light_world(&mut voxel_world, &blocks);
+
+        let naive_mesh = mesh_at(&voxel_world, &blocks, IVec3::ZERO, MeshingMode::Naive);
+        let greedy_mesh = mesh_at(&voxel_world, &blocks, IVec3::ZERO, MeshingMode::Greedy);
+        let naive_faces = covered_faces(&naive_mesh);
+        assert_eq!(naive_faces.len(), meshed_quads(&naive_mesh).len(), "Naive meshing should emit a quad for every face.");
+        assert!(covered_faces(&greedy_mesh) == naive_faces, "Greedy meshing should cover the same faces as naive meshing.");
+        assert!(meshed_quads(&greedy_mesh).len() < naive_faces.len());
+    }
+
+    #[test]
+    fn greedy_meshing_merges_flat_terrain() {
+        let blocks = test_blocks();
+        let [stone, grass_block] = ["stone", "grass_block"].map(|path| blocks.id(path));
+        let mut voxel_world = VoxelWorld::default();
+        voxel_world.insert_column(IVec2::ZERO, ChunkColumn::default());
+        for x in 0..SECTION_SIZE {
+            for z in 0..SECTION_SIZE {
+                for y in 0..3 {
+                    voxel_world.set_block(IVec3::new(x, y, z), stone).expect("The column should be loaded.");
+                }
+                voxel_world.set_block(IVec3::new(x, 3, z), grass_block).expect("The column should be loaded.");
+            }
+        }
+        light_world(&mut voxel_world, &blocks);
+        let naive_quads = meshed_quads(&mesh_at(&voxel_world, &blocks, IVec3::ZERO, MeshingMode::Naive));
+        let greedy_quads = meshed_quads(&mesh_at(&voxel_world, &blocks, IVec3::ZERO, MeshingMode::Greedy));
+        let top_quad_count = |quads: &[MeshedQuad]| quads.iter().filter(|quad| quad.normal == IVec3::Y).count();
+        assert_eq!(top_quad_count(&naive_quads), 256);
+        assert_eq!(top_quad_count(&greedy_quads), 1, "The lawn should be a single quad.");
+        assert!(greedy_quads.len() < naive_quads.len());
+    }
+
+    #[test]
+    fn light_is_averaged_and_rounded() {
+        assert_eq!(light_color(&[[15, 0]]), U8Vec4::new(255, 0, 0, 255));
+        assert_eq!(light_color(&[[15, 4], [0, 4], [0, 3]]), U8Vec4::new(85, 62, 0, 255));
+    }
+
+    /// The corner of the top face of a floor block between two stone blocks
+    /// on the floor is fully occluded, so the quad is split along the other
+    /// diagonal.
+    #[test]
+    fn corners_are_occluded_by_the_blocks_beside_them() {
+        let blocks = test_blocks();
+        let stone = blocks.id("stone");
+        let mut voxel_world = VoxelWorld::default();
+        voxel_world.insert_column(IVec2::ZERO, ChunkColumn::default());
+        for x in 0..SECTION_SIZE {
+            for z in 0..SECTION_SIZE {
+                voxel_world.set_block(IVec3::new(x, 0, z), stone).expect("The column should be loaded.");
+            }
+        }
+        for position in [IVec3::new(5, 1, 5), IVec3::new(6, 1, 4)] {
+            voxel_world.set_block(position, stone).expect("The column should be loaded.");
+        }
+        let mesh = mesh_at(&voxel_world, &blocks, IVec3::ZERO, MeshingMode::Naive);
+        let corner = |x, z| IVec3::new(x, 1, z);
+        let quad = mesh.vertices()
+            .chunks_exact(4)
+            .position(|vertices| {
+                let mut corners: Vec<_> = vertices.iter().map(Vec3::as_ivec3).collect();
+                corners.sort_by_key(|position| (position.x, position.z));
+                corners == [corner(6, 5), corner(6, 6), corner(7, 5), corner(7, 6)]
+            })
+            .expect("The top face of the floor block should be meshed.");
+        let vertex_indices = quad * 4..quad * 4 + 4;
+        #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss, reason = "The ambient occlusion is from 0 to 1.")]
+        let ambient_occlusion_at = |position: IVec3| {
+            let vertex_index = vertex_indices.clone()
+                .find(|vertex_index| mesh.vertices()[*vertex_index].as_ivec3() == position)
+                .expect("The quad should have a vertex at the corner.");
+            (mesh.ambient_occlusion()[vertex_index] * f32::from(MAX_AMBIENT_OCCLUSION)).round() as u8
+        };
+        assert_eq!(ambient_occlusion_at(corner(6, 5)), 0);
+        assert_eq!(ambient_occlusion_at(corner(6, 6)), 2);
+        assert_eq!(ambient_occlusion_at(corner(7, 5)), 2);
+        assert_eq!(ambient_occlusion_at(corner(7, 6)), 3);
+        // Each quad has 6 indices, and its diagonal is made of the 2 vertices
+        // that both triangles use.
+        let quad_indices = &mesh.indices()[quad * 6..quad * 6 + 6];
+        let mut diagonal: Vec<_> = quad_indices[..3].iter()
+            .filter(|index| quad_indices[3..].contains(index))
+            .map(|index| mesh.vertices()[*index].as_ivec3())
+            .collect();
+        diagonal.sort_by_key(|position| position.x);
+        assert_eq!(diagonal, [corner(6, 6), corner(7, 5)]);
+    }
+}
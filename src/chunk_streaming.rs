@@ -0,0 +1,104 @@
+//! Contains [`StreamingDistances`], which decides which columns are loaded
+//! around the chunk loader, and [`spiral_column_positions`], the order that
+//! they are loaded in. They are used by
+//! [`crate::systems::chunk_streaming::stream_columns_system`].
+//!
+//! Distances between columns are measured in columns along the axis they are
+//! the furthest apart on, so the columns within a distance form a square.
+
+use glam::*;
+use std::iter;
+
+/// How far from the column of the chunk loader columns are loaded and
+/// simulated, in columns.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StreamingDistances {
+    /// Columns up to this far away are generated and meshed.
+    pub render_distance: i32,
+    /// Columns up to this far away are meant to be simulated, as decided by
+    /// [`Self::is_simulated`]. It is capped at [`Self::render_distance`], since
+    /// only loaded columns can be simulated. Nothing is simulated yet, so this
+    /// has no effect.
+    pub simulation_distance: i32,
+    /// How much further than [`Self::render_distance`] a column must be
+    /// before it is unloaded. Without it, moving back and forth across the
+    /// border of a column would load and unload a whole row of columns every
+    /// time.
+    pub unload_margin: i32
+}
+
+impl StreamingDistances {
+    /// Returns `true` if the column at `column_position` should be unloaded
+    /// while the chunk loader is in the column at `center`. Columns between
+    /// [`Self::render_distance`] and [`Self::unload_margin`] beyond it are
+    /// neither loaded nor unloaded.
+    pub fn should_unload(&self, center: IVec2, column_position: IVec2) -> bool {
+        column_distance(center, column_position) > self.render_distance + self.unload_margin
+    }
+
+    /// Returns `true` if the column at `column_position` should be simulated
+    /// while the chunk loader is in the column at `center`.
+    #[expect(dead_code, reason = "Nothing is simulated yet.")]
+    pub fn is_simulated(&self, center: IVec2, column_position: IVec2) -> bool {
+        column_distance(center, column_position) <= self.simulation_distance.min(self.render_distance)
+    }
+}
+
+/// The distance between the columns at `a` and `b` along the axis they are the
+/// furthest apart on.
+pub fn column_distance(a: IVec2, b: IVec2) -> i32 {
+    (a - b).abs().max_element()
+}
+
+/// Iterates over the column positions within `radius` of `center`, starting
+/// at `center` and spiralling outwards one square ring at a time, so that the
+/// closest columns come first and neighbouring columns come one after another.
+pub fn spiral_column_positions(center: IVec2, radius: i32) -> impl Iterator<Item = IVec2> {
+    let rings = (1..=radius).flat_map(move |ring| {
+        // Each side of the ring starts at a corner and stops right before the
+        // next one, going counterclockwise from the corner with the smallest
+        // coordinates.
+        let corner = center - IVec2::splat(ring);
+        let sides = [IVec2::X, IVec2::Y, IVec2::NEG_X, IVec2::NEG_Y];
+        (0..4).zip(sides).flat_map(move |(side_index, direction)| {
+            let side_start = corner + sides[..side_index].iter().copied().sum::<IVec2>() * ring * 2;
+            (0..ring * 2).map(move |step| side_start + direction * step)
+        })
+    });
+    iter::once(center).chain(rings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::*;
+
+    #[test]
+    fn spiral_covers_every_column_once_from_the_center_outwards() {
+        let center = IVec2::new(3, -5);
+        let radius = 4;
+        let column_positions: Vec<_> = spiral_column_positions(center, radius).collect();
+        let unique_column_positions: HashSet<_> = column_positions.iter().copied().collect();
+        assert_eq!(column_positions.len(), 81, "Every column within the radius should be visited.");
+        assert_eq!(unique_column_positions.len(), column_positions.len(), "No column should be visited twice.");
+        assert!(column_positions.iter().all(|column_position| column_distance(center, *column_position) <= radius));
+        assert!(column_positions.array_windows().all(|[a, b]| column_distance(center, *a) <= column_distance(center, *b)),
+            "Closer columns should come first.");
+        assert!(column_positions.array_windows()
+            .filter(|[a, b]| column_distance(center, *a) == column_distance(center, *b))
+            .all(|[a, b]| column_distance(*a, *b) == 1),
+            "Each column should be next to the one before it in the same ring.");
+    }
+
+    #[test]
+    fn columns_near_the_border_are_neither_loaded_nor_unloaded() {
+        let distances = StreamingDistances { render_distance: 4, simulation_distance: 2, unload_margin: 2 };
+        let center = IVec2::ZERO;
+        assert!(!distances.should_unload(center, IVec2::new(4, -4)));
+        for column_position in [IVec2::new(5, 0), IVec2::new(0, -6)] {
+            assert!(column_distance(center, column_position) > distances.render_distance, "{column_position} is outside the render distance.");
+            assert!(!distances.should_unload(center, column_position), "{column_position} is inside the unload margin.");
+        }
+        assert!(distances.should_unload(center, IVec2::new(7, 1)));
+    }
+}
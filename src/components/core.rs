@@ -35,12 +35,14 @@ impl Default for TransformComponent {
 }
 
 impl From<TransformComponent> for Affine3A {
+    #[inline]
     fn from(value: TransformComponent) -> Self {
         Self::from(&value)
     }
 }
 
 impl From<&TransformComponent> for Affine3A {
+    #[inline]
     fn from(value: &TransformComponent) -> Self {
         Self::from_scale_rotation_translation(value.scale, value.rotation, value.position)
     }
@@ -58,6 +60,11 @@ pub struct GlobalTransformComponent {
 }
 
 impl GlobalTransformComponent {
+    /// The global (world) transform of an [`bevy_ecs::entity::Entity`].
+    pub const fn global_transform(&self) -> &Affine3A {
+        &self.global_transform
+    }
+
     /// Transforms `transform` with the transformation stored in
     /// [`GlobalTransformComponent`].
     pub fn mul_transform(&self, transform: &TransformComponent) -> Self {
@@ -76,6 +83,7 @@ impl<T: Borrow<Self>> Mul<T> for GlobalTransformComponent {
 impl<T: Borrow<GlobalTransformComponent>> Mul<T> for &GlobalTransformComponent {
     type Output = GlobalTransformComponent;
 
+    #[inline]
     fn mul(self, rhs: T) -> Self::Output {
         GlobalTransformComponent { global_transform: self.global_transform * rhs.borrow().global_transform }
     }
@@ -97,9 +105,19 @@ pub struct TransformTreeChangedComponent;
 #[derive(Component)]
 #[require(TransformComponent)]
 pub struct CameraComponent {
-    pub projection_mode: ProjectionMode
+    pub projection_mode: ProjectionMode,
+    /// Whether the depth range is reversed. See
+    /// [`ProjectionMode::projection_matrix`].
+    pub reverse_z: bool
 }
 
+/// Marks the entity that columns are loaded around, such as the player. If no
+/// entity has this, columns are loaded around the first [`CameraComponent`]
+/// instead. See [`crate::systems::chunk_streaming`].
+#[derive(Component)]
+#[require(TransformComponent)]
+pub struct ChunkLoaderComponent;
+
 #[derive(Component)]
 #[require(TransformComponent)]
 pub struct MeshRendererComponent {
@@ -1,27 +1,46 @@
 use const_format::*;
+use wgpu::*;
+use winit::keyboard::*;
 
 /// The bind group index (the number inside `@group()` in the shader to use)
 /// that contains bindings that only need to be updated once per frame per
 /// camera. The view matrix is one such binding.
-pub const PER_VIEW_BIND_GROUP: i32 = 0;
+pub const PER_VIEW_BIND_GROUP: u32 = 0;
 
 /// The bind group index (the number inside `@group()` in the shader to use)
 /// that the [`wgpu::BindGroup`] returned by
 /// [`crate::material::Material::bind_group`] will be put. All objects using the
 /// same [`crate::material::Material`] will use the same [`wgpu::BindGroup`] at
 /// this index.
-pub const PER_MATERIAL_BIND_GROUP: i32 = 1;
+pub const PER_MATERIAL_BIND_GROUP: u32 = 1;
 
 /// The bind group index (the number inside `@group()` in the shader to use)
 /// that contains bindings that need to be updated for every object to render.
 /// The model matrix is one such binding.
-pub const PER_OBJECT_BIND_GROUP: i32 = 2;
+pub const PER_OBJECT_BIND_GROUP: u32 = 2;
+
+/// The format of [`crate::resources::wgpu::WgpuResource::depth_texture`].
+/// Every pipeline that renders to the surface is created with this format, so
+/// changing it here is enough to change it everywhere.
+pub const DEPTH_TEXTURE_FORMAT: TextureFormat = TextureFormat::Depth32Float;
+
+/// The format of [`crate::resources::wgpu::RenderTarget::Offscreen`].
+pub const OFFSCREEN_TEXTURE_FORMAT: TextureFormat = TextureFormat::Rgba8UnormSrgb;
+
+/// Pressing this key saves a screenshot to
+/// [`crate::screenshot::SCREENSHOTS_PATH`].
+pub const SCREENSHOT_KEY: KeyCode = KeyCode::F2;
 
 /// The path to the assets directory. This path is relative to the location of
 /// the executable.
 pub const ASSETS_PATH: &str = "assets";
 
+/// The path to the directory that the world is saved in, next to
+/// [`ASSETS_PATH`]. This path is relative to the location of the executable.
+pub const WORLD_PATH: &str = "world";
+
 /// The path to the directory that stores shaders in the assets directory. This
 /// path is relative to the location of the executable.
 #[expect(unused_qualifications, reason = "Seems to be a false positive.")]
+#[expect(dead_code, reason = "Shaders are currently loaded by their asset IDs instead of their paths.")]
 pub const SHADERS_PATH: &str = formatcp!("{ASSETS_PATH}/shaders");
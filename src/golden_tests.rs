@@ -0,0 +1,189 @@
+//! Golden-image tests that render small scenes using [`HeadlessApp`] on the
+//! fallback adapter and compare them against the reference PNGs in
+//! [`GOLDEN_IMAGES_PATH`].
+//!
+//! If a test fails, the rendered image and an image highlighting the differing
+//! pixels are written to [`DIFF_IMAGES_PATH`]. To (re)generate the reference
+//! images after an intended change to the renderer, run the tests with the
+//! environment variable `UPDATE_GOLDEN_IMAGES` set, and check the new images
+//! by eye before committing them.
+
+use bevy_ecs::world::*;
+use futures::executor::*;
+use glam::*;
+use image::*;
+use std::env;
+use std::fs;
+use std::path::*;
+use std::sync::*;
+use crate::asset::*;
+use crate::camera::*;
+use crate::components::core::*;
+use crate::headless::*;
+use crate::materials::*;
+use crate::mesh::*;
+use crate::shapes::*;
+use crate::world::*;
+
+/// Relative to the root of the crate.
+const GOLDEN_IMAGES_PATH: &str = "tests/golden_images";
+
+/// Relative to the root of the crate.
+const DIFF_IMAGES_PATH: &str = "target/golden_image_diffs";
+
+const IMAGE_SIZE: u32 = 256;
+
+/// The largest perceptual difference between two pixels, as returned by
+/// [`perceptual_difference`], for them to be considered the same. Allows for
+/// slightly different rasterization and rounding between driver versions.
+const PIXEL_DIFFERENCE_THRESHOLD: f32 = 0.1;
+
+/// The largest fraction of pixels that may differ before a test fails. Allows
+/// for pixels on the edges of triangles to be covered differently.
+const MAX_DIFFERING_PIXEL_FRACTION: f32 = 0.002;
+
+/// Renders a single frame of `world` and compares it against the reference
+/// image called `name`.
+fn assert_matches_golden_image(name: &str, world: World) {
+    let mut headless_app = block_on(HeadlessApp::new(world, &HeadlessAppDescriptor {
+        width: IMAGE_SIZE,
+        height: IMAGE_SIZE,
+        force_fallback_adapter: true
+    })).expect("The fallback adapter should be available.");
+    headless_app.render().expect("The frame should be rendered.");
+    let actual = headless_app.capture_frame().expect("The frame should be read back.");
+    let crate_root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let golden_image_path = crate_root.join(GOLDEN_IMAGES_PATH).join(format!("{name}.png"));
+    if env::var_os("UPDATE_GOLDEN_IMAGES").is_some() {
+        fs::create_dir_all(crate_root.join(GOLDEN_IMAGES_PATH)).expect("The golden images directory should be created.");
+        actual.save(&golden_image_path).expect("The golden image should be saved.");
+        return;
+    }
+    let diff_images_path = crate_root.join(DIFF_IMAGES_PATH);
+    let save_actual_image = || {
+        fs::create_dir_all(&diff_images_path).expect("The diff images directory should be created.");
+        actual.save(diff_images_path.join(format!("{name}-actual.png"))).expect("The actual image should be saved.");
+    };
+    let expected_result = open(&golden_image_path);
+    if expected_result.is_err() {
+        save_actual_image();
+    }
+    let expected = expected_result.map(DynamicImage::into_rgba8).expect("The golden image should be readable. Run the tests with UPDATE_GOLDEN_IMAGES set to create it.");
+    assert_eq!(expected.dimensions(), actual.dimensions(), "The golden image {} has the wrong size.", golden_image_path.display());
+    let diff = RgbaImage::from_fn(IMAGE_SIZE, IMAGE_SIZE, |x, y| {
+        let expected_pixel = expected.get_pixel(x, y);
+        if perceptual_difference(*expected_pixel, *actual.get_pixel(x, y)) > PIXEL_DIFFERENCE_THRESHOLD {
+            Rgba([255, 0, 0, 255])
+        }
+        else {
+            // Faded so that the red pixels stand out.
+            let luma = (expected_pixel.to_luma()[0] >> 2) + 191;
+            Rgba([luma, luma, luma, 255])
+        }
+    });
+    let differing_pixel_count = diff.pixels().filter(|pixel| pixel[1] == 0).count();
+    let matches = differing_pixel_fraction(differing_pixel_count) <= MAX_DIFFERING_PIXEL_FRACTION;
+    if !matches {
+        save_actual_image();
+        diff.save(diff_images_path.join(format!("{name}-diff.png"))).expect("The diff image should be saved.");
+    }
+    assert!(matches, "{differing_pixel_count} pixels differ from the golden image {}. See {} for the differences.", golden_image_path.display(), diff_images_path.display());
+}
+
+/// Returns a value between 0 and 1 that approximates how differently two
+/// colors are perceived, based on the YIQ color space as in
+/// <https://github.com/mapbox/pixelmatch>. The colors are blended with white
+/// first so that differences in transparent pixels matter less.
+#[expect(clippy::float_arithmetic, reason = "Comparing colors requires floating point arithmetic.")]
+fn perceptual_difference(a: Rgba<u8>, b: Rgba<u8>) -> f32 {
+    // The rows of this matrix convert RGB into Y, I, and Q respectively.
+    let yiq_from_rgb = Mat3::from_cols(
+        Vec3::new(0.298_895_3, 0.595_977_9, 0.211_470_2),
+        Vec3::new(0.586_622_5, -0.274_171_6, -0.522_617_6),
+        Vec3::new(0.114_482_2, -0.321_801_3, 0.311_147_4)
+    );
+    let yiq_weights = Vec3::new(0.5053, 0.299, 0.1957);
+    let max_delta = 35215.0;
+    let to_yiq = |pixel: Rgba<u8>| {
+        let alpha = f32::from(pixel[3]) / 255.0;
+        let rgb = Vec3::new(f32::from(pixel[0]), f32::from(pixel[1]), f32::from(pixel[2]));
+        yiq_from_rgb * Vec3::splat(255.0).lerp(rgb, alpha)
+    };
+    let delta = to_yiq(a) - to_yiq(b);
+    // The square root makes thresholds mean the same as they do in
+    // pixelmatch.
+    (delta.dot(delta * yiq_weights) / max_delta).sqrt()
+}
+
+#[expect(clippy::float_arithmetic, reason = "Calculating a fraction requires a division.")]
+#[expect(clippy::cast_precision_loss, reason = "The number of pixels is small enough.")]
+fn differing_pixel_fraction(differing_pixel_count: usize) -> f32 {
+    differing_pixel_count as f32 / (IMAGE_SIZE * IMAGE_SIZE) as f32
+}
+
+/// Creates a [`World`] with a camera that looks down at the origin.
+fn create_world(reverse_z: bool) -> World {
+    let mut world = World::new();
+    add_asset_cache_resources(&mut world).expect("The assets directory should exist.");
+    world.spawn((
+        CameraComponent {
+            projection_mode: ProjectionMode::Perspective(PerspectiveProjectionConfig {
+                horizontal_fov: 60_f32.to_radians(),
+                near_clipping_plane_distance: 0.1,
+                far_clipping_plane_distance: 100.0
+            }),
+            reverse_z
+        },
+        TransformComponent {
+            position: Vec3::new(0.0, 2.0, -3.5),
+            rotation: Quat::from_rotation_x(0.52),
+            ..Default::default()
+        }
+    ));
+    world
+}
+
+fn spawn_cuboid(world: &mut World, dimensions: Vec3, position: Vec3, rotation: Quat, color: Vec4) {
+    let mesh = Cuboid { dimensions }.as_mesh_builder().build().expect("A cuboid should always be a valid mesh.");
+    world.spawn((
+        MeshRendererComponent {
+            material: AssetHandle::Dynamic(Arc::new(UnlitMaterial { color })),
+            mesh: AssetHandle::Dynamic(Arc::new(mesh))
+        },
+        TransformComponent {
+            position,
+            rotation,
+            ..Default::default()
+        }
+    ));
+}
+
+fn spawn_overlapping_cuboids(world: &mut World) {
+    // Closer to the camera, but spawned first, so it would be drawn over by the
+    // other cuboid if depth testing did not work.
+    spawn_cuboid(world, Vec3::ONE, Vec3::new(0.5, 0.5, 0.0), Quat::from_rotation_y(0.4), Vec4::new(0.2, 0.4, 0.8, 1.0));
+    spawn_cuboid(world, Vec3::new(3.0, 1.0, 1.0), Vec3::new(0.0, 0.0, 1.0), Quat::IDENTITY, Vec4::new(0.8, 0.2, 0.2, 1.0));
+}
+
+#[test]
+fn single_cuboid() {
+    let mut world = create_world(true);
+    spawn_cuboid(&mut world, Vec3::ONE, Vec3::ZERO, Quat::from_rotation_y(0.6), Vec4::new(0.2, 0.6, 0.3, 1.0));
+    assert_matches_golden_image("single_cuboid", world);
+}
+
+#[test]
+fn overlapping_cuboids() {
+    let mut world = create_world(true);
+    spawn_overlapping_cuboids(&mut world);
+    assert_matches_golden_image("overlapping_cuboids", world);
+}
+
+/// Reverse-Z should only change the precision of the depth buffer, so the
+/// result must look the same as [`overlapping_cuboids`].
+#[test]
+fn overlapping_cuboids_without_reverse_z() {
+    let mut world = create_world(false);
+    spawn_overlapping_cuboids(&mut world);
+    assert_matches_golden_image("overlapping_cuboids", world);
+}
@@ -0,0 +1,55 @@
+//! Contains the steps to set up [`wgpu`] that are shared by
+//! [`crate::application_handler::App`] and [`crate::headless::HeadlessApp`].
+
+use log::*;
+use wgpu::*;
+
+/// * `backends`: Headless rendering uses [`Backends::all`] so that software
+///   renderers exposed through secondary backends can be found too.
+pub fn create_instance(backends: Backends) -> Instance {
+    Instance::new(&InstanceDescriptor {
+        backends,
+        flags: InstanceFlags::from_env_or_default(),
+        ..Default::default()
+    })
+}
+
+/// * `compatible_surface`: [`None`] when rendering headlessly.
+/// * `force_fallback_adapter`: If `true`, only software adapters, such as
+///   llvmpipe, are considered, which makes the output the same on every
+///   machine.
+pub async fn create_adapter(instance: &Instance, compatible_surface: Option<&Surface<'_>>, force_fallback_adapter: bool) -> Result<Adapter, RequestAdapterError> {
+    match instance.request_adapter(&RequestAdapterOptions {
+        power_preference: PowerPreference::HighPerformance,
+        compatible_surface,
+        force_fallback_adapter
+    }).await {
+        Ok(adapter) => {
+            info!("The adapter has been created. {adapter:#?}");
+            info!("Adapter limits: {:#?}", adapter.limits());
+            info!("Adapter features: {:#?}", adapter.features());
+            Ok(adapter)
+        }
+        Err(err) => {
+            error!("The adapter could not be created. {err:#?}");
+            Err(err)
+        }
+    }
+}
+
+pub async fn create_device_and_queue(adapter: &Adapter) -> Result<(Device, Queue), RequestDeviceError> {
+    match adapter.request_device(&DeviceDescriptor {
+        label: Some("default-device"),
+        required_limits: adapter.limits(),
+        ..Default::default()
+    }).await {
+        Ok(val) => {
+            info!("The device and command queue has been created. {:#?}, {:#?}", val.0, val.1);
+            Ok(val)
+        },
+        Err(err) => {
+            error!("The device and command queue could not be created. {err:#?}");
+            Err(err)
+        }
+    }
+}
@@ -0,0 +1,235 @@
+//! Contains the GPU-side counterparts of [`Mesh`]es.
+
+use strum::*;
+use wgpu::*;
+use wgpu::util::*;
+use crate::mesh::*;
+
+/// Describes how the vertex attributes of a [`Mesh`] are interleaved in a
+/// single vertex buffer. The vertex attributes are always laid out in the order
+/// of the discriminants of [`VertexAttributeKind`], so two layouts that
+/// contain the same [`VertexAttributeKind`]s in the same formats produce
+/// identical vertex buffers even if their shader locations differ.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct InterleavedVertexLayout {
+    vertex_attributes: Vec<VertexAttribute>,
+    vertex_attribute_kinds: Vec<VertexAttributeKind>,
+    array_stride: BufferAddress
+}
+
+/// Identifies the vertex buffers that [`InterleavedVertexLayout`]s produce.
+/// See [`InterleavedVertexLayout::vertex_buffer_key`].
+pub type VertexBufferKey = Vec<(VertexAttributeKind, VertexFormat)>;
+
+impl InterleavedVertexLayout {
+    /// Creates a layout from the return values of
+    /// [`crate::material::Material::attribute_to_shader_location_mapping`] and
+    /// [`crate::material::Material::custom_attribute_formats`].
+    pub fn from_shader_location_mapping(mapping: [Option<u32>; VertexAttributeKind::COUNT], custom_attribute_formats: [VertexFormat; CUSTOM_VERTEX_ATTRIBUTE_COUNT]) -> Self {
+        let mut vertex_attributes = Vec::new();
+        let mut vertex_attribute_kinds = Vec::new();
+        let mut array_stride = 0;
+        for (kind, shader_location_option) in VertexAttributeKind::iter().zip(mapping) {
+            let Some(shader_location) = shader_location_option else {
+                continue;
+            };
+            let format = kind.custom_index().map_or_else(|| vertex_attribute_format(kind), |index| custom_attribute_formats[index]);
+            vertex_attributes.push(VertexAttribute { format, offset: array_stride, shader_location });
+            vertex_attribute_kinds.push(kind);
+            array_stride += format.size();
+        }
+        Self { vertex_attributes, vertex_attribute_kinds, array_stride }
+    }
+
+    /// The [`VertexBufferLayout`] to use in a [`RenderPipeline`].
+    pub fn vertex_buffer_layout(&self) -> VertexBufferLayout<'_> {
+        VertexBufferLayout {
+            array_stride: self.array_stride,
+            step_mode: VertexStepMode::Vertex,
+            attributes: &self.vertex_attributes
+        }
+    }
+
+    /// Layouts with the same key produce identical vertex buffers, so they can
+    /// share them.
+    pub fn vertex_buffer_key(&self) -> VertexBufferKey {
+        self.vertex_attribute_kinds.iter().copied().zip(self.vertex_attributes.iter().map(|attribute| attribute.format)).collect()
+    }
+
+    /// Interleaves the vertex attributes of `mesh` according to this layout.
+    /// Returns [`None`] if `mesh` lacks any of the vertex attributes, or if
+    /// any of its custom vertex attributes is in a different format.
+    fn interleave(&self, mesh: &Mesh) -> Option<Vec<u8>> {
+        let attribute_bytes = self.vertex_attribute_kinds.iter()
+            .zip(&self.vertex_attributes)
+            .map(|(kind, attribute)| {
+                let (bytes, format) = vertex_attribute_bytes_and_format(mesh, *kind);
+                (mesh.has_attribute(*kind) && format == attribute.format).then_some(bytes)
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let vertex_count = self.vertex_attribute_kinds.iter()
+            .map(|kind| mesh.attribute_length(*kind))
+            .min()
+            .unwrap_or(0);
+        #[expect(clippy::cast_possible_truncation, reason = "The stride of a vertex is never large enough to overflow a usize.")]
+        let mut vertex_data = Vec::with_capacity(vertex_count * self.array_stride as usize);
+        for vertex_index in 0..vertex_count {
+            for (bytes, attribute) in attribute_bytes.iter().zip(&self.vertex_attributes) {
+                let size = attribute.format.size();
+                #[expect(clippy::cast_possible_truncation, reason = "The size of a vertex attribute is never large enough to overflow a usize.")]
+                let start = vertex_index * size as usize;
+                #[expect(clippy::cast_possible_truncation, reason = "The size of a vertex attribute is never large enough to overflow a usize.")]
+                let end = start + size as usize;
+                vertex_data.extend_from_slice(&bytes[start..end]);
+            }
+        }
+        Some(vertex_data)
+    }
+}
+
+/// The GPU buffers created from a [`Mesh`] for a particular
+/// [`InterleavedVertexLayout`].
+pub struct GpuMesh<'a> {
+    pub vertex_buffer: &'a Buffer,
+    /// [`None`] if [`Mesh::indices`] is implied.
+    pub index_buffer: Option<&'a GpuIndexBuffer>,
+    pub index_count: u32
+}
+
+impl GpuMesh<'_> {
+    /// Binds the buffers to slot 0 of `render_pass` and draws them.
+    pub fn draw(&self, render_pass: &mut RenderPass<'_>) {
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        if let Some(index_buffer) = self.index_buffer {
+            render_pass.set_index_buffer(index_buffer.buffer.slice(..), index_buffer.format);
+            render_pass.draw_indexed(0..self.index_count, 0, 0..1);
+        }
+        else {
+            render_pass.draw(0..self.index_count, 0..1);
+        }
+    }
+}
+
+/// An index buffer that uses [`IndexFormat::Uint16`] if all indices fit in a
+/// [`u16`], or [`IndexFormat::Uint32`] otherwise.
+pub struct GpuIndexBuffer {
+    pub buffer: Buffer,
+    pub format: IndexFormat
+}
+
+impl GpuIndexBuffer {
+    /// Returns [`None`] if [`Mesh::indices`] is implied.
+    pub fn new(device: &Device, mesh: &Mesh) -> Option<Self> {
+        let max_index = mesh.indices().iter().copied().max()?;
+        let (contents, format) = if u16::try_from(max_index).is_ok() {
+            #[expect(clippy::cast_possible_truncation, reason = "All indices have been checked to fit in a u16.")]
+            let indices: Vec<u16> = mesh.indices().iter().map(|index| *index as u16).collect();
+            (bytemuck::cast_slice(&indices).to_vec(), IndexFormat::Uint16)
+        }
+        else {
+            #[expect(clippy::cast_possible_truncation, reason = "Meshes with more than u32::MAX vertices cannot be rendered anyway.")]
+            let indices: Vec<u32> = mesh.indices().iter().map(|index| *index as u32).collect();
+            (bytemuck::cast_slice(&indices).to_vec(), IndexFormat::Uint32)
+        };
+        let buffer = device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("mesh-index-buffer"),
+            contents: &contents,
+            usage: BufferUsages::INDEX
+        });
+        Some(Self { buffer, format })
+    }
+}
+
+/// Creates the vertex buffer of `mesh` interleaved according to `layout`.
+/// Returns [`None`] if `mesh` lacks any of the vertex attributes in `layout`.
+pub fn create_interleaved_vertex_buffer(device: &Device, mesh: &Mesh, layout: &InterleavedVertexLayout) -> Option<Buffer> {
+    let vertex_data = layout.interleave(mesh)?;
+    Some(device.create_buffer_init(&BufferInitDescriptor {
+        label: Some("mesh-vertex-buffer"),
+        contents: &vertex_data,
+        usage: BufferUsages::VERTEX
+    }))
+}
+
+/// The [`VertexFormat`] that each vertex attribute of a [`Mesh`] is uploaded
+/// as. Custom vertex attributes are uploaded in the format of their
+/// [`CustomVertexAttribute`] variant instead, and their formats in a layout
+/// come from the [`crate::material::Material`], so the format returned for
+/// them is only the default of
+/// [`crate::material::Material::custom_attribute_formats`].
+const fn vertex_attribute_format(kind: VertexAttributeKind) -> VertexFormat {
+    match kind {
+        VertexAttributeKind::Positions | VertexAttributeKind::Normals => VertexFormat::Float32x3,
+        VertexAttributeKind::Uv | VertexAttributeKind::Uv2 => VertexFormat::Float32x2,
+        VertexAttributeKind::Colors => VertexFormat::Unorm8x4,
+        VertexAttributeKind::AmbientOcclusion => VertexFormat::Float32,
+        VertexAttributeKind::Tangents => VertexFormat::Float32x4,
+        VertexAttributeKind::Custom0 | VertexAttributeKind::Custom1 | VertexAttributeKind::Custom2 | VertexAttributeKind::Custom3 => VertexFormat::Uint32
+    }
+}
+
+/// Returns the bytes of the vertex attribute of `kind` in `mesh`, along with
+/// the [`VertexFormat`] that they are in.
+fn vertex_attribute_bytes_and_format(mesh: &Mesh, kind: VertexAttributeKind) -> (&[u8], VertexFormat) {
+    let bytes = match kind {
+        VertexAttributeKind::Positions => bytemuck::cast_slice(mesh.vertices()),
+        VertexAttributeKind::Uv => bytemuck::cast_slice(mesh.uv()),
+        VertexAttributeKind::Colors => bytemuck::cast_slice(mesh.colors()),
+        VertexAttributeKind::AmbientOcclusion => bytemuck::cast_slice(mesh.ambient_occlusion()),
+        VertexAttributeKind::Normals => bytemuck::cast_slice(mesh.normals()),
+        VertexAttributeKind::Tangents => bytemuck::cast_slice(mesh.tangents()),
+        VertexAttributeKind::Uv2 => bytemuck::cast_slice(mesh.uv2()),
+        VertexAttributeKind::Custom0 | VertexAttributeKind::Custom1 | VertexAttributeKind::Custom2 | VertexAttributeKind::Custom3 => {
+            return mesh.custom_attribute(kind).map_or_else(|| (&[], vertex_attribute_format(kind)), custom_attribute_bytes_and_format);
+        }
+    };
+    (bytes, vertex_attribute_format(kind))
+}
+
+fn custom_attribute_bytes_and_format(attribute: &CustomVertexAttribute) -> (&[u8], VertexFormat) {
+    match attribute {
+        CustomVertexAttribute::Uint32(values) => (bytemuck::cast_slice(values), VertexFormat::Uint32),
+        CustomVertexAttribute::Uint32x2(values) => (bytemuck::cast_slice(values), VertexFormat::Uint32x2),
+        CustomVertexAttribute::Uint32x4(values) => (bytemuck::cast_slice(values), VertexFormat::Uint32x4),
+        CustomVertexAttribute::Sint32(values) => (bytemuck::cast_slice(values), VertexFormat::Sint32),
+        CustomVertexAttribute::Sint32x2(values) => (bytemuck::cast_slice(values), VertexFormat::Sint32x2),
+        CustomVertexAttribute::Sint32x4(values) => (bytemuck::cast_slice(values), VertexFormat::Sint32x4),
+        CustomVertexAttribute::Float32(values) => (bytemuck::cast_slice(values), VertexFormat::Float32),
+        CustomVertexAttribute::Float32x2(values) => (bytemuck::cast_slice(values), VertexFormat::Float32x2),
+        CustomVertexAttribute::Float32x3(values) => (bytemuck::cast_slice(values), VertexFormat::Float32x3),
+        CustomVertexAttribute::Float32x4(values) => (bytemuck::cast_slice(values), VertexFormat::Float32x4),
+        CustomVertexAttribute::Unorm8x4(values) => (bytemuck::cast_slice(values), VertexFormat::Unorm8x4)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::*;
+
+    #[test]
+    fn custom_attributes_are_interleaved_in_the_formats_of_the_material() {
+        let mut mapping = [None; VertexAttributeKind::COUNT];
+        mapping[VertexAttributeKind::Positions as usize] = Some(0);
+        mapping[VertexAttributeKind::Custom1 as usize] = Some(1);
+        let mut custom_attribute_formats = [VertexFormat::Uint32; CUSTOM_VERTEX_ATTRIBUTE_COUNT];
+        let layout = InterleavedVertexLayout::from_shader_location_mapping(mapping, custom_attribute_formats);
+        assert_eq!(layout.vertex_buffer_layout().array_stride, 16);
+        let mesh_with_packed_data = |packed_data| Mesh::builder()
+            .vertices(vec![Vec3::ONE; 3])
+            .custom_attributes(vec![CustomVertexAttribute::Float32(vec![0.0; 3]), packed_data])
+            .build()
+            .expect("Every attribute should be long enough.");
+        let vertex_data = layout.interleave(&mesh_with_packed_data(CustomVertexAttribute::Uint32(vec![7, 8, 9]))).expect("The mesh should have every attribute.");
+        let expected: Vec<u8> = [7_u32, 8, 9]
+            .iter()
+            .flat_map(|packed| bytemuck::bytes_of(&Vec3::ONE).iter().chain(bytemuck::bytes_of(packed)).copied())
+            .collect();
+        assert_eq!(vertex_data, expected);
+        assert!(layout.interleave(&mesh_with_packed_data(CustomVertexAttribute::Sint32(vec![7, 8, 9]))).is_none());
+        custom_attribute_formats[1] = VertexFormat::Sint32;
+        let signed_layout = InterleavedVertexLayout::from_shader_location_mapping(mapping, custom_attribute_formats);
+        assert_ne!(layout.vertex_buffer_key(), signed_layout.vertex_buffer_key());
+        assert!(signed_layout.interleave(&mesh_with_packed_data(CustomVertexAttribute::Sint32(vec![7, 8, 9]))).is_some());
+    }
+}
@@ -0,0 +1,120 @@
+//! Contains [`HeadlessApp`], which runs the app without a window so that it can
+//! render in CI or on a server with no display.
+
+use bevy_ecs::world::*;
+use image::*;
+use log::*;
+use thiserror::*;
+use wgpu::*;
+use crate::constants::*;
+use crate::gpu::*;
+use crate::resources::chunk_meshing::*;
+use crate::resources::terrain_generation::*;
+use crate::resources::wgpu::*;
+use crate::screenshot::*;
+use crate::schedules::*;
+
+/// Runs the same [`bevy_ecs::schedule::Schedule`]s as
+/// [`crate::application_handler::App`], but renders into a
+/// [`RenderTarget::Offscreen`] instead of a window. Systems that need a window,
+/// such as the ones that render `egui`, are skipped.
+pub struct HeadlessApp {
+    world: World,
+    schedules: AppSchedules
+}
+
+impl HeadlessApp {
+    /// Sets up [`wgpu`] without a [`Surface`] and runs [`StartupSchedule`] on
+    /// `world`.
+    pub async fn new(mut world: World, descriptor: &HeadlessAppDescriptor) -> Result<Self, HeadlessAppInitializationError> {
+        let &HeadlessAppDescriptor { width, height, force_fallback_adapter } = descriptor;
+        let wgpu_instance = create_instance(Backends::all());
+        let adapter = create_adapter(&wgpu_instance, None, force_fallback_adapter).await?;
+        let (device, command_queue) = create_device_and_queue(&adapter).await?;
+        let render_target = RenderTarget::new_offscreen(&device, width, height);
+        let depth_texture = DepthTexture::new(&device, DEPTH_TEXTURE_FORMAT, width, height);
+        info!("The offscreen render target has been created with the size {width}x{height}.");
+        world.insert_resource(WgpuResource { render_target, depth_texture, device, command_queue });
+        let mut schedules = AppSchedules::new();
+        schedules.run_startup(&mut world);
+        Ok(Self { world, schedules })
+    }
+
+    /// Runs a frame, rendering it into the offscreen [`Texture`].
+    pub fn render(&mut self) -> Result<(), HeadlessRenderError> {
+        let wgpu_resource: &WgpuResource = self.world.resource();
+        let RenderTarget::Offscreen(texture) = &wgpu_resource.render_target else {
+            let err = HeadlessRenderError::NotOffscreen;
+            error!("{err}");
+            return Err(err);
+        };
+        self.world.insert_resource(WgpuFrameResource { output_texture: FrameOutputTexture::Offscreen(texture.clone()) });
+        self.schedules.run_frame(&mut self.world);
+        self.world.remove_resource::<WgpuFrameResource>();
+        Ok(())
+    }
+
+    /// Runs [`ShutdownSchedule`], which saves the world. This should be called
+    /// once, after the last frame.
+    pub fn shutdown(&mut self) {
+        self.schedules.run_shutdown(&mut self.world);
+    }
+
+    /// Returns `true` if some chunks are still waiting to be generated or
+    /// meshed, or are being generated or meshed in the background, in which
+    /// case the frame rendered by [`Self::render`] may be missing some of them.
+    pub fn is_loading_chunks(&self) -> bool {
+        self.world.get_resource::<TerrainGenerationResource>().is_some_and(TerrainGenerationResource::is_generating)
+            || self.world.get_resource::<ChunkMeshingResource>().is_some_and(ChunkMeshingResource::is_meshing)
+    }
+
+    /// Reads the last frame rendered by [`Self::render`] back from the GPU.
+    /// Unlike [`crate::resources::screenshot::ScreenshotResource::request`],
+    /// this blocks until the pixels are available.
+    pub fn capture_frame(&self) -> Result<RgbaImage, HeadlessRenderError> {
+        let wgpu_resource: &WgpuResource = self.world.resource();
+        let RenderTarget::Offscreen(texture) = &wgpu_resource.render_target else {
+            let err = HeadlessRenderError::NotOffscreen;
+            error!("{err}");
+            return Err(err);
+        };
+        let readback = TextureReadback::new(&wgpu_resource.device, &wgpu_resource.command_queue, texture)?;
+        Ok(readback.read_blocking(&wgpu_resource.device)?)
+    }
+}
+
+pub struct HeadlessAppDescriptor {
+    pub width: u32,
+    pub height: u32,
+    /// See [`create_adapter`].
+    pub force_fallback_adapter: bool
+}
+
+impl Default for HeadlessAppDescriptor {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            width: 1280,
+            height: 720,
+            force_fallback_adapter: false
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum HeadlessAppInitializationError {
+    #[error(transparent)]
+    RequestAdapter(#[from] RequestAdapterError),
+
+    #[error(transparent)]
+    RequestDevice(#[from] RequestDeviceError)
+}
+
+#[derive(Error, Debug)]
+pub enum HeadlessRenderError {
+    #[error("The render target of a HeadlessApp must be an offscreen texture.")]
+    NotOffscreen,
+
+    #[error(transparent)]
+    Screenshot(#[from] ScreenshotError)
+}
@@ -0,0 +1,447 @@
+//! Contains the code that spreads sky light and block light through a
+//! [`VoxelWorld`]. Like in Minecraft, every block has a level of each from 0 to
+//! [`MAX_LIGHT_LEVEL`]:
+//! - A block that emits light has a block light level of at least its
+//!   [`BlockDefinition::light_emission`].
+//! - Sky light is [`MAX_LIGHT_LEVEL`] above the world, and goes straight down
+//!   without getting dimmer until it reaches an opaque block.
+//! - Otherwise, light spreads from a block to its 6 neighbours, one level
+//!   dimmer each time.
+//! - Opaque blocks stop light, while every other block, such as water or
+//!   leaves, lets it through like air does.
+//!
+//! Light is spread breadth-first with a queue, across the borders of sections
+//! and columns, but never into columns that are not loaded. A column that is
+//! inserted is lit from scratch, and then exchanges light with its loaded
+//! neighbours. When a block is set, the light that may have come through it is
+//! removed by a second breadth-first search, which also finds the light around
+//! it that has to be spread again.
+//!
+//! [`BlockDefinition::light_emission`]: crate::block::BlockDefinition::light_emission
+
+use glam::*;
+use std::collections::*;
+use strum::*;
+use crate::block::*;
+use crate::block_registry::*;
+use crate::voxel_world::*;
+
+/// The offsets from a block position to those of its 6 neighbours.
+const NEIGHBOUR_OFFSETS: [IVec3; 6] = [IVec3::X, IVec3::NEG_X, IVec3::Y, IVec3::NEG_Y, IVec3::Z, IVec3::NEG_Z];
+
+/// What light propagation needs to know about a kind of block.
+#[derive(Clone, Copy, Debug)]
+struct BlockLightingInfo {
+    is_opaque: bool,
+    light_emission: u8
+}
+
+/// [`BlockLightingInfo`] of every kind of block, indexed by [`BlockId`]. It
+/// needs to be recreated whenever the [`BlockRegistry`] changes.
+pub struct BlockLightingTable {
+    blocks: Vec<BlockLightingInfo>,
+    /// Used for blocks that are not in the [`BlockRegistry`], such as those
+    /// whose definitions have been removed. They are rendered as opaque
+    /// blocks, so they stop light too.
+    unknown_block: BlockLightingInfo
+}
+
+impl BlockLightingTable {
+    pub fn new(block_registry: &BlockRegistry) -> Self {
+        let unknown_block = BlockLightingInfo { is_opaque: true, light_emission: 0 };
+        let mut blocks = Vec::new();
+        for (id, block_type) in block_registry.iter() {
+            let index = usize::from(id.0);
+            if blocks.len() <= index {
+                blocks.resize(index + 1, unknown_block);
+            }
+            blocks[index] = BlockLightingInfo {
+                is_opaque: block_type.definition.opacity == BlockOpacity::Opaque,
+                light_emission: block_type.definition.light_emission
+            };
+        }
+        Self { blocks, unknown_block }
+    }
+
+    fn get(&self, id: BlockId) -> BlockLightingInfo {
+        self.blocks.get(usize::from(id.0)).copied().unwrap_or(self.unknown_block)
+    }
+}
+
+/// Updates the light of `voxel_world` for `updates`, which are usually taken
+/// with [`VoxelWorld::take_light_updates`]. Every section whose light changes
+/// is marked dirty.
+pub fn update_light(voxel_world: &mut VoxelWorld, lighting_table: &BlockLightingTable, updates: &LightUpdates) {
+    for kind in LightKind::iter() {
+        let mut propagator = LightPropagator { voxel_world, lighting_table, kind, additions: VecDeque::new(), removals: VecDeque::new() };
+        // Every column is lit on its own first, so that light is only
+        // exchanged between columns once they all have their own light.
+        for column_position in &updates.columns {
+            propagator.light_column(*column_position);
+        }
+        for column_position in &updates.columns {
+            propagator.queue_border_exchanges(*column_position);
+        }
+        for block_position in &updates.blocks {
+            propagator.queue_removal(*block_position);
+        }
+        propagator.remove_queued();
+        for block_position in &updates.blocks {
+            propagator.queue_source(*block_position);
+        }
+        propagator.spread_queued();
+    }
+}
+
+/// Spreads and removes one [`LightKind`] of light.
+struct LightPropagator<'a> {
+    voxel_world: &'a mut VoxelWorld,
+    lighting_table: &'a BlockLightingTable,
+    kind: LightKind,
+    /// The block positions of blocks whose light has to be spread to their
+    /// neighbours.
+    additions: VecDeque<IVec3>,
+    /// The block positions of blocks whose light has been removed, along with
+    /// the level they had.
+    removals: VecDeque<(IVec3, u8)>
+}
+
+impl LightPropagator<'_> {
+    /// Returns [`None`] if the block is not loaded.
+    fn block_info(&self, block_position: IVec3) -> Option<BlockLightingInfo> {
+        Some(self.lighting_table.get(self.voxel_world.get_block(block_position)?))
+    }
+
+    /// The level that light of `level` has after spreading from a block by
+    /// `offset`.
+    fn spread_level(&self, level: u8, offset: IVec3) -> u8 {
+        if self.kind == LightKind::Sky && offset == IVec3::NEG_Y && level == MAX_LIGHT_LEVEL {
+            MAX_LIGHT_LEVEL
+        }
+        else {
+            level.saturating_sub(1)
+        }
+    }
+
+    /// Lights the column at `column_position` from scratch, without taking
+    /// its neighbours into account, and queues the blocks whose light has to
+    /// be spread.
+    fn light_column(&mut self, column_position: IVec2) {
+        match self.kind {
+            LightKind::Sky => self.light_column_with_sky(column_position),
+            LightKind::Block => self.light_column_with_blocks(column_position)
+        }
+    }
+
+    fn light_column_with_sky(&mut self, column_position: IVec2) {
+        let Some(column) = self.voxel_world.column(column_position) else {
+            return;
+        };
+        // The Y of the highest opaque block of each column of blocks, indexed
+        // by Z and then X.
+        let mut found_heights = [[None; SECTION_SIZE as usize]; SECTION_SIZE as usize];
+        for (section_y, section) in column.sections().collect::<Vec<_>>().into_iter().rev() {
+            if section.is_empty() {
+                continue;
+            }
+            for (z, row) in (0..).zip(&mut found_heights) {
+                for (x, height) in (0..).zip(row.iter_mut().filter(|height| height.is_none())) {
+                    *height = (0..SECTION_SIZE).rev()
+                        .find(|y| self.lighting_table.get(section.get_block(UVec3::new(x, y.cast_unsigned(), z))).is_opaque)
+                        .map(|y| section_y * SECTION_SIZE + y);
+                }
+            }
+        }
+        let heights = found_heights.map(|row| row.map(|height| height.unwrap_or(COLUMN_MIN_Y - 1)));
+        let max_height = heights.as_flattened().iter().copied().max().unwrap_or(COLUMN_MIN_Y - 1);
+        let origin = column_position * SECTION_SIZE;
+        for section_y in (COLUMN_MIN_SECTION_Y..).take(COLUMN_SECTION_COUNT) {
+            let section_position = IVec3::new(column_position.x, section_y, column_position.y);
+            let section_min_y = section_y * SECTION_SIZE;
+            if section_min_y > max_height {
+                self.voxel_world.fill_section_light(LightKind::Sky, section_position, MAX_LIGHT_LEVEL);
+                continue;
+            }
+            self.voxel_world.fill_section_light(LightKind::Sky, section_position, 0);
+            for (z, row) in (0..).zip(&heights) {
+                for (x, height) in (0..).zip(row) {
+                    for y in (height + 1).max(section_min_y)..section_min_y + SECTION_SIZE {
+                        self.voxel_world.set_light(LightKind::Sky, IVec3::new(origin.x + x, y, origin.y + z), MAX_LIGHT_LEVEL);
+                    }
+                }
+            }
+        }
+        // Only the blocks lit straight from the sky that are next to blocks
+        // that are not can spread their light further. Those next to other
+        // columns are queued by Self::queue_border_exchanges.
+        let height_at = |x: i32, z: i32| heights.get(usize::try_from(z).ok()?)?.get(usize::try_from(x).ok()?).copied();
+        for (z, row) in (0..).zip(&heights) {
+            for (x, height) in (0..).zip(row) {
+                let neighbour_max_height = [(x + 1, z), (x - 1, z), (x, z + 1), (x, z - 1)].into_iter()
+                    .filter_map(|(neighbour_x, neighbour_z)| height_at(neighbour_x, neighbour_z))
+                    .max()
+                    .unwrap_or(*height);
+                for y in height + 1..=neighbour_max_height {
+                    self.additions.push_back(IVec3::new(origin.x + x, y, origin.y + z));
+                }
+            }
+        }
+    }
+
+    fn light_column_with_blocks(&mut self, column_position: IVec2) {
+        let Some(column) = self.voxel_world.column(column_position) else {
+            return;
+        };
+        let mut sources = Vec::new();
+        let origin = column_position * SECTION_SIZE;
+        for (section_y, section) in column.sections() {
+            if !section.blocks().palette().iter().any(|block| self.lighting_table.get(*block).light_emission > 0) {
+                continue;
+            }
+            let section_origin = IVec3::new(origin.x, section_y * SECTION_SIZE, origin.y);
+            for y in 0..SECTION_SIZE {
+                for z in 0..SECTION_SIZE {
+                    for x in 0..SECTION_SIZE {
+                        let local_position = IVec3::new(x, y, z);
+                        let light_emission = self.lighting_table.get(section.get_block(local_position.as_uvec3())).light_emission;
+                        if light_emission > 0 {
+                            sources.push((section_origin + local_position, light_emission));
+                        }
+                    }
+                }
+            }
+        }
+        for section_y in (COLUMN_MIN_SECTION_Y..).take(COLUMN_SECTION_COUNT) {
+            self.voxel_world.fill_section_light(LightKind::Block, IVec3::new(column_position.x, section_y, column_position.y), 0);
+        }
+        for (block_position, light_emission) in sources {
+            self.voxel_world.set_light(LightKind::Block, block_position, light_emission);
+            self.additions.push_back(block_position);
+        }
+    }
+
+    /// Queues the blocks on both sides of the borders between the column at
+    /// `column_position` and its loaded horizontal neighbours whose light is
+    /// bright enough to spread to the other side.
+    fn queue_border_exchanges(&mut self, column_position: IVec2) {
+        const LAST: u32 = SECTION_SIZE as u32 - 1;
+        for (offset, border_x, neighbour_border_x) in [(IVec2::X, LAST, 0), (IVec2::NEG_X, 0, LAST)] {
+            self.queue_border_exchange(column_position, column_position + offset, |along, y| (UVec3::new(border_x, y, along), UVec3::new(neighbour_border_x, y, along)));
+        }
+        for (offset, border_z, neighbour_border_z) in [(IVec2::Y, LAST, 0), (IVec2::NEG_Y, 0, LAST)] {
+            self.queue_border_exchange(column_position, column_position + offset, |along, y| (UVec3::new(along, y, border_z), UVec3::new(along, y, neighbour_border_z)));
+        }
+    }
+
+    /// * `to_local_positions`: Takes the position along the border and the Y
+    ///   inside a section, and returns the local positions of the blocks on
+    ///   either side of the border.
+    fn queue_border_exchange(&mut self, column_position: IVec2, neighbour_column_position: IVec2, to_local_positions: impl Fn(u32, u32) -> (UVec3, UVec3)) {
+        let (Some(column), Some(neighbour_column)) = (self.voxel_world.column(column_position), self.voxel_world.column(neighbour_column_position)) else {
+            return;
+        };
+        let to_block_position = |side_column_position: IVec2, section_y: i32, local_position: UVec3| {
+            IVec3::new(side_column_position.x, section_y, side_column_position.y) * SECTION_SIZE + local_position.as_ivec3()
+        };
+        for ((section_y, section), (_, neighbour_section)) in column.sections().zip(neighbour_column.sections()) {
+            if let (Some(level), Some(neighbour_level)) = (section.light(self.kind).uniform_level(), neighbour_section.light(self.kind).uniform_level())
+                && level.abs_diff(neighbour_level) <= 1 {
+                continue;
+            }
+            for along in 0..SECTION_SIZE.cast_unsigned() {
+                for y in 0..SECTION_SIZE.cast_unsigned() {
+                    let (local_position, neighbour_local_position) = to_local_positions(along, y);
+                    let level = section.get_light(self.kind, local_position);
+                    let neighbour_level = neighbour_section.get_light(self.kind, neighbour_local_position);
+                    if level > neighbour_level + 1 && !self.lighting_table.get(neighbour_section.get_block(neighbour_local_position)).is_opaque {
+                        self.additions.push_back(to_block_position(column_position, section_y, local_position));
+                    }
+                    else if neighbour_level > level + 1 && !self.lighting_table.get(section.get_block(local_position)).is_opaque {
+                        self.additions.push_back(to_block_position(neighbour_column_position, section_y, neighbour_local_position));
+                    }
+                    else {
+                        // The light on either side cannot brighten the other.
+                    }
+                }
+            }
+        }
+    }
+
+    /// Darkens the block at `block_position`, which has been set, and queues
+    /// its old light to be removed from around it.
+    fn queue_removal(&mut self, block_position: IVec3) {
+        let Some(level) = self.voxel_world.get_light(self.kind, block_position) else {
+            return;
+        };
+        self.voxel_world.set_light(self.kind, block_position, 0);
+        self.removals.push_back((block_position, level));
+    }
+
+    /// Queues the block at `block_position`, which has been set, to spread its
+    /// light if it is a source of light: if it emits block light, or if it is
+    /// at the top of the world and lets sky light through.
+    fn queue_source(&mut self, block_position: IVec3) {
+        let Some(block_info) = self.block_info(block_position) else {
+            return;
+        };
+        let level = match self.kind {
+            LightKind::Sky if block_position.y == COLUMN_MAX_Y && !block_info.is_opaque => MAX_LIGHT_LEVEL,
+            LightKind::Sky => return,
+            LightKind::Block => block_info.light_emission
+        };
+        if level > 0 {
+            self.voxel_world.set_light(self.kind, block_position, level);
+            self.additions.push_back(block_position);
+        }
+    }
+
+    /// Removes the light that came from the blocks in [`Self::removals`].
+    /// A neighbour that is dimmer than a removed block, or that got sky light
+    /// straight from above it, must have been lit through it, so its light is
+    /// removed too. A neighbour that is at least as bright must have been lit
+    /// some other way, so it is queued to spread its light back into the
+    /// darkened blocks.
+    fn remove_queued(&mut self) {
+        while let Some((block_position, level)) = self.removals.pop_front() {
+            for offset in NEIGHBOUR_OFFSETS {
+                let neighbour = block_position + offset;
+                let Some(neighbour_level) = self.voxel_world.get_light(self.kind, neighbour) else {
+                    continue;
+                };
+                if neighbour_level != 0 && (neighbour_level < level || (level == MAX_LIGHT_LEVEL && self.spread_level(level, offset) == level)) {
+                    self.voxel_world.set_light(self.kind, neighbour, 0);
+                    self.removals.push_back((neighbour, neighbour_level));
+                    // Blocks that emit light keep it.
+                    self.queue_source(neighbour);
+                }
+                else if neighbour_level >= level {
+                    self.additions.push_back(neighbour);
+                }
+                else {
+                    // Unlit blocks have no light to remove or spread.
+                }
+            }
+        }
+    }
+
+    /// Spreads the light of the blocks in [`Self::additions`] to their
+    /// neighbours, and from there on to every block that it makes brighter.
+    fn spread_queued(&mut self) {
+        while let Some(block_position) = self.additions.pop_front() {
+            let Some(level @ 1..) = self.voxel_world.get_light(self.kind, block_position) else {
+                continue;
+            };
+            for offset in NEIGHBOUR_OFFSETS {
+                let neighbour = block_position + offset;
+                let spread_level = self.spread_level(level, offset);
+                if self.block_info(neighbour).is_none_or(|block_info| block_info.is_opaque) {
+                    continue;
+                }
+                if self.voxel_world.get_light(self.kind, neighbour).is_some_and(|neighbour_level| neighbour_level < spread_level) {
+                    self.voxel_world.set_light(self.kind, neighbour, spread_level);
+                    self.additions.push_back(neighbour);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assets_manager::*;
+    use crate::constants::*;
+
+    struct TestBlocks {
+        lighting_table: BlockLightingTable,
+        stone: BlockId,
+        glowstone: BlockId
+    }
+
+    fn test_blocks() -> TestBlocks {
+        let asset_cache = AssetCache::new(ASSETS_PATH).expect("The assets directory should exist.");
+        let block_definitions = asset_cache.load::<BlockDefinitions>(BLOCKS_ASSET_ID).expect("The block definitions should be loaded.");
+        let block_registry = BlockRegistry::new(&block_definitions.read()).expect("The block registry should be created.");
+        let block = |path| block_registry.id(&BlockName::new(BlockName::DEFAULT_NAMESPACE, path).expect("The name should be valid.")).expect("The block should be registered.");
+        TestBlocks { lighting_table: BlockLightingTable::new(&block_registry), stone: block("stone"), glowstone: block("glowstone") }
+    }
+
+    /// A column with a floor of stone at Y = 0.
+    fn floor_column(stone: BlockId) -> ChunkColumn {
+        let mut column = ChunkColumn::default();
+        for x in 0..SECTION_SIZE {
+            for z in 0..SECTION_SIZE {
+                column.set_block(IVec3::new(x, 0, z), stone);
+            }
+        }
+        column
+    }
+
+    fn light_at(voxel_world: &VoxelWorld, kind: LightKind, x: i32, y: i32, z: i32) -> Option<u8> {
+        voxel_world.get_light(kind, IVec3::new(x, y, z))
+    }
+
+    fn apply_light_updates(voxel_world: &mut VoxelWorld, lighting_table: &BlockLightingTable) {
+        let updates = voxel_world.take_light_updates();
+        update_light(voxel_world, lighting_table, &updates);
+    }
+
+    #[test]
+    fn sky_light_spreads_under_a_roof_across_columns_and_back_after_it_is_opened() {
+        let blocks = test_blocks();
+        let mut voxel_world = VoxelWorld::default();
+        // The roof covers the whole column at X = 1 and the column at X = 0
+        // from X = 8, so the only light under it in the column at X = 1 comes
+        // from the column at X = 0.
+        let mut roofed_column = floor_column(blocks.stone);
+        for x in 0..SECTION_SIZE {
+            for z in 0..SECTION_SIZE {
+                roofed_column.set_block(IVec3::new(x, 4, z), blocks.stone);
+            }
+        }
+        voxel_world.insert_column(IVec2::new(1, 0), roofed_column);
+        apply_light_updates(&mut voxel_world, &blocks.lighting_table);
+        assert_eq!(voxel_world.get_light(LightKind::Sky, IVec3::new(16, 2, 5)), Some(0));
+        voxel_world.insert_column(IVec2::ZERO, floor_column(blocks.stone));
+        for x in 8..SECTION_SIZE {
+            for z in 0..SECTION_SIZE {
+                voxel_world.set_block(IVec3::new(x, 4, z), blocks.stone).expect("The column should be loaded.");
+            }
+        }
+        apply_light_updates(&mut voxel_world, &blocks.lighting_table);
+        assert_eq!(light_at(&voxel_world, LightKind::Sky, 0, COLUMN_MAX_Y, 0), Some(MAX_LIGHT_LEVEL));
+        assert_eq!(light_at(&voxel_world, LightKind::Sky, 7, 1, 5), Some(MAX_LIGHT_LEVEL));
+        assert_eq!(light_at(&voxel_world, LightKind::Sky, 8, 4, 5), Some(0), "Opaque blocks should not be lit.");
+        assert_eq!(light_at(&voxel_world, LightKind::Sky, 5, -1, 5), Some(0));
+        for (x, distance) in (8..=22).zip(1..) {
+            assert_eq!(light_at(&voxel_world, LightKind::Sky, x, 2, 5), Some(MAX_LIGHT_LEVEL - distance), "X = {x}");
+        }
+        voxel_world.set_block(IVec3::new(20, 4, 5), BlockId::AIR).expect("The column should be loaded.");
+        apply_light_updates(&mut voxel_world, &blocks.lighting_table);
+        assert_eq!(light_at(&voxel_world, LightKind::Sky, 20, 1, 5), Some(MAX_LIGHT_LEVEL), "The light should go straight down through the hole.");
+        assert_eq!(light_at(&voxel_world, LightKind::Sky, 22, 1, 5), Some(MAX_LIGHT_LEVEL - 2));
+        voxel_world.set_block(IVec3::new(20, 4, 5), blocks.stone).expect("The column should be loaded.");
+        apply_light_updates(&mut voxel_world, &blocks.lighting_table);
+        for (x, distance) in (8..=22).zip(1..) {
+            assert_eq!(light_at(&voxel_world, LightKind::Sky, x, 2, 5), Some(MAX_LIGHT_LEVEL - distance), "X = {x} after closing the hole");
+        }
+    }
+
+    #[test]
+    fn block_light_spreads_across_columns_and_is_removed_with_its_source() {
+        let blocks = test_blocks();
+        let mut voxel_world = VoxelWorld::default();
+        voxel_world.insert_column(IVec2::ZERO, floor_column(blocks.stone));
+        voxel_world.insert_column(IVec2::X, floor_column(blocks.stone));
+        voxel_world.set_block(IVec3::new(14, 1, 8), blocks.glowstone).expect("The column should be loaded.");
+        apply_light_updates(&mut voxel_world, &blocks.lighting_table);
+        assert_eq!(light_at(&voxel_world, LightKind::Block, 14, 1, 8), Some(MAX_LIGHT_LEVEL));
+        assert_eq!(light_at(&voxel_world, LightKind::Block, 17, 1, 8), Some(MAX_LIGHT_LEVEL - 3));
+        assert_eq!(light_at(&voxel_world, LightKind::Block, 16, 3, 10), Some(MAX_LIGHT_LEVEL - 6));
+        assert_eq!(light_at(&voxel_world, LightKind::Block, 14, 0, 8), Some(0), "Opaque blocks should not be lit.");
+        voxel_world.set_block(IVec3::new(14, 1, 8), BlockId::AIR).expect("The column should be loaded.");
+        apply_light_updates(&mut voxel_world, &blocks.lighting_table);
+        assert!(voxel_world.sections().all(|(_, section)| section.light(LightKind::Block).uniform_level() == Some(0)
+            || (0..SECTION_VOLUME).all(|index| section.light(LightKind::Block).get(index) == 0)));
+    }
+}
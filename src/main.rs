@@ -1,5 +1,11 @@
+mod anvil;
 mod application_handler;
 mod asset;
+mod background_jobs;
+mod biome;
+mod block;
+mod block_registry;
+mod block_texture_atlas;
 mod components;
 mod resources;
 mod schedules;
@@ -7,18 +13,45 @@ mod system_sets;
 mod systems;
 mod world;
 mod camera;
+mod chunk_mesher;
+mod chunk_streaming;
 mod egui_renderer;
 mod egui_state;
 mod extensions;
+mod gpu;
+mod gpu_mesh;
+#[cfg(test)]
+mod golden_tests;
+mod headless;
+mod lighting;
 mod material;
+mod materials;
 mod mesh;
+#[cfg(test)]
+mod meshing_benchmarks;
+mod nbt;
+mod ore;
+mod region;
+mod screenshot;
 mod shapes;
+mod structure;
 mod constants;
 mod shader;
+mod terrain_features;
+mod terrain_generator;
+mod terrain_passes;
+mod voxel_world;
+mod world_storage;
 
 use std::error::*;
 use std::env::*;
 use application_handler::*;
+use headless::*;
+use screenshot::*;
+use std::path::*;
+use std::time::*;
+use tokio::time::sleep;
+use world::*;
 use env_logger::*;
 use log::*;
 use winit::event_loop::*;
@@ -27,6 +60,15 @@ use winit::event_loop::*;
 async fn main() -> Result<(), Box<dyn Error>> {
     init_env_logger();
     info!("App started.");
+    // Resolved before changing the working directory, so that relative paths
+    // are relative to where the app was started from.
+    let anvil_world_path_arg = match args().skip_while(|arg| arg != "--import-anvil").nth(1).map(absolute).transpose() {
+        Ok(anvil_world_path) => anvil_world_path,
+        Err(err) => {
+            error!("Could not resolve the path of the Minecraft world to import. Error: {err:#?}");
+            return Err(err.into());
+        }
+    };
     let current_exe_dir = match current_exe() {
         #[expect(clippy::unwrap_used, reason = "current_exe returns the absolute path to the executable file, so there must be a parent directory.")]
         Ok(current_exe_path) => current_exe_path.parent().unwrap().to_path_buf(),
@@ -39,6 +81,14 @@ async fn main() -> Result<(), Box<dyn Error>> {
         error!("Could not set the current working directory to the directory of the executable. Error: {err:#?}");
         return Err(err.into());
     }
+    if let Some(anvil_world_path) = anvil_world_path_arg {
+        import_anvil_world(&anvil_world_path)?;
+        info!("Exiting.");
+        return Ok(());
+    }
+    if args().any(|arg| arg == "--headless") {
+        return run_headless().await;
+    }
     let event_loop = match EventLoop::new() {
         Ok(event_loop) => event_loop,
         Err(err) => {
@@ -59,6 +109,29 @@ async fn main() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+/// How long [`run_headless`] waits between frames while chunks are loading.
+const HEADLESS_LOADING_FRAME_INTERVAL: Duration = Duration::from_millis(16);
+
+/// Renders a single frame of the main [`bevy_ecs::world::World`] without a
+/// window, saves it to [`SCREENSHOTS_PATH`], saves the world, and exits, which
+/// is useful to check that rendering works on machines with no display.
+async fn run_headless() -> Result<(), Box<dyn Error>> {
+    info!("Running headlessly.");
+    let mut headless_app = HeadlessApp::new(create_main_world()?, &HeadlessAppDescriptor::default()).await?;
+    headless_app.render()?;
+    // Chunks are generated and meshed in the background, so frames are
+    // rendered until all of them show up. Waiting between frames leaves the
+    // CPU to the background jobs.
+    while headless_app.is_loading_chunks() {
+        sleep(HEADLESS_LOADING_FRAME_INTERVAL).await;
+        headless_app.render()?;
+    }
+    save_png(&headless_app.capture_frame()?, Path::new(SCREENSHOTS_PATH))?;
+    headless_app.shutdown();
+    info!("Exiting.");
+    Ok(())
+}
+
 fn init_env_logger() {
     let env = Env::new().filter_or("RUST_LOG", "info");
     Builder::from_env(env).format_timestamp_millis().init();
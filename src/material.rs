@@ -3,13 +3,18 @@ use crate::mesh::*;
 use crate::shader::*;
 use assets_manager::*;
 use assets_manager::Error as AssetCacheError;
+use std::any::*;
 use strum::*;
 use thiserror::*;
 use wgpu::*;
 
 /// A [`Material`] determines how an object should be rendered. It contains the
 /// shaders to use and the data to pass to the shaders.
-pub trait Material {
+///
+/// [`Material`]s of the same type share their [`RenderPipeline`]s as long as
+/// they use the same shaders, so all [`Material`]s of the same type must return
+/// the same [`BindGroupLayout`] from [`Material::bind_group_layout`].
+pub trait Material: Any {
     /// Creates the [`BindGroup`] that will be used to render an object. The
     /// return value of [`Material::bind_group_layout`] is passed into this
     /// function as `layout`. The returned [`BindGroup`] will be the
@@ -60,6 +65,17 @@ pub trait Material {
     /// return result;
     /// ```
     fn attribute_to_shader_location_mapping(&self) -> [Option<u32>; VertexAttributeKind::COUNT];
+
+    /// The [`VertexFormat`] of each custom vertex attribute, indexed by
+    /// [`VertexAttributeKind::custom_index`]. Only the formats of the custom
+    /// vertex attributes that
+    /// [`Material::attribute_to_shader_location_mapping`] maps matter, and a
+    /// [`Mesh`] is only rendered if its [`CustomVertexAttribute`]s are in those
+    /// formats. Defaults to [`VertexFormat::Uint32`] for all of them, which
+    /// fits data packed into a [`u32`].
+    fn custom_attribute_formats(&self) -> [VertexFormat; CUSTOM_VERTEX_ATTRIBUTE_COUNT] {
+        [VertexFormat::Uint32; CUSTOM_VERTEX_ATTRIBUTE_COUNT]
+    }
 }
 
 #[derive(Debug, Error)]
@@ -0,0 +1,208 @@
+//! Contains implementations of [`Material`].
+
+use assets_manager::*;
+use glam::*;
+use strum::*;
+use wgpu::*;
+use wgpu::util::*;
+use crate::asset::*;
+use crate::block_texture_atlas::*;
+use crate::material::*;
+use crate::mesh::*;
+use crate::shader::*;
+
+/// A [`Material`] that renders a [`Mesh`] with a single color and no
+/// lighting. It requires [`Mesh::vertices`] and [`Mesh::uv`].
+pub struct UnlitMaterial {
+    /// The color in linear space.
+    pub color: Vec4
+}
+
+impl Material for UnlitMaterial {
+    fn bind_group(&self, device: &Device, layout: &BindGroupLayout) -> BindGroup {
+        let color_buffer = device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("unlit-material-color-buffer"),
+            contents: bytemuck::bytes_of(&self.color),
+            usage: BufferUsages::UNIFORM
+        });
+        device.create_bind_group(&BindGroupDescriptor {
+            label: Some("unlit-material-bind-group"),
+            layout,
+            entries: &[BindGroupEntry {
+                binding: 0,
+                resource: color_buffer.as_entire_binding()
+            }]
+        })
+    }
+
+    fn bind_group_layout(&self, device: &Device) -> BindGroupLayout {
+        device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("unlit-material-bind-group-layout"),
+            entries: &[BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None
+                },
+                count: None
+            }]
+        })
+    }
+
+    fn vertex_shader(&self, asset_cache: &AssetCache) -> Result<AssetHandle<WeslShader>, ShaderLoadingError> {
+        Ok(asset_cache.load::<WeslShader>("shaders.unlit")?.strong().into())
+    }
+
+    fn vertex_shader_entry_point(&self) -> Option<String> {
+        Some("vertex_main".to_owned())
+    }
+
+    fn fragment_shader(&self, asset_cache: &AssetCache) -> Result<AssetHandle<WeslShader>, ShaderLoadingError> {
+        Ok(asset_cache.load::<WeslShader>("shaders.unlit")?.strong().into())
+    }
+
+    fn fragment_shader_entry_point(&self) -> Option<String> {
+        Some("fragment_main".to_owned())
+    }
+
+    fn attribute_to_shader_location_mapping(&self) -> [Option<u32>; VertexAttributeKind::COUNT] {
+        let mut result = [None; VertexAttributeKind::COUNT];
+        result[VertexAttributeKind::Positions as usize] = Some(0);
+        result[VertexAttributeKind::Uv as usize] = Some(1);
+        result
+    }
+}
+
+/// The [`Material`] used by chunk meshes. It samples a [`BlockTextureAtlas`]
+/// uploaded to the GPU and requires [`Mesh::vertices`], [`Mesh::uv`],
+/// [`Mesh::colors`] and [`Mesh::ambient_occlusion`], with the UVs encoded as
+/// described in [`crate::chunk_mesher::TILE_UV_STRIDE`] and the light in the
+/// colors as described in [`crate::chunk_mesher::light_color`].
+pub struct BlockMaterial {
+    pub atlas_texture_view: TextureView,
+    pub atlas_sampler: Sampler,
+    /// The number of tiles in each row and column of the atlas.
+    pub tiles_per_side: u32
+}
+
+impl BlockMaterial {
+    /// Uploads the image of `atlas` to the GPU.
+    pub fn new(device: &Device, queue: &Queue, atlas: &BlockTextureAtlas) -> Self {
+        let atlas_image = atlas.image();
+        let atlas_texture = device.create_texture_with_data(queue, &TextureDescriptor {
+            label: Some("block-atlas-texture"),
+            size: Extent3d {
+                width: atlas_image.width(),
+                height: atlas_image.height(),
+                depth_or_array_layers: 1
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Rgba8UnormSrgb,
+            usage: TextureUsages::TEXTURE_BINDING,
+            view_formats: &[]
+        }, TextureDataOrder::LayerMajor, atlas_image.as_raw());
+        let atlas_sampler = device.create_sampler(&SamplerDescriptor {
+            label: Some("block-atlas-sampler"),
+            // Block textures are pixel art, so they must not be blurred.
+            mag_filter: FilterMode::Nearest,
+            min_filter: FilterMode::Nearest,
+            ..Default::default()
+        });
+        Self {
+            atlas_texture_view: atlas_texture.create_view(&TextureViewDescriptor::default()),
+            atlas_sampler,
+            tiles_per_side: atlas.tiles_per_side()
+        }
+    }
+}
+
+impl Material for BlockMaterial {
+    fn bind_group(&self, device: &Device, layout: &BindGroupLayout) -> BindGroup {
+        let tiles_per_side_buffer = device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("block-material-tiles-per-side-buffer"),
+            contents: bytemuck::bytes_of(&self.tiles_per_side),
+            usage: BufferUsages::UNIFORM
+        });
+        device.create_bind_group(&BindGroupDescriptor {
+            label: Some("block-material-bind-group"),
+            layout,
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::TextureView(&self.atlas_texture_view)
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::Sampler(&self.atlas_sampler)
+                },
+                BindGroupEntry {
+                    binding: 2,
+                    resource: tiles_per_side_buffer.as_entire_binding()
+                }
+            ]
+        })
+    }
+
+    fn bind_group_layout(&self, device: &Device) -> BindGroupLayout {
+        device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("block-material-bind-group-layout"),
+            entries: &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Texture {
+                        sample_type: TextureSampleType::Float { filterable: true },
+                        view_dimension: TextureViewDimension::D2,
+                        multisampled: false
+                    },
+                    count: None
+                },
+                BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                    count: None
+                },
+                BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None
+                    },
+                    count: None
+                }
+            ]
+        })
+    }
+
+    fn vertex_shader(&self, asset_cache: &AssetCache) -> Result<AssetHandle<WeslShader>, ShaderLoadingError> {
+        Ok(asset_cache.load::<WeslShader>("shaders.block")?.strong().into())
+    }
+
+    fn vertex_shader_entry_point(&self) -> Option<String> {
+        Some("vertex_main".to_owned())
+    }
+
+    fn fragment_shader(&self, asset_cache: &AssetCache) -> Result<AssetHandle<WeslShader>, ShaderLoadingError> {
+        Ok(asset_cache.load::<WeslShader>("shaders.block")?.strong().into())
+    }
+
+    fn fragment_shader_entry_point(&self) -> Option<String> {
+        Some("fragment_main".to_owned())
+    }
+
+    fn attribute_to_shader_location_mapping(&self) -> [Option<u32>; VertexAttributeKind::COUNT] {
+        let mut result = [None; VertexAttributeKind::COUNT];
+        result[VertexAttributeKind::Positions as usize] = Some(0);
+        result[VertexAttributeKind::Uv as usize] = Some(1);
+        result[VertexAttributeKind::Colors as usize] = Some(2);
+        result[VertexAttributeKind::AmbientOcclusion as usize] = Some(3);
+        result
+    }
+}
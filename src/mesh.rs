@@ -40,7 +40,23 @@ pub struct Mesh {
     vertices: Vec<Vec3>,
     indices: Vec<usize>,
     uv: Vec<Vec2>,
-    colors: Vec<U8Vec4>
+    colors: Vec<U8Vec4>,
+    /// How much of the ambient light reaches each vertex, from 0 for fully
+    /// occluded to 1 for not occluded at all.
+    ambient_occlusion: Vec<f32>,
+    /// Expected to be normalized.
+    normals: Vec<Vec3>,
+    /// The tangents in XYZ, expected to be normalized, with the sign of the
+    /// bitangent in W, so that the bitangent is `normal.cross(tangent.xyz()) *
+    /// tangent.w`, as in `MikkTSpace`.
+    tangents: Vec<Vec4>,
+    /// A second set of UVs, such as for lightmaps or detail textures.
+    uv2: Vec<Vec2>,
+    /// Vertex attributes whose meaning is up to the
+    /// [`crate::material::Material`] that uses them. The element at index `i`
+    /// is the vertex attribute whose [`VertexAttributeKind::custom_index`] is
+    /// `i`.
+    custom_attributes: Vec<CustomVertexAttribute>
 }
 
 #[bon]
@@ -54,40 +70,56 @@ impl Mesh {
         #[builder(default)]
         uv: Vec<Vec2>,
         #[builder(default)]
-        colors: Vec<U8Vec4>
+        colors: Vec<U8Vec4>,
+        #[builder(default)]
+        ambient_occlusion: Vec<f32>,
+        #[builder(default)]
+        normals: Vec<Vec3>,
+        #[builder(default)]
+        tangents: Vec<Vec4>,
+        #[builder(default)]
+        uv2: Vec<Vec2>,
+        /// At most [`CUSTOM_VERTEX_ATTRIBUTE_COUNT`] of them.
+        #[builder(default)]
+        custom_attributes: Vec<CustomVertexAttribute>
     ) -> Result<Self, MeshCreationError> {
-        if !vertices.len().is_multiple_of(3) {
+        if !indices.len().is_multiple_of(3) {
             let err = MeshCreationError::IndicesLengthNotMultipleOf3 {
-                indices_length: vertices.len()
+                indices_length: indices.len()
+            };
+            error!("Mesh creation failed: {err}");
+            return Err(err);
+        }
+        if custom_attributes.len() > CUSTOM_VERTEX_ATTRIBUTE_COUNT {
+            let err = MeshCreationError::TooManyCustomAttributes {
+                custom_attributes_length: custom_attributes.len()
             };
             error!("Mesh creation failed: {err}");
             return Err(err);
         }
+        let mesh = Self { vertices, indices, uv, colors, ambient_occlusion, normals, tangents, uv2, custom_attributes };
         // This will be None if all vertex attributes are empty.
-        let shortest_attribute_length_and_name = vec![
-            (vertices.len(), "vertices"),
-            (uv.len(), "uv"),
-            (colors.len(), "colors")]
-            .into_iter()
+        let shortest_attribute_length_and_name = VertexAttributeKind::iter()
+            .map(|kind| (mesh.attribute_length(kind), kind.field_name()))
             .filter(|pair| pair.0 != 0)
             .min_by_key(|pair| pair.0);
         match shortest_attribute_length_and_name {
             None => {
-                if let indices_length @ 1.. = indices.len() {
+                if let indices_length @ 1.. = mesh.indices.len() {
                     let err = MeshCreationError::EmptyMeshWithNonemptyIndices { indices_length };
                     error!("Mesh creation failed: {err}");
                     return Err(err);
                 }
                 // Empty Mesh.
-                Ok(Self { vertices, indices, uv, colors })
+                Ok(mesh)
             }
             Some((shortest_attribute_length, shortest_attribute_name)) => {
-                if indices.is_empty() {
+                if mesh.indices.is_empty() {
                     // Using the implied [0, 1, ..., L - 1] indices.
-                    return Ok(Self { vertices, indices, uv, colors });
+                    return Ok(mesh);
                 }
                 #[expect(clippy::unwrap_used, reason = "We have already checked that indices.len() is not 0.")]
-                let max_index = indices.iter().copied().max().unwrap();
+                let max_index = mesh.indices.iter().copied().max().unwrap();
                 if max_index > shortest_attribute_length - 1 {
                     let err = MeshCreationError::IndicesOutOfBounds {
                         out_of_bounds_vertex_attribute_name: shortest_attribute_name,
@@ -99,10 +131,283 @@ impl Mesh {
                 }
                 // This is the typical situation where you have some vertex
                 // attributes and vertices is not empty.
-                Ok(Self { vertices, indices, uv, colors })
+                Ok(mesh)
+            }
+        }
+    }
+
+    /// The number of vertices to draw, which is the length of
+    /// [`Self::indices`], or L as described in the invariants of [`Mesh`] if
+    /// [`Self::indices`] is implied.
+    pub fn index_count(&self) -> usize {
+        if !self.indices.is_empty() {
+            return self.indices.len();
+        }
+        VertexAttributeKind::iter()
+            .map(|kind| self.attribute_length(kind))
+            .filter(|length| *length != 0)
+            .min()
+            .unwrap_or(0)
+    }
+
+    /// The number of elements in the vertex attribute of `kind`, which is 0 if
+    /// it is empty.
+    pub fn attribute_length(&self, kind: VertexAttributeKind) -> usize {
+        match kind {
+            VertexAttributeKind::Positions => self.vertices.len(),
+            VertexAttributeKind::Uv => self.uv.len(),
+            VertexAttributeKind::Colors => self.colors.len(),
+            VertexAttributeKind::AmbientOcclusion => self.ambient_occlusion.len(),
+            VertexAttributeKind::Normals => self.normals.len(),
+            VertexAttributeKind::Tangents => self.tangents.len(),
+            VertexAttributeKind::Uv2 => self.uv2.len(),
+            VertexAttributeKind::Custom0 | VertexAttributeKind::Custom1 | VertexAttributeKind::Custom2 | VertexAttributeKind::Custom3 => {
+                self.custom_attribute(kind).map_or(0, CustomVertexAttribute::len)
             }
         }
     }
+
+    /// Returns `true` if the vertex attribute of `kind` is not empty.
+    pub fn has_attribute(&self, kind: VertexAttributeKind) -> bool {
+        self.attribute_length(kind) != 0
+    }
+
+    /// Returns [`None`] if `kind` is not a custom vertex attribute, or if this
+    /// [`Mesh`] does not have it.
+    pub fn custom_attribute(&self, kind: VertexAttributeKind) -> Option<&CustomVertexAttribute> {
+        self.custom_attributes.get(kind.custom_index()?)
+    }
+}
+
+/// Utilities that generate vertex attributes from the others. Each of them
+/// takes into account the case where [`Mesh::indices`] is implied.
+#[expect(clippy::multiple_inherent_impl, reason = "Attributes such as cfg_attr cannot be used in the impl block that #[bon] expands without tripping clippy::cfg_not_test.")]
+#[cfg_attr(not(test), expect(dead_code, reason = "Nothing is shaded with normals or tangents yet."))]
+impl Mesh {
+    /// Returns this [`Mesh`] with [`Self::normals`] set to the normal of the
+    /// triangle that each vertex belongs to, as returned by
+    /// [`triangle_normal`]. Triangles with different normals cannot share
+    /// vertices, so if [`Self::indices`] is not implied, every vertex attribute
+    /// is first duplicated so that each triangle has its own vertices, and
+    /// [`Self::indices`] becomes implied. Degenerate triangles get zero
+    /// normals. [`Self::tangents`] are cleared, since they were made
+    /// perpendicular to the old normals.
+    pub fn with_flat_normals(self) -> Self {
+        let mut mesh = self.with_unshared_vertices();
+        mesh.normals = mesh.triangles()
+            .into_iter()
+            .flat_map(|triangle| [triangle_normal(triangle.map(|index| mesh.vertices[index])).unwrap_or_default(); 3])
+            .collect();
+        mesh.tangents.clear();
+        mesh
+    }
+
+    /// Returns this [`Mesh`] with [`Self::normals`] set to the average of the
+    /// normals of the triangles that share each vertex, weighted by the angle
+    /// of the triangle at that vertex so that the result does not depend on
+    /// how the surface around it is split into triangles. Only vertices with
+    /// the same index are shared, so vertices that are merely at the same
+    /// position, such as the corners of [`crate::shapes::Cuboid`], which has
+    /// separate vertices for each face, keep hard edges. Vertices that are only
+    /// used by degenerate triangles get a zero normal. Like in
+    /// [`Self::with_flat_normals`], [`Self::tangents`] are cleared.
+    pub fn with_smooth_normals(mut self) -> Self {
+        let mut normals = vec![Vec3::ZERO; self.vertices.len()];
+        for triangle in self.triangles() {
+            let positions = triangle.map(|index| self.vertices[index]);
+            let Some(normal) = triangle_normal(positions) else {
+                continue;
+            };
+            for (corner, index) in triangle.into_iter().enumerate() {
+                normals[index] += normal * corner_angle(positions, corner);
+            }
+        }
+        self.normals = normals.into_iter().map(Vec3::normalize_or_zero).collect();
+        self.tangents.clear();
+        self
+    }
+
+    /// Returns this [`Mesh`] with [`Self::tangents`] generated from
+    /// [`Self::vertices`], [`Self::normals`] and [`Self::uv`] the way
+    /// `MikkTSpace` does, so that normal maps baked by other tools look right:
+    /// the tangent of each triangle points along increasing U, is made
+    /// perpendicular to the normal of each of its vertices, and is averaged
+    /// per vertex weighted by the angle of the triangle at that vertex. The
+    /// sign in W makes the bitangent point along increasing V. Unlike
+    /// `MikkTSpace`, vertices shared by triangles whose UVs are mirrored
+    /// relative to each other are not split, so such meshes should not share
+    /// vertices across the mirror.
+    pub fn with_tangents(mut self) -> Result<Self, TangentGenerationError> {
+        if let Some(kind) = [VertexAttributeKind::Positions, VertexAttributeKind::Normals, VertexAttributeKind::Uv].into_iter().find(|kind| !self.has_attribute(*kind)) {
+            let err = TangentGenerationError::MissingAttribute(kind);
+            error!("Tangent generation failed: {err}");
+            return Err(err);
+        }
+        let mut tangents = vec![Vec3::ZERO; self.vertices.len()];
+        let mut bitangents = vec![Vec3::ZERO; self.vertices.len()];
+        for triangle in self.triangles() {
+            let positions = triangle.map(|index| self.vertices[index]);
+            if triangle_normal(positions).is_none() {
+                continue;
+            }
+            let uv = triangle.map(|index| self.uv[index]);
+            let (first_edge, second_edge) = (positions[1] - positions[0], positions[2] - positions[0]);
+            let (first_uv_edge, second_uv_edge) = (uv[1] - uv[0], uv[2] - uv[0]);
+            // The derivatives of the position with respect to U and V, scaled
+            // by the signed area of the triangle in UV space. Only the sign of
+            // the area matters, since they are normalized later.
+            let uv_area_sign = first_uv_edge.perp_dot(second_uv_edge).signum();
+            let tangent = (first_edge * second_uv_edge.y - second_edge * first_uv_edge.y) * uv_area_sign;
+            let bitangent = (second_edge * first_uv_edge.x - first_edge * second_uv_edge.x) * uv_area_sign;
+            for (corner, index) in triangle.into_iter().enumerate() {
+                let normal = self.normals[index];
+                let angle = corner_angle(positions, corner);
+                tangents[index] += (tangent - normal * normal.dot(tangent)).normalize_or_zero() * angle;
+                bitangents[index] += bitangent * angle;
+            }
+        }
+        self.tangents = tangents.into_iter()
+            .zip(bitangents)
+            .zip(&self.normals)
+            .map(|((tangent, bitangent), normal)| {
+                // Vertices whose triangles have no UVs to follow get any
+                // tangent perpendicular to their normal.
+                let unit_tangent = tangent.try_normalize().unwrap_or_else(|| normal.try_normalize().map_or(Vec3::X, |unit_normal| unit_normal.any_orthonormal_vector()));
+                let sign = if normal.cross(unit_tangent).dot(bitangent) < 0.0 { -1.0 } else { 1.0 };
+                unit_tangent.extend(sign)
+            })
+            .collect();
+        Ok(self)
+    }
+
+    /// The indices of the vertices of each triangle, including when
+    /// [`Self::indices`] is implied.
+    fn triangles(&self) -> Vec<[usize; 3]> {
+        if self.indices.is_empty() {
+            #[expect(clippy::integer_division, reason = "Vertices that do not make up a whole triangle are not drawn.")]
+            let triangle_count = self.index_count() / 3;
+            (0..triangle_count).map(|triangle| [0, 1, 2].map(|corner| triangle * 3 + corner)).collect()
+        }
+        else {
+            self.indices.as_chunks::<3>().0.to_vec()
+        }
+    }
+
+    /// Duplicates every vertex attribute so that each triangle has its own
+    /// vertices, which makes [`Self::indices`] implied. Does nothing if it
+    /// already is.
+    fn with_unshared_vertices(self) -> Self {
+        if self.indices.is_empty() {
+            return self;
+        }
+        let indices = &self.indices;
+        Self {
+            vertices: unshare(&self.vertices, indices),
+            uv: unshare(&self.uv, indices),
+            colors: unshare(&self.colors, indices),
+            ambient_occlusion: unshare(&self.ambient_occlusion, indices),
+            normals: unshare(&self.normals, indices),
+            tangents: unshare(&self.tangents, indices),
+            uv2: unshare(&self.uv2, indices),
+            custom_attributes: self.custom_attributes.iter().map(|attribute| attribute.unshared(indices)).collect(),
+            indices: Vec::new()
+        }
+    }
+}
+
+/// The unit normal of the triangle at `positions`, which points towards the
+/// side that it is wound counterclockwise from, or [`None`] if the triangle is
+/// degenerate. The world is left-handed, so that is the opposite of the
+/// direction of the cross product of its first two edges.
+///
+/// Triangles whose area is within rounding errors of 0 for their size, such
+/// as those around the poles of a sphere whose vertices are only almost at
+/// the same position, count as degenerate, because the direction of their
+/// cross product is just noise.
+#[expect(clippy::float_arithmetic, reason = "The threshold is proportional to the size of the triangle.")]
+fn triangle_normal(positions: [Vec3; 3]) -> Option<Vec3> {
+    let cross = (positions[2] - positions[0]).cross(positions[1] - positions[0]);
+    let longest_edge_length_squared = [0, 1, 2]
+        .map(|corner| positions[corner].distance_squared(positions[(corner + 1) % 3]))
+        .into_iter()
+        .fold(0.0, f32::max);
+    if cross.length() <= f32::EPSILON * longest_edge_length_squared {
+        return None;
+    }
+    cross.try_normalize()
+}
+
+/// The angle of the triangle at `positions` at its `corner`-th vertex.
+fn corner_angle(positions: [Vec3; 3], corner: usize) -> f32 {
+    let vertex = positions[corner];
+    (positions[(corner + 1) % 3] - vertex).angle_between(positions[(corner + 2) % 3] - vertex)
+}
+
+/// The value of `values` at each of `indices`, or nothing if `values` is
+/// empty.
+fn unshare<T: Copy>(values: &[T], indices: &[usize]) -> Vec<T> {
+    if values.is_empty() {
+        return Vec::new();
+    }
+    indices.iter().map(|index| values[*index]).collect()
+}
+
+/// The number of custom vertex attributes that a [`Mesh`] can have, which are
+/// [`VertexAttributeKind::Custom0`] to [`VertexAttributeKind::Custom3`].
+pub const CUSTOM_VERTEX_ATTRIBUTE_COUNT: usize = 4;
+
+/// A vertex attribute of a [`Mesh`] whose meaning is up to the
+/// [`crate::material::Material`] that uses it, such as voxel data packed into a
+/// [`prim@u32`]. Each variant is uploaded as the `wgpu` vertex format of the
+/// same name.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CustomVertexAttribute {
+    Uint32(Vec<u32>),
+    Uint32x2(Vec<UVec2>),
+    Uint32x4(Vec<UVec4>),
+    Sint32(Vec<i32>),
+    Sint32x2(Vec<IVec2>),
+    Sint32x4(Vec<IVec4>),
+    Float32(Vec<f32>),
+    Float32x2(Vec<Vec2>),
+    Float32x3(Vec<Vec3>),
+    Float32x4(Vec<Vec4>),
+    Unorm8x4(Vec<U8Vec4>)
+}
+
+impl CustomVertexAttribute {
+    const fn len(&self) -> usize {
+        match self {
+            Self::Uint32(values) => values.len(),
+            Self::Uint32x2(values) => values.len(),
+            Self::Uint32x4(values) => values.len(),
+            Self::Sint32(values) => values.len(),
+            Self::Sint32x2(values) => values.len(),
+            Self::Sint32x4(values) => values.len(),
+            Self::Float32(values) => values.len(),
+            Self::Float32x2(values) => values.len(),
+            Self::Float32x3(values) => values.len(),
+            Self::Float32x4(values) => values.len(),
+            Self::Unorm8x4(values) => values.len()
+        }
+    }
+
+    /// See [`unshare`].
+    fn unshared(&self, indices: &[usize]) -> Self {
+        match self {
+            Self::Uint32(values) => Self::Uint32(unshare(values, indices)),
+            Self::Uint32x2(values) => Self::Uint32x2(unshare(values, indices)),
+            Self::Uint32x4(values) => Self::Uint32x4(unshare(values, indices)),
+            Self::Sint32(values) => Self::Sint32(unshare(values, indices)),
+            Self::Sint32x2(values) => Self::Sint32x2(unshare(values, indices)),
+            Self::Sint32x4(values) => Self::Sint32x4(unshare(values, indices)),
+            Self::Float32(values) => Self::Float32(unshare(values, indices)),
+            Self::Float32x2(values) => Self::Float32x2(unshare(values, indices)),
+            Self::Float32x3(values) => Self::Float32x3(unshare(values, indices)),
+            Self::Float32x4(values) => Self::Float32x4(unshare(values, indices)),
+            Self::Unorm8x4(values) => Self::Unorm8x4(unshare(values, indices))
+        }
+    }
 }
 
 pub trait AsMeshBuilder<BuilderState: State> {
@@ -130,9 +435,19 @@ pub enum MeshCreationError {
     #[error("All vertex attributes are empty, but `indices` is not empty. It's length is {}", .indices_length)]
     EmptyMeshWithNonemptyIndices {
         indices_length: usize
+    },
+    #[error("A mesh can have at most {} custom vertex attributes, but {} were given.", CUSTOM_VERTEX_ATTRIBUTE_COUNT, .custom_attributes_length)]
+    TooManyCustomAttributes {
+        custom_attributes_length: usize
     }
 }
 
+#[derive(Debug, Error)]
+pub enum TangentGenerationError {
+    #[error("Generating tangents requires the vertex attribute {0:?}, but it is empty.")]
+    MissingAttribute(VertexAttributeKind)
+}
+
 /// Used in a [`crate::material::Material`] to specify which vertex attributes
 /// it needs and which `@location()` to assign to each of them in the shader.
 /// 
@@ -140,10 +455,215 @@ pub enum MeshCreationError {
 /// return value of
 /// [`crate::material::Material::attribute_to_shader_location_mapping`] will be
 /// indexed out of bounds.
-#[derive(EnumCount)]
+#[derive(EnumCount, EnumIter, Clone, Copy, Debug, PartialEq, Eq, Hash)]
 #[repr(usize)]
 pub enum VertexAttributeKind {
     Positions,
     Uv,
-    Colors
+    Colors,
+    AmbientOcclusion,
+    Normals,
+    Tangents,
+    Uv2,
+    Custom0,
+    Custom1,
+    Custom2,
+    Custom3
+}
+
+impl VertexAttributeKind {
+    /// The index of this custom vertex attribute in
+    /// [`Mesh::custom_attributes`], or [`None`] if it is not a custom vertex
+    /// attribute.
+    pub const fn custom_index(self) -> Option<usize> {
+        match self {
+            Self::Custom0 => Some(0),
+            Self::Custom1 => Some(1),
+            Self::Custom2 => Some(2),
+            Self::Custom3 => Some(3),
+            _ => None
+        }
+    }
+
+    /// The name of the field of [`Mesh`] that holds this vertex attribute.
+    const fn field_name(self) -> &'static str {
+        match self {
+            Self::Positions => "vertices",
+            Self::Uv => "uv",
+            Self::Colors => "colors",
+            Self::AmbientOcclusion => "ambient_occlusion",
+            Self::Normals => "normals",
+            Self::Tangents => "tangents",
+            Self::Uv2 => "uv2",
+            Self::Custom0 | Self::Custom1 | Self::Custom2 | Self::Custom3 => "custom_attributes"
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::*;
+    use crate::shapes::*;
+
+    const RINGS: usize = 16;
+    const SECTORS: usize = 32;
+
+    /// A unit sphere whose vertices are shared by the triangles around them,
+    /// with a seam where U wraps around and degenerate triangles at the poles.
+    #[expect(clippy::cast_precision_loss, reason = "The sphere has few rings and sectors.")]
+    #[expect(clippy::float_arithmetic, reason = "The vertices are calculated from spherical coordinates.")]
+    fn sphere() -> Mesh {
+        let mut vertices = Vec::new();
+        let mut uv = Vec::new();
+        for ring in 0..=RINGS {
+            let v = ring as f32 / RINGS as f32;
+            for sector in 0..=SECTORS {
+                let u = sector as f32 / SECTORS as f32;
+                let (polar_sin, polar_cos) = (v * PI).sin_cos();
+                let (azimuth_sin, azimuth_cos) = (u * TAU).sin_cos();
+                vertices.push(Vec3::new(polar_sin * azimuth_cos, -polar_cos, polar_sin * azimuth_sin));
+                uv.push(Vec2::new(u, v));
+            }
+        }
+        let index = |ring: usize, sector: usize| ring * (SECTORS + 1) + sector;
+        let mut indices = Vec::new();
+        for ring in 0..RINGS {
+            for sector in 0..SECTORS {
+                let corners = [index(ring, sector), index(ring, sector + 1), index(ring + 1, sector + 1), index(ring + 1, sector)];
+                indices.extend([0, 1, 2, 2, 3, 0].map(|corner| corners[corner]));
+            }
+        }
+        Mesh::builder().vertices(vertices).uv(uv).indices(indices).build().expect("The sphere should be a valid mesh.")
+    }
+
+    /// Checks that every tangent is a unit vector perpendicular to its normal,
+    /// and that the tangent and the bitangent point along increasing U and V
+    /// across every triangle.
+    fn assert_tangents_follow_uv(mesh: &Mesh) {
+        for (tangent, normal) in mesh.tangents().iter().zip(mesh.normals()) {
+            assert!(tangent.truncate().is_normalized(), "The tangent {tangent} should be normalized.");
+            assert!(tangent.truncate().dot(*normal).abs() < 1e-4, "The tangent {tangent} should be perpendicular to the normal {normal}.");
+        }
+        for triangle in mesh.triangles() {
+            let [first_edge, second_edge] = [1, 2].map(|corner| mesh.vertices()[triangle[corner]] - mesh.vertices()[triangle[0]]);
+            let [first_uv_edge, second_uv_edge] = [1, 2].map(|corner| mesh.uv()[triangle[corner]] - mesh.uv()[triangle[0]]);
+            if triangle_normal([0, 1, 2].map(|corner| mesh.vertices()[triangle[corner]])).is_none() {
+                continue;
+            }
+            let uv_area = first_uv_edge.perp_dot(second_uv_edge);
+            let position_per_u = (first_edge * second_uv_edge.y - second_edge * first_uv_edge.y) / uv_area;
+            let position_per_v = (second_edge * first_uv_edge.x - first_edge * second_uv_edge.x) / uv_area;
+            for index in triangle {
+                let tangent = mesh.tangents()[index];
+                let bitangent = mesh.normals()[index].cross(tangent.truncate()) * tangent.w;
+                assert!(tangent.truncate().dot(position_per_u) > 0.0, "The tangent {tangent} should point along increasing U.");
+                assert!(bitangent.dot(position_per_v) > 0.0, "The bitangent {bitangent} should point along increasing V.");
+            }
+        }
+    }
+
+    #[test]
+    fn cuboid_normals_are_regenerated_as_they_are_authored() {
+        let cuboid = Cuboid { dimensions: Vec3::new(1.0, 2.0, 3.0) }.as_mesh_builder().build().expect("A cuboid should always be a valid mesh.");
+        let authored_normals = cuboid.normals().clone();
+        let smooth = cuboid.with_smooth_normals();
+        assert_eq!(smooth.normals(), &authored_normals);
+        let flat = smooth.with_flat_normals();
+        assert!(flat.indices().is_empty());
+        assert_eq!(flat.index_count(), 36);
+        let unshared_normals: Vec<_> = Cuboid { dimensions: Vec3::ONE }.as_mesh_builder().build().expect("A cuboid should always be a valid mesh.")
+            .indices()
+            .iter()
+            .map(|index| authored_normals[*index])
+            .collect();
+        assert_eq!(flat.normals(), &unshared_normals);
+        assert_eq!(flat.uv().len(), 36);
+        let with_tangents = flat.with_tangents().expect("The cuboid should have every attribute that tangents need.");
+        assert_tangents_follow_uv(&with_tangents);
+        // The +X face has U along +Z and V along +Y.
+        assert_eq!(with_tangents.tangents()[0], Vec4::new(0.0, 0.0, 1.0, -1.0));
+    }
+
+    #[test]
+    fn sphere_normals_point_away_from_its_center() {
+        let smooth = sphere().with_smooth_normals();
+        // At each pole, the vertex at the end of the seam is only used by
+        // degenerate triangles.
+        assert_eq!(smooth.normals().iter().filter(|normal| **normal == Vec3::ZERO).count(), 2);
+        for (vertex, normal) in smooth.vertices().iter().zip(smooth.normals()).filter(|(_, normal)| **normal != Vec3::ZERO) {
+            assert!(vertex.dot(*normal) > 0.99, "The smooth normal {normal} at {vertex} should point away from the center.");
+        }
+        let flat = smooth.with_flat_normals();
+        for (triangle, normals) in flat.vertices().as_chunks::<3>().0.iter().zip(flat.normals().as_chunks::<3>().0) {
+            let center = (triangle[0] + triangle[1] + triangle[2]) / 3.0;
+            if normals[0] != Vec3::ZERO {
+                assert!(center.normalize().dot(normals[0]) > 0.99, "The flat normal {} of a triangle at {center} should point away from the center.", normals[0]);
+            }
+        }
+    }
+
+    #[test]
+    fn sphere_tangents_follow_its_uv() {
+        let with_tangents = sphere().with_smooth_normals().with_tangents().expect("The sphere should have every attribute that tangents need.");
+        assert_tangents_follow_uv(&with_tangents);
+    }
+
+    #[test]
+    fn regenerating_normals_clears_tangents() {
+        let with_tangents = || sphere().with_smooth_normals().with_tangents().expect("The sphere should have every attribute that tangents need.");
+        assert!(with_tangents().with_smooth_normals().tangents().is_empty(), "Smooth normals should clear the tangents made for the old normals.");
+        assert!(with_tangents().with_flat_normals().tangents().is_empty(), "Flat normals should clear the tangents made for the old normals.");
+    }
+
+    #[test]
+    fn tangents_need_normals() {
+        assert!(matches!(sphere().with_tangents(), Err(TangentGenerationError::MissingAttribute(VertexAttributeKind::Normals))));
+    }
+
+    #[test]
+    fn indices_are_checked_against_the_shortest_attribute() {
+        let quad_indices = vec![0, 1, 2, 2, 3, 0];
+        let vertices = vec![Vec3::ZERO, Vec3::X, Vec3::ONE, Vec3::Y];
+        let result = Mesh::builder()
+            .vertices(vertices.clone())
+            .indices(quad_indices.clone())
+            .normals(vec![Vec3::Z; 4])
+            .custom_attributes(vec![CustomVertexAttribute::Uint32(vec![0; 3])])
+            .build();
+        assert!(matches!(
+            result,
+            Err(MeshCreationError::IndicesOutOfBounds { out_of_bounds_vertex_attribute_name: "custom_attributes", out_of_bounds_vertex_attribute_length: 3, max_index: 3 })
+        ));
+        let mesh = Mesh::builder()
+            .vertices(vertices)
+            .indices(quad_indices)
+            .tangents(vec![Vec4::new(1.0, 0.0, 0.0, 1.0); 4])
+            .uv2(vec![Vec2::ZERO; 4])
+            .custom_attributes(vec![CustomVertexAttribute::Uint32(vec![0; 4]), CustomVertexAttribute::Float32x2(vec![Vec2::ZERO; 5])])
+            .build()
+            .expect("Every attribute should be long enough.");
+        assert!(mesh.has_attribute(VertexAttributeKind::Custom1));
+        assert!(!mesh.has_attribute(VertexAttributeKind::Custom2));
+        assert!(!mesh.has_attribute(VertexAttributeKind::Normals));
+    }
+
+    #[test]
+    fn implied_indices_use_the_shortest_attribute() {
+        let mesh = Mesh::builder()
+            .vertices(vec![Vec3::ZERO; 6])
+            .normals(vec![Vec3::Y; 3])
+            .build()
+            .expect("Implied indices are always in bounds.");
+        assert_eq!(mesh.index_count(), 3);
+    }
+
+    #[test]
+    fn at_most_four_custom_attributes_are_allowed() {
+        let result = Mesh::builder()
+            .vertices(vec![Vec3::ZERO; 3])
+            .custom_attributes(vec![CustomVertexAttribute::Sint32(vec![0; 3]); CUSTOM_VERTEX_ATTRIBUTE_COUNT + 1])
+            .build();
+        assert!(matches!(result, Err(MeshCreationError::TooManyCustomAttributes { custom_attributes_length: 5 })));
+    }
 }
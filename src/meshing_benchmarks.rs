@@ -0,0 +1,112 @@
+//! Benchmarks that compare [`MeshingMode::Naive`] and [`MeshingMode::Greedy`]
+//! on generated terrain. They are ignored by default because their timings are
+//! only meaningful in release builds. Run them with
+//! `cargo test --release -- --ignored --nocapture meshing_benchmarks`.
+
+use assets_manager::*;
+use glam::*;
+use std::time::*;
+use crate::block::*;
+use crate::block_registry::*;
+use crate::block_texture_atlas::*;
+use crate::chunk_mesher::*;
+use crate::constants::*;
+use crate::voxel_world::*;
+
+/// The generated terrain is this many columns along X and Z.
+const TERRAIN_SIZE_IN_COLUMNS: i32 = 8;
+
+/// The height of the water surface of the generated terrain.
+const SEA_LEVEL: i32 = 0;
+
+/// How many times every section is meshed in each mode, to average out noise.
+const ITERATIONS: u32 = 5;
+
+fn block_id(block_registry: &BlockRegistry, path: &str) -> BlockId {
+    let name = BlockName::new(BlockName::DEFAULT_NAMESPACE, path).expect("The block name should be valid.");
+    block_registry.id(&name).expect("The block should be defined.")
+}
+
+/// Generates rolling hills made of a few layers of blocks, with water filling
+/// the valleys, which has both large flat areas and lots of steps.
+#[expect(clippy::float_arithmetic, reason = "The height of the terrain is a sum of sine waves.")]
+#[expect(clippy::cast_possible_truncation, reason = "The height of the terrain is small.")]
+#[expect(clippy::cast_precision_loss, reason = "Block positions in the terrain are small.")]
+fn generate_terrain(block_registry: &BlockRegistry) -> VoxelWorld {
+    let stone = block_id(block_registry, "stone");
+    let dirt = block_id(block_registry, "dirt");
+    let grass_block = block_id(block_registry, "grass_block");
+    let sand = block_id(block_registry, "sand");
+    let water = block_id(block_registry, "water");
+    let bedrock = block_id(block_registry, "bedrock");
+    let mut voxel_world = VoxelWorld::default();
+    for column_x in 0..TERRAIN_SIZE_IN_COLUMNS {
+        for column_z in 0..TERRAIN_SIZE_IN_COLUMNS {
+            voxel_world.insert_column(IVec2::new(column_x, column_z), ChunkColumn::default());
+        }
+    }
+    let min_y = COLUMN_MIN_SECTION_Y * SECTION_SIZE;
+    for x in 0..TERRAIN_SIZE_IN_COLUMNS * SECTION_SIZE {
+        for z in 0..TERRAIN_SIZE_IN_COLUMNS * SECTION_SIZE {
+            let (x_f32, z_f32) = (x as f32, z as f32);
+            let height = (x_f32 * 0.07).sin().mul_add(9.0, (z_f32 * 0.05).cos().mul_add(7.0, ((x_f32 + z_f32) * 0.13).sin() * 3.0)) as i32;
+            for y in min_y..=height.max(SEA_LEVEL) {
+                let block = if y == min_y {
+                    bedrock
+                }
+                else if y > height {
+                    water
+                }
+                else if y < height - 3 {
+                    stone
+                }
+                else if height <= SEA_LEVEL + 1 {
+                    sand
+                }
+                else if y < height {
+                    dirt
+                }
+                else {
+                    grass_block
+                };
+                voxel_world.set_block(IVec3::new(x, y, z), block).expect("The terrain should be inside the loaded columns.");
+            }
+        }
+    }
+    voxel_world
+}
+
+/// The total number of vertices and the total time spent meshing every section
+/// of `voxel_world` once, averaged over [`ITERATIONS`].
+fn measure(voxel_world: &VoxelWorld, meshing_table: &BlockMeshingTable, meshing_mode: MeshingMode) -> (usize, Duration) {
+    let snapshots: Vec<_> = voxel_world.sections()
+        .filter_map(|(section_position, _)| SectionSnapshot::new(voxel_world, section_position))
+        .collect();
+    let mut vertex_count = 0;
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        vertex_count = snapshots.iter()
+            .map(|snapshot| mesh_section(snapshot, meshing_table, meshing_mode).expect("The section should be meshed.").vertices().len())
+            .sum();
+    }
+    (vertex_count, start.elapsed() / ITERATIONS)
+}
+
+#[test]
+#[ignore = "Timings are only meaningful in release builds."]
+fn compare_naive_and_greedy_meshing() {
+    let asset_cache = AssetCache::new(ASSETS_PATH).expect("The assets directory should exist.");
+    let block_definitions = asset_cache.load::<BlockDefinitions>(BLOCKS_ASSET_ID).expect("The block definitions should be loaded.");
+    let block_registry = BlockRegistry::new(&block_definitions.read()).expect("The block registry should be created.");
+    let atlas = BlockTextureAtlas::new(&asset_cache, &block_registry);
+    let meshing_table = BlockMeshingTable::new(&block_registry, &atlas);
+    let voxel_world = generate_terrain(&block_registry);
+    let mut vertex_counts = Vec::new();
+    println!("{:<8} {:>10} {:>10}", "Mode", "Vertices", "Time (µs)");
+    for (name, meshing_mode) in [("Naive", MeshingMode::Naive), ("Greedy", MeshingMode::Greedy)] {
+        let (vertex_count, duration) = measure(&voxel_world, &meshing_table, meshing_mode);
+        println!("{name:<8} {vertex_count:>10} {:>10}", duration.as_micros());
+        vertex_counts.push(vertex_count);
+    }
+    assert!(vertex_counts[1] < vertex_counts[0], "Greedy meshing should produce fewer vertices than naive meshing.");
+}
@@ -0,0 +1,301 @@
+//! Contains a reader for NBT, the binary format that Minecraft stores chunks
+//! and other data in. Only the Java Edition flavour is supported, where every
+//! number is big-endian.
+//!
+//! Strings are stored as Java's modified UTF-8, which only differs from UTF-8
+//! for the null character and characters outside the Basic Multilingual Plane.
+//! Neither appear in block or biome names, so strings are decoded as UTF-8,
+//! replacing invalid sequences.
+
+#![expect(clippy::big_endian_bytes, reason = "NBT is big-endian.")]
+
+use std::collections::*;
+use std::iter;
+use thiserror::*;
+
+/// How deeply lists and compounds may be nested. Minecraft uses the same
+/// limit, and it stops malicious files from overflowing the stack.
+const MAX_DEPTH: usize = 512;
+
+/// A value in an NBT document.
+#[derive(Clone, Debug, PartialEq)]
+pub enum NbtTag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(String),
+    /// Every element has the same type.
+    List(Vec<Self>),
+    Compound(NbtCompound),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>)
+}
+
+impl NbtTag {
+    /// The number that identifies the type of the tag in NBT documents.
+    #[cfg(test)]
+    const fn id(&self) -> u8 {
+        match self {
+            Self::Byte(_) => 1,
+            Self::Short(_) => 2,
+            Self::Int(_) => 3,
+            Self::Long(_) => 4,
+            Self::Float(_) => 5,
+            Self::Double(_) => 6,
+            Self::ByteArray(_) => 7,
+            Self::String(_) => 8,
+            Self::List(_) => 9,
+            Self::Compound(_) => 10,
+            Self::IntArray(_) => 11,
+            Self::LongArray(_) => 12
+        }
+    }
+}
+
+/// Named tags in no particular order.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NbtCompound {
+    tags: HashMap<String, NbtTag>
+}
+
+impl NbtCompound {
+    pub fn get(&self, name: &str) -> Option<&NbtTag> {
+        self.tags.get(name)
+    }
+
+    pub fn insert(&mut self, name: impl Into<String>, tag: NbtTag) {
+        self.tags.insert(name.into(), tag);
+    }
+
+    /// Returns [`None`] if there is no byte named `name`. The same goes for the
+    /// other getters of specific types.
+    pub fn get_byte(&self, name: &str) -> Option<i8> {
+        match self.get(name)? {
+            NbtTag::Byte(value) => Some(*value),
+            _ => None
+        }
+    }
+
+    pub fn get_int(&self, name: &str) -> Option<i32> {
+        match self.get(name)? {
+            NbtTag::Int(value) => Some(*value),
+            _ => None
+        }
+    }
+
+    pub fn get_string(&self, name: &str) -> Option<&str> {
+        match self.get(name)? {
+            NbtTag::String(value) => Some(value),
+            _ => None
+        }
+    }
+
+    pub fn get_list(&self, name: &str) -> Option<&[NbtTag]> {
+        match self.get(name)? {
+            NbtTag::List(value) => Some(value),
+            _ => None
+        }
+    }
+
+    pub fn get_compound(&self, name: &str) -> Option<&Self> {
+        match self.get(name)? {
+            NbtTag::Compound(value) => Some(value),
+            _ => None
+        }
+    }
+
+    pub fn get_long_array(&self, name: &str) -> Option<&[i64]> {
+        match self.get(name)? {
+            NbtTag::LongArray(value) => Some(value),
+            _ => None
+        }
+    }
+}
+
+/// Reads an NBT document whose root is a compound, as Minecraft chunks are,
+/// and returns it along with its name. `bytes` must already be decompressed.
+pub fn read_nbt(bytes: &[u8]) -> Result<(String, NbtCompound), NbtError> {
+    let mut reader = NbtReader { remaining: bytes };
+    let tag_id = reader.read_u8()?;
+    if tag_id != 10 {
+        return Err(NbtError::RootNotCompound(tag_id));
+    }
+    let name = reader.read_string()?;
+    let root = reader.read_compound(0)?;
+    Ok((name, root))
+}
+
+/// Writes `root` as an NBT document named `name`, in the format that
+/// [`read_nbt`] reads. It is only used to make test fixtures.
+#[cfg(test)]
+pub fn write_nbt(name: &str, root: &NbtCompound) -> Vec<u8> {
+    let mut bytes = vec![10];
+    write_string(&mut bytes, name);
+    write_compound(&mut bytes, root);
+    bytes
+}
+
+struct NbtReader<'a> {
+    remaining: &'a [u8]
+}
+
+impl NbtReader<'_> {
+    fn read_bytes<const N: usize>(&mut self) -> Result<[u8; N], NbtError> {
+        let (bytes, rest) = self.remaining.split_first_chunk::<N>().ok_or(NbtError::UnexpectedEnd)?;
+        self.remaining = rest;
+        Ok(*bytes)
+    }
+
+    fn read_u8(&mut self) -> Result<u8, NbtError> {
+        Ok(u8::from_be_bytes(self.read_bytes()?))
+    }
+
+    fn read_i16(&mut self) -> Result<i16, NbtError> {
+        Ok(i16::from_be_bytes(self.read_bytes()?))
+    }
+
+    fn read_i32(&mut self) -> Result<i32, NbtError> {
+        Ok(i32::from_be_bytes(self.read_bytes()?))
+    }
+
+    fn read_i64(&mut self) -> Result<i64, NbtError> {
+        Ok(i64::from_be_bytes(self.read_bytes()?))
+    }
+
+    /// Reads the length of an array or a list. Negative lengths are treated as
+    /// 0, like Minecraft does.
+    fn read_length(&mut self) -> Result<usize, NbtError> {
+        Ok(usize::try_from(self.read_i32()?).unwrap_or_default())
+    }
+
+    fn read_string(&mut self) -> Result<String, NbtError> {
+        let length = usize::from(u16::from_be_bytes(self.read_bytes()?));
+        let (bytes, rest) = self.remaining.split_at_checked(length).ok_or(NbtError::UnexpectedEnd)?;
+        self.remaining = rest;
+        Ok(String::from_utf8_lossy(bytes).into_owned())
+    }
+
+    /// Reads `length` elements with `read_element`, making sure first that
+    /// there are enough bytes left for them, so that a corrupted length
+    /// cannot make it allocate a huge amount of memory.
+    fn read_array<T>(&mut self, element_size: usize, mut read_element: impl FnMut(&mut Self) -> Result<T, NbtError>) -> Result<Vec<T>, NbtError> {
+        let length = self.read_length()?;
+        if length.saturating_mul(element_size) > self.remaining.len() {
+            return Err(NbtError::UnexpectedEnd);
+        }
+        iter::repeat_with(|| read_element(self)).take(length).collect()
+    }
+
+    fn read_compound(&mut self, depth: usize) -> Result<NbtCompound, NbtError> {
+        let mut compound = NbtCompound::default();
+        loop {
+            let tag_id = self.read_u8()?;
+            if tag_id == 0 {
+                return Ok(compound);
+            }
+            let name = self.read_string()?;
+            compound.insert(name, self.read_tag(tag_id, depth + 1)?);
+        }
+    }
+
+    fn read_tag(&mut self, tag_id: u8, depth: usize) -> Result<NbtTag, NbtError> {
+        if depth > MAX_DEPTH {
+            return Err(NbtError::TooDeep);
+        }
+        Ok(match tag_id {
+            1 => NbtTag::Byte(i8::from_be_bytes(self.read_bytes()?)),
+            2 => NbtTag::Short(self.read_i16()?),
+            3 => NbtTag::Int(self.read_i32()?),
+            4 => NbtTag::Long(self.read_i64()?),
+            5 => NbtTag::Float(f32::from_be_bytes(self.read_bytes()?)),
+            6 => NbtTag::Double(f64::from_be_bytes(self.read_bytes()?)),
+            7 => NbtTag::ByteArray(self.read_array(1, |reader| Ok(i8::from_be_bytes(reader.read_bytes()?)))?),
+            8 => NbtTag::String(self.read_string()?),
+            9 => {
+                let element_id = self.read_u8()?;
+                // Every tag takes at least a byte. Empty lists may have the
+                // element type 0.
+                NbtTag::List(self.read_array(1, |reader| reader.read_tag(element_id, depth + 1))?)
+            }
+            10 => NbtTag::Compound(self.read_compound(depth)?),
+            11 => NbtTag::IntArray(self.read_array(4, Self::read_i32)?),
+            12 => NbtTag::LongArray(self.read_array(8, Self::read_i64)?),
+            _ => return Err(NbtError::InvalidTagType(tag_id))
+        })
+    }
+}
+
+#[cfg(test)]
+fn write_string(bytes: &mut Vec<u8>, string: &str) {
+    #[expect(clippy::cast_possible_truncation, reason = "Strings in test fixtures are short.")]
+    bytes.extend_from_slice(&(string.len() as u16).to_be_bytes());
+    bytes.extend_from_slice(string.as_bytes());
+}
+
+#[cfg(test)]
+#[expect(clippy::cast_possible_truncation, clippy::cast_possible_wrap, reason = "Arrays in test fixtures are short.")]
+fn write_length(bytes: &mut Vec<u8>, length: usize) {
+    bytes.extend_from_slice(&(length as i32).to_be_bytes());
+}
+
+#[cfg(test)]
+fn write_compound(bytes: &mut Vec<u8>, compound: &NbtCompound) {
+    for (name, tag) in &compound.tags {
+        bytes.push(tag.id());
+        write_string(bytes, name);
+        write_tag(bytes, tag);
+    }
+    bytes.push(0);
+}
+
+#[cfg(test)]
+fn write_tag(bytes: &mut Vec<u8>, tag: &NbtTag) {
+    match tag {
+        NbtTag::Byte(value) => bytes.extend_from_slice(&value.to_be_bytes()),
+        NbtTag::Short(value) => bytes.extend_from_slice(&value.to_be_bytes()),
+        NbtTag::Int(value) => bytes.extend_from_slice(&value.to_be_bytes()),
+        NbtTag::Long(value) => bytes.extend_from_slice(&value.to_be_bytes()),
+        NbtTag::Float(value) => bytes.extend_from_slice(&value.to_be_bytes()),
+        NbtTag::Double(value) => bytes.extend_from_slice(&value.to_be_bytes()),
+        NbtTag::ByteArray(values) => {
+            write_length(bytes, values.len());
+            bytes.extend(values.iter().flat_map(|value| value.to_be_bytes()));
+        }
+        NbtTag::String(value) => write_string(bytes, value),
+        NbtTag::List(values) => {
+            bytes.push(values.first().map_or(0, NbtTag::id));
+            write_length(bytes, values.len());
+            for value in values {
+                write_tag(bytes, value);
+            }
+        }
+        NbtTag::Compound(value) => write_compound(bytes, value),
+        NbtTag::IntArray(values) => {
+            write_length(bytes, values.len());
+            bytes.extend(values.iter().flat_map(|value| value.to_be_bytes()));
+        }
+        NbtTag::LongArray(values) => {
+            write_length(bytes, values.len());
+            bytes.extend(values.iter().flat_map(|value| value.to_be_bytes()));
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum NbtError {
+    #[error("The NBT data ends in the middle of a tag.")]
+    UnexpectedEnd,
+
+    #[error("The root tag of the NBT data has the type {0} instead of being a compound.")]
+    RootNotCompound(u8),
+
+    #[error("The NBT data contains a tag with the unknown type {0}.")]
+    InvalidTagType(u8),
+
+    #[error("The NBT data is nested more than {MAX_DEPTH} levels deep.")]
+    TooDeep
+}
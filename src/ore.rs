@@ -0,0 +1,108 @@
+//! Contains ore-related definitions. Ores are loaded from RON files in the
+//! `assets/ores` directory and placed in veins by
+//! [`crate::terrain_passes::OreVeinPass`].
+
+use assets_manager::*;
+use assets_manager::asset::*;
+use log::*;
+use serde::*;
+use std::borrow::*;
+use thiserror::*;
+use crate::block::*;
+
+/// The ID of the directory in the [`AssetCache`] that [`OreDefinitions`] are
+/// loaded from.
+pub const ORES_ASSET_ID: &str = "ores";
+
+/// The largest [`OreDefinition::vein_size`]. Keeping veins small means that a
+/// vein can only reach the columns next to the one it starts in.
+pub const MAX_VEIN_SIZE: u8 = 16;
+
+/// How often and where an ore appears. Each one is loaded from a RON file in
+/// the `assets/ores` directory, and is named after the file.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OreDefinition {
+    /// The block the ore is made of. It only replaces stone.
+    pub block: BlockName,
+    /// The lowest Y that veins start at.
+    pub min_y: i32,
+    /// The highest Y that veins start at.
+    pub max_y: i32,
+    /// The average number of veins that start in each column. The fractional
+    /// part is the chance of one more vein.
+    pub veins_per_column: f64,
+    /// The number of blocks in each vein, from 1 to [`MAX_VEIN_SIZE`]. Veins
+    /// can be smaller where they leave stone.
+    pub vein_size: u8
+}
+
+impl OreDefinition {
+    fn validate(&self) -> Result<(), OreDefinitionError> {
+        if self.min_y > self.max_y {
+            return Err(OreDefinitionError::EmptyHeightRange(self.min_y, self.max_y));
+        }
+        if !self.veins_per_column.is_finite() || self.veins_per_column.is_sign_negative() {
+            return Err(OreDefinitionError::InvalidVeinsPerColumn(self.veins_per_column));
+        }
+        if !(1..=MAX_VEIN_SIZE).contains(&self.vein_size) {
+            return Err(OreDefinitionError::VeinSizeOutOfRange(self.vein_size));
+        }
+        Ok(())
+    }
+}
+
+impl FileAsset for OreDefinition {
+    const EXTENSION: &'static str = "ron";
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Result<Self, BoxedError> {
+        let definition: Self = load_ron(&bytes)?;
+        if let Err(err) = definition.validate() {
+            error!("{err}");
+            return Err(err.into());
+        }
+        Ok(definition)
+    }
+}
+
+/// Every [`OreDefinition`] directly inside a directory, sorted by name. Ores
+/// are placed in this order, so an ore that comes later can replace the stone
+/// around an earlier one, but never the earlier ore itself.
+pub struct OreDefinitions {
+    definitions: Vec<(String, OreDefinition)>
+}
+
+impl OreDefinitions {
+    /// Iterates over the definitions in order of their names, along with their
+    /// names.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &OreDefinition)> {
+        self.definitions.iter().map(|(name, definition)| (name.as_str(), definition))
+    }
+}
+
+impl Asset for OreDefinitions {
+    fn load(cache: &AssetCache, id: &SharedString) -> Result<Self, BoxedError> {
+        info!("Loading ore definitions: {id}");
+        let directory = cache.load_dir::<OreDefinition>(id)?;
+        let mut definitions = Vec::new();
+        for asset_id in directory.read().ids() {
+            let name = asset_id.rsplit_once('.').map_or(asset_id.as_str(), |(_, name)| name);
+            definitions.push((name.to_owned(), cache.load::<OreDefinition>(asset_id)?.cloned()));
+        }
+        definitions.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        info!("Loaded {} ore definitions.", definitions.len());
+        Ok(Self { definitions })
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum OreDefinitionError {
+    #[error("The minimum Y {0} is higher than the maximum Y {1}.")]
+    EmptyHeightRange(i32, i32),
+
+    #[error("The number of veins per column {0} is not a finite non-negative number.")]
+    InvalidVeinsPerColumn(f64),
+
+    #[error("The vein size {0} is not between 1 and {MAX_VEIN_SIZE}.")]
+    VeinSizeOutOfRange(u8)
+}
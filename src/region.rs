@@ -0,0 +1,473 @@
+//! Contains the format of region files, which store the saved columns of a
+//! world. Each region file stores the columns of a [`REGION_SIZE`]×
+//! [`REGION_SIZE`] area, each compressed with zlib on its own, so that a single
+//! column can be read without decompressing the rest of its region.
+//!
+//! A region file starts with [`REGION_FILE_MAGIC`] and [`REGION_FILE_VERSION`],
+//! followed by the compressed length of every column in the region, which is 0
+//! for columns that have not been saved, in the order of
+//! [`region_local_index`]. The compressed columns follow in the same order.
+//!
+//! Before compression, a column is made of:
+//! - The number of distinct blocks in it, followed by their names.
+//! - The number of distinct biomes in it, followed by their names.
+//! - The index into the biome names of every column of blocks, one byte each,
+//!   in the order of [`ChunkColumn::biome`].
+//! - For each section from the bottom to the top, the length of its palette,
+//!   the palette as indices into the block names, the bits per entry as a
+//!   byte, the number of `u64`s of packed entries and the packed entries, as
+//!   in [`PalettedContainer::from_raw_parts`].
+//!
+//! Lengths and indices are `u16`s and names are UTF-8 strings prefixed with
+//! their lengths. All numbers are little-endian. Blocks and biomes are stored
+//! by name because [`BlockId`]s are only stable for a single run and
+//! [`BiomeId`]s change whenever biomes are added or removed.
+
+#![expect(clippy::little_endian_bytes, reason = "Region files are little-endian so that they can be copied between machines.")]
+
+use flate2::Compression;
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use glam::*;
+use log::*;
+use std::collections::*;
+use std::fs::File;
+use std::fs;
+use std::io::{self, BufReader, ErrorKind, Read, Write};
+use std::path::*;
+use thiserror::*;
+use crate::biome::*;
+use crate::block::*;
+use crate::block_registry::*;
+use crate::voxel_world::*;
+
+/// The number of columns along each side of a region.
+pub const REGION_SIZE: i32 = 32;
+
+/// The number of columns in a region.
+const REGION_AREA: usize = 1024;
+
+/// The bytes that every region file starts with.
+pub const REGION_FILE_MAGIC: [u8; 4] = *b"MCRG";
+
+/// Increased whenever the format changes.
+pub const REGION_FILE_VERSION: u32 = 1;
+
+/// The size of the magic, the version and the lengths of the columns.
+const REGION_HEADER_SIZE: usize = 8 + REGION_AREA * 4;
+
+/// The position of the region containing the column at `column_position`, in
+/// regions.
+pub const fn column_to_region_position(column_position: IVec2) -> IVec2 {
+    IVec2::new(column_position.x >> 5, column_position.y >> 5)
+}
+
+/// The name of the file of the region at `region_position`.
+pub fn region_file_name(region_position: IVec2) -> String {
+    format!("r.{}.{}.region", region_position.x, region_position.y)
+}
+
+/// The index of the column at `column_position` inside its region. X changes
+/// the fastest, then Z.
+pub const fn region_local_index(column_position: IVec2) -> usize {
+    ((column_position.y & (REGION_SIZE - 1)) * REGION_SIZE + (column_position.x & (REGION_SIZE - 1))).cast_unsigned() as usize
+}
+
+/// The names that [`BlockId`]s and [`BiomeId`]s stand for in a run, which are
+/// needed to save columns and to load them back.
+pub struct StorageNames {
+    /// Indexed by [`BlockId`].
+    block_names: Vec<Option<BlockName>>,
+    block_ids: HashMap<BlockName, BlockId>,
+    /// Indexed by [`BiomeId`].
+    biome_names: Vec<String>
+}
+
+impl StorageNames {
+    pub fn new(block_registry: &BlockRegistry, biome_definitions: &BiomeDefinitions) -> Self {
+        let mut block_names = Vec::new();
+        let mut block_ids = HashMap::new();
+        for (id, block_type) in block_registry.iter() {
+            let index = usize::from(id.0);
+            if block_names.len() <= index {
+                block_names.resize(index + 1, None);
+            }
+            block_names[index] = Some(block_type.name.clone());
+            block_ids.insert(block_type.name.clone(), id);
+        }
+        let biome_names = biome_definitions.iter().map(|(_, name, _)| name.to_owned()).collect();
+        Self { block_names, block_ids, biome_names }
+    }
+
+    /// * `block_names`: Indexed by [`BlockId`].
+    /// * `biome_names`: Indexed by [`BiomeId`].
+    #[cfg(test)]
+    pub fn from_names(block_names: Vec<BlockName>, biome_names: Vec<String>) -> Self {
+        let block_ids = (0..).zip(&block_names).map(|(id, name)| (name.clone(), BlockId(id))).collect();
+        Self { block_names: block_names.into_iter().map(Some).collect(), block_ids, biome_names }
+    }
+
+    /// Blocks whose definitions were removed have no names, and are saved as
+    /// air.
+    pub fn block_name(&self, id: BlockId) -> Option<&BlockName> {
+        self.block_names.get(usize::from(id.0))?.as_ref()
+    }
+
+    pub fn block_id(&self, name: &BlockName) -> Option<BlockId> {
+        self.block_ids.get(name).copied()
+    }
+
+    fn biome_name(&self, id: BiomeId) -> Option<&str> {
+        self.biome_names.get(usize::from(id.0)).map(String::as_str)
+    }
+
+    fn biome_id(&self, name: &str) -> Option<BiomeId> {
+        self.biome_names.iter().position(|biome_name| biome_name == name).and_then(|index| u8::try_from(index).ok()).map(BiomeId)
+    }
+}
+
+/// Serializes and compresses `column` in the format described in the module
+/// documentation.
+pub fn encode_column(column: &ChunkColumn, names: &StorageNames) -> Result<Vec<u8>, RegionError> {
+    let air_name = BlockName::air();
+    let mut block_names: Vec<&BlockName> = Vec::new();
+    let mut block_indices = HashMap::new();
+    for (_, section) in column.sections() {
+        for block in section.blocks().palette() {
+            block_indices.entry(*block).or_insert_with(|| {
+                let name = names.block_name(*block).unwrap_or(&air_name);
+                let index = block_names.iter().position(|block_name| *block_name == name).unwrap_or_else(|| {
+                    block_names.push(name);
+                    block_names.len() - 1
+                });
+                to_u16(index)
+            });
+        }
+    }
+    let mut biome_names: Vec<&str> = Vec::new();
+    let mut biome_indices = Vec::with_capacity(COLUMN_AREA);
+    for local_position in column_local_positions() {
+        let name = names.biome_name(column.biome(local_position)).unwrap_or_default();
+        let index = biome_names.iter().position(|biome_name| *biome_name == name).unwrap_or_else(|| {
+            biome_names.push(name);
+            biome_names.len() - 1
+        });
+        biome_indices.push(u8::try_from(index).ok().ok_or(RegionError::InvalidColumn("There are more than 256 biomes in the column."))?);
+    }
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    write_u16(&mut encoder, to_u16(block_names.len()))?;
+    for name in block_names {
+        write_string(&mut encoder, name.as_str())?;
+    }
+    write_u16(&mut encoder, to_u16(biome_names.len()))?;
+    for name in biome_names {
+        write_string(&mut encoder, name)?;
+    }
+    encoder.write_all(&biome_indices)?;
+    for (_, section) in column.sections() {
+        let blocks = section.blocks();
+        write_u16(&mut encoder, to_u16(blocks.palette().len()))?;
+        for block in blocks.palette() {
+            write_u16(&mut encoder, block_indices[block])?;
+        }
+        #[expect(clippy::cast_possible_truncation, reason = "There are at most 16 bits per entry.")]
+        encoder.write_all(&[blocks.bits_per_entry() as u8])?;
+        write_u16(&mut encoder, to_u16(blocks.data().len()))?;
+        for entry in blocks.data() {
+            encoder.write_all(&entry.to_le_bytes())?;
+        }
+    }
+    Ok(encoder.finish()?)
+}
+
+/// Decompresses and deserializes a column made by [`encode_column`]. Blocks
+/// and biomes that no longer exist are replaced with air and the biome with
+/// the ID 0.
+pub fn decode_column(bytes: &[u8], names: &StorageNames) -> Result<ChunkColumn, RegionError> {
+    let mut decoder = ZlibDecoder::new(bytes);
+    let block_name_count = read_u16(&mut decoder)?;
+    let mut blocks = Vec::with_capacity(usize::from(block_name_count));
+    for _ in 0..block_name_count {
+        let name: BlockName = read_string(&mut decoder)?.parse()?;
+        blocks.push(names.block_id(&name).unwrap_or_else(|| {
+            warn!("The saved block {name} no longer exists, so it is replaced with air.");
+            BlockId::AIR
+        }));
+    }
+    let biome_name_count = read_u16(&mut decoder)?;
+    let mut biomes = Vec::with_capacity(usize::from(biome_name_count));
+    for _ in 0..biome_name_count {
+        let name = read_string(&mut decoder)?;
+        biomes.push(names.biome_id(&name).unwrap_or_else(|| {
+            warn!("The saved biome {name} no longer exists, so it is replaced with the biome with the ID 0.");
+            BiomeId::default()
+        }));
+    }
+    let mut column = ChunkColumn::default();
+    let mut biome_indices = [0; COLUMN_AREA];
+    decoder.read_exact(&mut biome_indices)?;
+    for (local_position, biome_index) in column_local_positions().zip(biome_indices) {
+        let biome = biomes.get(usize::from(biome_index)).ok_or(RegionError::InvalidColumn("A biome index is out of range."))?;
+        column.set_biome(local_position, *biome);
+    }
+    for section_y in (COLUMN_MIN_SECTION_Y..).take(COLUMN_SECTION_COUNT) {
+        let palette_length = read_u16(&mut decoder)?;
+        let mut palette = Vec::with_capacity(usize::from(palette_length));
+        for _ in 0..palette_length {
+            let block_index = read_u16(&mut decoder)?;
+            palette.push(*blocks.get(usize::from(block_index)).ok_or(RegionError::InvalidColumn("A block index is out of range."))?);
+        }
+        let mut bits_per_entry = [0];
+        decoder.read_exact(&mut bits_per_entry)?;
+        let data_length = read_u16(&mut decoder)?;
+        let mut data = Vec::with_capacity(usize::from(data_length));
+        for _ in 0..data_length {
+            let mut entry = [0; 8];
+            decoder.read_exact(&mut entry)?;
+            data.push(u64::from_le_bytes(entry));
+        }
+        let container = PalettedContainer::from_raw_parts(palette, u32::from(bits_per_entry[0]), data)
+            .ok_or(RegionError::InvalidColumn("The blocks of a section are inconsistent."))?;
+        if let Some(section) = column.section_mut(section_y) {
+            *section = ChunkSection::new(container);
+        }
+    }
+    Ok(column)
+}
+
+/// The compressed columns of a region, made by [`encode_column`].
+pub struct RegionFile {
+    /// Indexed by [`region_local_index`].
+    columns: Vec<Option<Vec<u8>>>
+}
+
+impl RegionFile {
+    /// Reads the region file at `path`. A file that does not exist is read as
+    /// an empty region.
+    pub fn read(path: &Path) -> Result<Self, RegionError> {
+        match fs::read(path) {
+            Ok(bytes) => Self::from_bytes(&bytes),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err.into())
+        }
+    }
+
+    /// Reads only the compressed column at `column_position` from the region
+    /// file at `path`, which is much faster than reading the whole region.
+    /// Returns [`None`] if the file or the column does not exist.
+    pub fn read_column(path: &Path, column_position: IVec2) -> Result<Option<Vec<u8>>, RegionError> {
+        let mut file = match File::open(path) {
+            Ok(file) => BufReader::new(file),
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into())
+        };
+        let mut header = vec![0; REGION_HEADER_SIZE];
+        file.read_exact(&mut header)?;
+        let lengths = read_header(&header)?;
+        let index = region_local_index(column_position);
+        if lengths[index] == 0 {
+            return Ok(None);
+        }
+        let offset: usize = lengths[..index].iter().sum();
+        file.seek_relative(i64::try_from(offset).ok().ok_or(RegionError::InvalidHeader)?)?;
+        let mut column = vec![0; lengths[index]];
+        file.read_exact(&mut column)?;
+        Ok(Some(column))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, RegionError> {
+        let header = bytes.get(..REGION_HEADER_SIZE).ok_or(RegionError::InvalidHeader)?;
+        let mut remaining = &bytes[REGION_HEADER_SIZE..];
+        let mut columns = Vec::with_capacity(REGION_AREA);
+        for length in read_header(header)? {
+            if length == 0 {
+                columns.push(None);
+                continue;
+            }
+            let (column, rest) = remaining.split_at_checked(length).ok_or(RegionError::Truncated)?;
+            columns.push(Some(column.to_vec()));
+            remaining = rest;
+        }
+        Ok(Self { columns })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(REGION_HEADER_SIZE);
+        bytes.extend_from_slice(&REGION_FILE_MAGIC);
+        bytes.extend_from_slice(&REGION_FILE_VERSION.to_le_bytes());
+        for column in &self.columns {
+            let length = column.as_ref().map_or(0, Vec::len);
+            #[expect(clippy::cast_possible_truncation, reason = "Compressed columns are much smaller than 4 GiB.")]
+            bytes.extend_from_slice(&(length as u32).to_le_bytes());
+        }
+        for column in self.columns.iter().flatten() {
+            bytes.extend_from_slice(column);
+        }
+        bytes
+    }
+
+    /// Replaces the compressed column at `column_position`, which must be
+    /// inside this region.
+    pub fn set_column(&mut self, column_position: IVec2, column: Vec<u8>) {
+        self.columns[region_local_index(column_position)] = Some(column);
+    }
+}
+
+impl Default for RegionFile {
+    #[inline]
+    fn default() -> Self {
+        Self { columns: vec![None; REGION_AREA] }
+    }
+}
+
+/// Checks the magic and the version in `header`, and returns the lengths of
+/// the compressed columns.
+fn read_header(header: &[u8]) -> Result<Vec<usize>, RegionError> {
+    let (magic, rest) = header.split_at_checked(4).ok_or(RegionError::InvalidHeader)?;
+    if magic != REGION_FILE_MAGIC {
+        return Err(RegionError::InvalidHeader);
+    }
+    let (version_bytes, lengths) = rest.split_first_chunk::<4>().ok_or(RegionError::InvalidHeader)?;
+    let version = u32::from_le_bytes(*version_bytes);
+    if version != REGION_FILE_VERSION {
+        return Err(RegionError::UnsupportedVersion(version));
+    }
+    lengths.as_chunks::<4>().0.iter().map(|length| usize::try_from(u32::from_le_bytes(*length)).ok().ok_or(RegionError::InvalidHeader)).collect()
+}
+
+/// Iterates over the positions of the columns of blocks in a [`ChunkColumn`]
+/// in the order of its biomes.
+fn column_local_positions() -> impl Iterator<Item = UVec2> {
+    (0..SECTION_SIZE.cast_unsigned()).flat_map(|z| (0..SECTION_SIZE.cast_unsigned()).map(move |x| UVec2::new(x, z)))
+}
+
+#[expect(clippy::cast_possible_truncation, reason = "Palettes, block names and packed entries of a column are far fewer than 65536.")]
+const fn to_u16(value: usize) -> u16 {
+    value as u16
+}
+
+fn write_u16(writer: &mut impl Write, value: u16) -> io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+fn write_string(writer: &mut impl Write, string: &str) -> io::Result<()> {
+    write_u16(writer, to_u16(string.len()))?;
+    writer.write_all(string.as_bytes())
+}
+
+fn read_u16(reader: &mut impl Read) -> io::Result<u16> {
+    let mut bytes = [0; 2];
+    reader.read_exact(&mut bytes)?;
+    Ok(u16::from_le_bytes(bytes))
+}
+
+fn read_string(reader: &mut impl Read) -> Result<String, RegionError> {
+    let mut bytes = vec![0; usize::from(read_u16(reader)?)];
+    reader.read_exact(&mut bytes)?;
+    String::from_utf8(bytes).ok().ok_or(RegionError::InvalidColumn("A name is not valid UTF-8."))
+}
+
+#[derive(Debug, Error)]
+pub enum RegionError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    #[error("The region file does not start with a valid header.")]
+    InvalidHeader,
+
+    #[error("The region file has the version {0}, but only version {REGION_FILE_VERSION} is supported.")]
+    UnsupportedVersion(u32),
+
+    #[error("The region file ends before all of its columns.")]
+    Truncated,
+
+    #[error("A saved column is invalid. {0}")]
+    InvalidColumn(&'static str),
+
+    #[error(transparent)]
+    InvalidBlockName(#[from] BlockNameParseError)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::iter;
+    use std::process;
+
+    const STONE: BlockId = BlockId(1);
+    const DIRT: BlockId = BlockId(2);
+
+    fn test_names(block_paths: &[&str], biome_names: &[&str]) -> StorageNames {
+        let block_names = iter::once(BlockName::air())
+            .chain(block_paths.iter().map(|path| BlockName::new(BlockName::DEFAULT_NAMESPACE, path).expect("The name should be valid.")))
+            .collect();
+        StorageNames::from_names(block_names, biome_names.iter().map(|name| (*name).to_owned()).collect())
+    }
+
+    /// Stone and dirt in layers up to Y = 40, and a second biome in one corner.
+    fn test_column() -> ChunkColumn {
+        let mut column = ChunkColumn::default();
+        for y in COLUMN_MIN_Y..40 {
+            for z in 0..SECTION_SIZE {
+                for x in 0..SECTION_SIZE {
+                    column.set_block(IVec3::new(x, y, z), if (x + y + z) % 3 == 0 { DIRT } else { STONE });
+                }
+            }
+        }
+        column.set_biome(UVec2::new(15, 15), BiomeId(1));
+        column
+    }
+
+    fn all_blocks(column: &ChunkColumn) -> Vec<Option<BlockId>> {
+        (COLUMN_MIN_Y..=COLUMN_MAX_Y)
+            .flat_map(|y| (0..SECTION_SIZE).flat_map(move |z| (0..SECTION_SIZE).map(move |x| IVec3::new(x, y, z))))
+            .map(|position| column.get_block(position))
+            .collect()
+    }
+
+    #[test]
+    fn columns_are_loaded_by_name_after_the_ids_change() {
+        let column = test_column();
+        let bytes = encode_column(&column, &test_names(&["stone", "dirt"], &["plains", "desert"])).expect("The column should be encoded.");
+        // Dirt now comes before stone, and a new biome comes before both.
+        let loaded = decode_column(&bytes, &test_names(&["dirt", "stone"], &["forest", "plains", "desert"])).expect("The column should be decoded.");
+        let swapped_blocks: Vec<_> = all_blocks(&column).into_iter()
+            .map(|block| block.map(|id| match id {
+                STONE => DIRT,
+                DIRT => STONE,
+                other => other
+            }))
+            .collect();
+        assert_eq!(all_blocks(&loaded), swapped_blocks);
+        assert_eq!(loaded.biome(UVec2::new(15, 15)), BiomeId(2));
+        assert_eq!(loaded.biome(UVec2::ZERO), BiomeId(1));
+        let without_dirt = decode_column(&bytes, &test_names(&["stone"], &["plains", "desert"])).expect("The column should be decoded.");
+        assert!(all_blocks(&without_dirt).iter().all(|block| *block != Some(DIRT)), "Blocks that no longer exist should be replaced with air.");
+    }
+
+    #[test]
+    fn columns_are_read_back_from_region_files() {
+        let names = test_names(&["stone", "dirt"], &["plains", "desert"]);
+        let column_positions = [IVec2::new(-1, -1), IVec2::new(-32, -5), IVec2::new(-17, -32)];
+        let mut region = RegionFile::default();
+        for (index, column_position) in (0_u8..).zip(column_positions) {
+            let mut column = test_column();
+            column.set_biome(UVec2::ZERO, BiomeId(index % 2));
+            region.set_column(column_position, encode_column(&column, &names).expect("The column should be encoded."));
+        }
+        let bytes = region.to_bytes();
+        assert_eq!(RegionFile::from_bytes(&bytes).expect("The region should be read.").to_bytes(), bytes);
+        let path = env::temp_dir().join(format!("mycraft-region-test-{}.region", process::id()));
+        fs::write(&path, &bytes).expect("The region file should be written.");
+        for (index, column_position) in (0_u8..).zip(column_positions) {
+            assert_eq!(column_to_region_position(column_position), IVec2::NEG_ONE);
+            let column_bytes = RegionFile::read_column(&path, column_position).expect("The region file should be read.").expect("The column should be saved.");
+            let column = decode_column(&column_bytes, &names).expect("The column should be decoded.");
+            assert_eq!(column.biome(UVec2::ZERO), BiomeId(index % 2));
+            assert_eq!(all_blocks(&column), all_blocks(&test_column()));
+        }
+        assert!(RegionFile::read_column(&path, IVec2::new(-2, -1)).expect("The region file should be read.").is_none());
+        drop(fs::remove_file(&path));
+    }
+}
@@ -1,4 +1,14 @@
+pub mod block;
+pub mod camera;
+pub mod chunk_meshing;
+pub mod chunk_streaming;
 pub mod core;
 pub mod egui;
+pub mod lighting;
+pub mod render;
+pub mod screenshot;
+pub mod terrain_generation;
+pub mod voxel_world;
 pub mod wgpu;
 pub mod winit;
+pub mod world_save;
@@ -0,0 +1,17 @@
+use assets_manager::*;
+use bevy_ecs::resource::*;
+use crate::asset::*;
+use crate::block_registry::*;
+
+/// Holds the [`BlockRegistry`] along with the [`BlockDefinitions`] it was
+/// created from, so that it can be updated when they are hot-reloaded. Systems
+/// that depend on the definitions of blocks can use `bevy_ecs` change detection
+/// on this resource to find out when that happens.
+#[derive(Resource)]
+pub struct BlockRegistryResource {
+    pub registry: BlockRegistry,
+    pub definitions: AssetHandle<BlockDefinitions>,
+    /// The [`ReloadId`] of [`Self::definitions`] that [`Self::registry`] is up
+    /// to date with.
+    pub definitions_reload_id: ReloadId
+}
@@ -0,0 +1,75 @@
+use bevy_ecs::resource::*;
+use glam::*;
+use wgpu::*;
+
+/// Owns the per-view uniform that is bound at
+/// [`crate::constants::PER_VIEW_BIND_GROUP`], which currently only contains the
+/// view-projection matrix of the active camera. It is kept up to date by
+/// [`crate::systems::camera::update_camera_view_system`].
+#[derive(Resource)]
+pub struct CameraViewResource {
+    per_view_buffer: Buffer,
+    per_view_bind_group: BindGroup,
+    /// The view-projection matrix last uploaded to [`Self::per_view_buffer`],
+    /// or [`None`] if there was no camera to upload it from.
+    view_projection: Option<Mat4>,
+    /// Whether the active camera uses reverse-Z. See
+    /// [`crate::camera::ProjectionMode::projection_matrix`].
+    reverse_z: bool
+}
+
+impl CameraViewResource {
+    /// * `per_view_bind_group_layout`: See
+    ///   [`crate::resources::render::PipelineCacheResource::per_view_bind_group_layout`].
+    pub fn new(device: &Device, per_view_bind_group_layout: &BindGroupLayout) -> Self {
+        let per_view_buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("per-view-buffer"),
+            size: size_of::<Mat4>() as u64,
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+            mapped_at_creation: false
+        });
+        let per_view_bind_group = device.create_bind_group(&BindGroupDescriptor {
+            label: Some("per-view-bind-group"),
+            layout: per_view_bind_group_layout,
+            entries: &[BindGroupEntry {
+                binding: 0,
+                resource: per_view_buffer.as_entire_binding()
+            }]
+        });
+        Self {
+            per_view_buffer,
+            per_view_bind_group,
+            view_projection: None,
+            reverse_z: false
+        }
+    }
+
+    /// Uploads `view_projection` to the per-view uniform buffer. Nothing is
+    /// uploaded if it is the same as the last one.
+    pub fn update(&mut self, queue: &Queue, view_projection: Mat4, reverse_z: bool) {
+        self.reverse_z = reverse_z;
+        if self.view_projection == Some(view_projection) {
+            return;
+        }
+        queue.write_buffer(&self.per_view_buffer, 0, bytemuck::bytes_of(&view_projection));
+        self.view_projection = Some(view_projection);
+    }
+
+    /// Marks that there is no active camera, so nothing should be rendered.
+    pub const fn clear(&mut self) {
+        self.view_projection = None;
+    }
+
+    /// Returns [`None`] if there is no active camera.
+    pub const fn view_projection(&self) -> Option<Mat4> {
+        self.view_projection
+    }
+
+    pub const fn per_view_bind_group(&self) -> &BindGroup {
+        &self.per_view_bind_group
+    }
+
+    pub const fn reverse_z(&self) -> bool {
+        self.reverse_z
+    }
+}
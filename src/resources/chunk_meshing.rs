@@ -0,0 +1,38 @@
+use bevy_ecs::entity::*;
+use bevy_ecs::resource::*;
+use glam::*;
+use std::collections::*;
+use std::sync::*;
+use crate::asset::*;
+use crate::background_jobs::*;
+use crate::chunk_mesher::*;
+use crate::material::*;
+use crate::mesh::*;
+
+/// The state shared by the systems in [`crate::systems::chunk_meshing`].
+#[derive(Resource)]
+pub struct ChunkMeshingResource {
+    /// Shared with the meshing jobs, which is why it is in an [`Arc`].
+    pub meshing_table: Arc<BlockMeshingTable>,
+    /// A [`crate::materials::BlockMaterial`] that samples the atlas that
+    /// [`Self::meshing_table`] was created from. It is shared by every chunk.
+    pub material: AssetHandle<dyn Material + Send + Sync>,
+    /// The entities that render the meshes of sections, keyed by their section
+    /// positions. Sections that have no visible faces do not
+    /// have entities.
+    pub section_entities: HashMap<IVec3, Entity>,
+    /// Sections that have been marked dirty, but are waiting for
+    /// [`Self::meshing_jobs`] to have a free slot, or for the job that is
+    /// already meshing them to be collected.
+    pub pending_sections: HashSet<IVec3>,
+    /// Keyed by section position.
+    pub meshing_jobs: BackgroundJobs<IVec3, Result<Mesh, MeshCreationError>>
+}
+
+impl ChunkMeshingResource {
+    /// Returns `true` if some sections are waiting to be meshed or are being
+    /// meshed.
+    pub fn is_meshing(&self) -> bool {
+        !self.pending_sections.is_empty() || !self.meshing_jobs.is_empty()
+    }
+}
@@ -0,0 +1,19 @@
+use bevy_ecs::resource::*;
+use glam::*;
+use crate::chunk_streaming::*;
+
+/// The state of [`crate::systems::chunk_streaming::stream_columns_system`].
+#[derive(Resource)]
+pub struct ChunkStreamingResource {
+    /// Changing these takes effect the next time the system runs.
+    pub distances: StreamingDistances,
+    /// The column of the chunk loader and the distances that the loaded
+    /// columns were last updated for, or [`None`] if they have never been.
+    pub streamed: Option<(IVec2, StreamingDistances)>
+}
+
+impl ChunkStreamingResource {
+    pub const fn new(distances: StreamingDistances) -> Self {
+        Self { distances, streamed: None }
+    }
+}
@@ -0,0 +1,10 @@
+use bevy_ecs::resource::*;
+use crate::lighting::*;
+
+/// The state used by the systems in [`crate::systems::lighting`].
+#[derive(Resource)]
+pub struct LightingResource {
+    /// Created from the [`crate::block_registry::BlockRegistry`], and
+    /// recreated when it changes.
+    pub lighting_table: BlockLightingTable
+}
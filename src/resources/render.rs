@@ -0,0 +1,493 @@
+use assets_manager::*;
+use bevy_ecs::resource::*;
+use futures::executor::*;
+use glam::*;
+use log::*;
+use std::any::*;
+use std::collections::*;
+use std::num::*;
+use strum::*;
+use thiserror::*;
+use wgpu::*;
+use wgpu::Error as WgpuError;
+use crate::asset::*;
+use crate::constants::*;
+use crate::gpu_mesh::*;
+use crate::material::*;
+use crate::mesh::*;
+use crate::shader::*;
+
+/// The size of the per-object uniform, which only contains the model matrix.
+const PER_OBJECT_UNIFORM_SIZE: u64 = size_of::<Mat4>() as u64;
+
+/// Renders [`crate::components::core::MeshRendererComponent`]s using a simple
+/// forward renderer. The [`BindGroup`] at [`PER_OBJECT_BIND_GROUP`] is owned by
+/// this [`Resource`], the one at [`PER_VIEW_BIND_GROUP`] by
+/// [`crate::resources::camera::CameraViewResource`], while the one at
+/// [`PER_MATERIAL_BIND_GROUP`] is created by each [`Material`]. Their layouts
+/// are described in [`PipelineCacheResource`].
+#[derive(Resource)]
+pub struct ForwardRendererResource {
+    /// Stores the model matrices of all objects drawn this frame, each
+    /// [`Self::per_object_stride`] bytes apart so that they can be selected
+    /// using dynamic offsets.
+    per_object_buffer: Buffer,
+    per_object_bind_group: BindGroup,
+    per_object_stride: u64,
+    /// The [`BindGroup`]s returned by [`Material::bind_group`], keyed by
+    /// [`AssetHandle::key`] of the [`Material`]s.
+    material_bind_groups: HashMap<AssetHandleKey, BindGroup>
+}
+
+impl ForwardRendererResource {
+    pub fn new(device: &Device, pipeline_cache: &PipelineCacheResource) -> Self {
+        let per_object_stride = PER_OBJECT_UNIFORM_SIZE.next_multiple_of(u64::from(device.limits().min_uniform_buffer_offset_alignment));
+        let (per_object_buffer, per_object_bind_group) = create_per_object_buffer_and_bind_group(device, pipeline_cache.per_object_bind_group_layout(), per_object_stride);
+        Self {
+            per_object_buffer,
+            per_object_bind_group,
+            per_object_stride,
+            material_bind_groups: HashMap::new()
+        }
+    }
+
+    /// Makes sure that the [`RenderPipeline`] and the [`BindGroup`] of
+    /// `material` are ready to be used, and returns the keys to use in
+    /// [`DrawCommand`]. Returns [`None`] if the shaders of `material` cannot be
+    /// loaded.
+    pub fn prepare_material(&mut self, device: &Device, asset_cache: &AssetCache, pipeline_cache: &mut PipelineCacheResource, pipeline_formats: &PipelineFormats, material: &AssetHandle<dyn Material + Send + Sync>) -> Option<PreparedMaterial> {
+        let material_key = material.key();
+        let material_guard = material.read();
+        let pipeline_key_result = pipeline_cache.prepare(device, asset_cache, pipeline_formats, &*material_guard);
+        self.material_bind_groups.entry(material_key.clone()).or_insert_with(|| {
+            let layout = material_guard.bind_group_layout(device);
+            material_guard.bind_group(device, &layout)
+        });
+        drop(material_guard);
+        match pipeline_key_result {
+            Ok(pipeline_key) => Some(PreparedMaterial { material_key, pipeline_key }),
+            Err(err) => {
+                error!("Could not load the shaders of a Material. {err:#?}");
+                None
+            }
+        }
+    }
+
+    /// Removes the [`BindGroup`]s of the [`Material`]s that are not in
+    /// `used_material_keys`.
+    pub fn retain_material_bind_groups(&mut self, used_material_keys: &HashSet<AssetHandleKey>) {
+        self.material_bind_groups.retain(|key, _| used_material_keys.contains(key));
+    }
+
+    /// Records the draw calls of `draw_commands` into a new render pass that
+    /// renders to `color_target` and submits it. The [`PreparedMaterial`]s in
+    /// `draw_commands` must have been returned by [`Self::prepare_material`].
+    /// The GPU buffers of the [`Mesh`]es are taken from `gpu_mesh_cache`.
+    ///
+    /// * `per_view_bind_group`: See
+    ///   [`crate::resources::camera::CameraViewResource::per_view_bind_group`].
+    #[expect(clippy::too_many_arguments, reason = "Rendering needs every resource involved in it.")]
+    pub fn render(&mut self, device: &Device, queue: &Queue, pipeline_cache: &PipelineCacheResource, gpu_mesh_cache: &mut GpuMeshCacheResource, per_view_bind_group: &BindGroup, render_targets: &RenderTargets<'_>, draw_commands: &[DrawCommand]) {
+        self.write_per_object_uniforms(device, queue, pipeline_cache.per_object_bind_group_layout(), draw_commands);
+        let mut command_encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("forward-renderer-command-encoder")
+        });
+        let mut render_pass = command_encoder.begin_render_pass(&RenderPassDescriptor {
+            label: Some("forward-renderer-render-pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: render_targets.color,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Load,
+                    store: StoreOp::Store
+                }
+            })],
+            // The depth is cleared here instead of in the main render pass
+            // because the clear value depends on whether the camera uses
+            // reverse-Z.
+            depth_stencil_attachment: Some(RenderPassDepthStencilAttachment {
+                view: render_targets.depth,
+                depth_ops: Some(Operations {
+                    load: LoadOp::Clear(render_targets.depth_clear_value),
+                    store: StoreOp::Store
+                }),
+                stencil_ops: None
+            }),
+            ..Default::default()
+        });
+        render_pass.set_bind_group(PER_VIEW_BIND_GROUP, per_view_bind_group, &[]);
+        for (object_index, draw_command) in draw_commands.iter().enumerate() {
+            let (Some(cached_pipeline), Some(material_bind_group)) = (
+                pipeline_cache.get(&draw_command.material.pipeline_key),
+                self.material_bind_groups.get(&draw_command.material.material_key)
+            ) else {
+                continue;
+            };
+            let Some(gpu_mesh) = gpu_mesh_cache.get_or_create(device, &draw_command.mesh, &cached_pipeline.vertex_layout) else {
+                warn!("A Mesh does not have all the vertex attributes its Material requires in the formats it requires, so it is not rendered. Required vertex attributes: {:#?}", cached_pipeline.vertex_layout.vertex_buffer_key());
+                continue;
+            };
+            #[expect(clippy::cast_possible_truncation, reason = "The per-object buffer is never large enough for its offsets to overflow a u32.")]
+            let per_object_offset = (object_index as u64 * self.per_object_stride) as u32;
+            render_pass.set_pipeline(&cached_pipeline.pipeline);
+            render_pass.set_bind_group(PER_MATERIAL_BIND_GROUP, material_bind_group, &[]);
+            render_pass.set_bind_group(PER_OBJECT_BIND_GROUP, &self.per_object_bind_group, &[per_object_offset]);
+            gpu_mesh.draw(&mut render_pass);
+        }
+        drop(render_pass); // Drops render_pass so command_encoder can be moved below.
+        queue.submit([command_encoder.finish()]);
+    }
+
+    fn write_per_object_uniforms(&mut self, device: &Device, queue: &Queue, per_object_bind_group_layout: &BindGroupLayout, draw_commands: &[DrawCommand]) {
+        let required_size = (draw_commands.len() as u64).max(1) * self.per_object_stride;
+        if required_size > self.per_object_buffer.size() {
+            (self.per_object_buffer, self.per_object_bind_group) = create_per_object_buffer_and_bind_group(device, per_object_bind_group_layout, required_size.next_power_of_two());
+        }
+        #[expect(clippy::cast_possible_truncation, reason = "The stride is at most the alignment limit of the device, which fits in a usize.")]
+        let per_object_stride = self.per_object_stride as usize;
+        let mut per_object_data = vec![0; draw_commands.len() * per_object_stride];
+        for (per_object_chunk, draw_command) in per_object_data.chunks_exact_mut(per_object_stride).zip(draw_commands) {
+            per_object_chunk[..size_of::<Mat4>()].copy_from_slice(bytemuck::bytes_of(&draw_command.model));
+        }
+        queue.write_buffer(&self.per_object_buffer, 0, &per_object_data);
+    }
+}
+
+/// Caches the GPU buffers created from [`Mesh`]es so that each [`Mesh`] is
+/// only uploaded once no matter how many entities use it. Entries are shared by
+/// [`AssetHandle::key`], so [`AssetHandle::Dynamic`]s are shared by pointer
+/// identity and [`AssetHandle::Static`]s by their IDs in the [`AssetCache`].
+///
+/// A [`Mesh`] has one index buffer, but one vertex buffer per
+/// [`InterleavedVertexLayout`] that it is rendered with, because different
+/// [`Material`]s may need different vertex attributes.
+#[derive(Resource, Default)]
+pub struct GpuMeshCacheResource {
+    entries: HashMap<AssetHandleKey, GpuMeshCacheEntry>
+}
+
+impl GpuMeshCacheResource {
+    /// Returns the GPU buffers of `mesh` interleaved according to `layout`,
+    /// creating them if they don't exist yet. If `mesh` has been hot-reloaded
+    /// since its buffers were created, they are recreated. Returns [`None`] if
+    /// `mesh` lacks any of the vertex attributes in `layout`, or has custom
+    /// vertex attributes in other formats.
+    pub fn get_or_create(&mut self, device: &Device, mesh: &AssetHandle<Mesh>, layout: &InterleavedVertexLayout) -> Option<GpuMesh<'_>> {
+        let entry = match self.entries.entry(mesh.key()) {
+            hash_map::Entry::Occupied(occupied_entry) => {
+                let entry = occupied_entry.into_mut();
+                // If the source is dead, the key of a dynamic asset is being
+                // reused by another asset that happens to be allocated at the
+                // same address.
+                if !entry.source.is_alive() || entry.reload_id != mesh.last_reload_id() {
+                    debug!("Recreating the GPU buffers of a stale Mesh.");
+                    *entry = GpuMeshCacheEntry::new(device, mesh);
+                }
+                entry
+            }
+            hash_map::Entry::Vacant(vacant_entry) => vacant_entry.insert(GpuMeshCacheEntry::new(device, mesh))
+        };
+        let GpuMeshCacheEntry { vertex_buffers, index_buffer, index_count, .. } = entry;
+        let vertex_buffer = vertex_buffers
+            .entry(layout.vertex_buffer_key())
+            .or_insert_with(|| create_interleaved_vertex_buffer(device, &mesh.read(), layout))
+            .as_ref()?;
+        Some(GpuMesh { vertex_buffer, index_buffer: index_buffer.as_ref(), index_count: *index_count })
+    }
+
+    /// Removes the entries whose [`Mesh`]es have been dropped.
+    pub fn evict_unused(&mut self) {
+        self.entries.retain(|_, entry| entry.source.is_alive());
+    }
+}
+
+struct GpuMeshCacheEntry {
+    source: WeakAssetHandle<Mesh>,
+    reload_id: ReloadId,
+    /// [`None`] if [`Mesh::indices`] is implied.
+    index_buffer: Option<GpuIndexBuffer>,
+    index_count: u32,
+    /// Keyed by the [`InterleavedVertexLayout::vertex_buffer_key`] of the
+    /// layout used to create the vertex buffer. A value is [`None`] if the
+    /// [`Mesh`] lacks any of those vertex attributes in those formats, so that
+    /// it isn't checked again every frame.
+    vertex_buffers: HashMap<VertexBufferKey, Option<Buffer>>
+}
+
+impl GpuMeshCacheEntry {
+    fn new(device: &Device, mesh: &AssetHandle<Mesh>) -> Self {
+        let mesh_guard = mesh.read();
+        #[expect(clippy::cast_possible_truncation, reason = "Meshes with more than u32::MAX vertices cannot be rendered anyway.")]
+        let index_count = mesh_guard.index_count() as u32;
+        let index_buffer = GpuIndexBuffer::new(device, &mesh_guard);
+        drop(mesh_guard);
+        Self {
+            source: mesh.downgrade(),
+            reload_id: mesh.last_reload_id(),
+            index_buffer,
+            index_count,
+            vertex_buffers: HashMap::new()
+        }
+    }
+}
+
+/// The keys returned by [`ForwardRendererResource::prepare_material`].
+#[derive(Clone)]
+pub struct PreparedMaterial {
+    material_key: AssetHandleKey,
+    pipeline_key: PipelineKey
+}
+
+/// A single object to draw in [`ForwardRendererResource::render`].
+pub struct DrawCommand {
+    pub mesh: AssetHandle<Mesh>,
+    pub material: PreparedMaterial,
+    /// The model matrix of the object.
+    pub model: Mat4
+}
+
+/// Caches the [`RenderPipeline`]s created from [`Material`]s. A
+/// [`RenderPipeline`] is created the first time it is needed, and is shared by
+/// all [`Material`]s that would create an identical one, as described by
+/// [`PipelineKey`].
+///
+/// When a [`WeslShader`] used by a [`RenderPipeline`] is hot-reloaded, the
+/// [`RenderPipeline`] is recreated. If that fails, for example because the
+/// reloaded shader fails validation, the last [`RenderPipeline`] that was
+/// created successfully is kept and an error is logged.
+///
+/// This also owns the [`BindGroupLayout`]s that every [`RenderPipeline`] uses
+/// at [`PER_VIEW_BIND_GROUP`] and [`PER_OBJECT_BIND_GROUP`]. The per-view bind
+/// group contains a single uniform buffer at `@binding(0)` with the
+/// view-projection matrix. The per-object bind group contains a single uniform
+/// buffer at `@binding(0)` with the model matrix, which is selected using a
+/// dynamic offset.
+#[derive(Resource)]
+pub struct PipelineCacheResource {
+    per_view_bind_group_layout: BindGroupLayout,
+    per_object_bind_group_layout: BindGroupLayout,
+    entries: HashMap<PipelineKey, CachedPipelineEntry>
+}
+
+impl PipelineCacheResource {
+    pub fn new(device: &Device) -> Self {
+        Self {
+            per_view_bind_group_layout: create_uniform_bind_group_layout(device, "per-view-bind-group-layout", false),
+            per_object_bind_group_layout: create_uniform_bind_group_layout(device, "per-object-bind-group-layout", true),
+            entries: HashMap::new()
+        }
+    }
+
+    /// Makes sure that the [`RenderPipeline`] of `material` has been created
+    /// and is up to date with its shaders, and returns the key to pass to
+    /// [`Self::get`].
+    pub fn prepare(&mut self, device: &Device, asset_cache: &AssetCache, pipeline_formats: &PipelineFormats, material: &dyn Material) -> Result<PipelineKey, ShaderLoadingError> {
+        let vertex_shader = material.vertex_shader(asset_cache)?;
+        let fragment_shader = material.fragment_shader(asset_cache)?;
+        let material_any: &dyn Any = material;
+        let key = PipelineKey {
+            material_type_id: material_any.type_id(),
+            vertex_shader_key: vertex_shader.key(),
+            vertex_shader_entry_point: material.vertex_shader_entry_point(),
+            fragment_shader_key: fragment_shader.key(),
+            fragment_shader_entry_point: material.fragment_shader_entry_point(),
+            attribute_to_shader_location_mapping: material.attribute_to_shader_location_mapping(),
+            custom_attribute_formats: material.custom_attribute_formats(),
+            formats: pipeline_formats.clone()
+        };
+        let shader_reload_ids = [vertex_shader.last_reload_id(), fragment_shader.last_reload_id()];
+        if self.entries.get(&key).is_some_and(|entry| entry.shader_reload_ids == shader_reload_ids) {
+            return Ok(key);
+        }
+        let pipeline_result = self.create_pipeline(device, &key, material, &vertex_shader, &fragment_shader);
+        match (self.entries.get_mut(&key), pipeline_result) {
+            (Some(entry), Ok(cached_pipeline)) => {
+                info!("Recreated a RenderPipeline because its shaders have been hot-reloaded.");
+                entry.cached_pipeline = Some(cached_pipeline);
+                entry.shader_reload_ids = shader_reload_ids;
+            }
+            (Some(entry), Err(err)) => {
+                error!("Could not recreate a RenderPipeline after its shaders have been hot-reloaded. The last working RenderPipeline will be kept. {err}");
+                entry.shader_reload_ids = shader_reload_ids;
+            }
+            (None, new_pipeline_result) => {
+                let cached_pipeline = new_pipeline_result
+                    .inspect_err(|err| error!("Could not create a RenderPipeline. {err}"))
+                    .ok();
+                self.entries.insert(key.clone(), CachedPipelineEntry { cached_pipeline, shader_reload_ids });
+            }
+        }
+        Ok(key)
+    }
+
+    /// Returns the [`CachedPipeline`] of `key`, or [`None`] if it has never
+    /// been created successfully.
+    pub fn get(&self, key: &PipelineKey) -> Option<&CachedPipeline> {
+        self.entries.get(key)?.cached_pipeline.as_ref()
+    }
+
+    /// The [`BindGroupLayout`] at [`PER_VIEW_BIND_GROUP`].
+    pub const fn per_view_bind_group_layout(&self) -> &BindGroupLayout {
+        &self.per_view_bind_group_layout
+    }
+
+    /// The [`BindGroupLayout`] at [`PER_OBJECT_BIND_GROUP`].
+    pub const fn per_object_bind_group_layout(&self) -> &BindGroupLayout {
+        &self.per_object_bind_group_layout
+    }
+
+    /// Creates a [`RenderPipeline`] inside an error scope so that validation
+    /// errors are returned instead of causing a panic.
+    fn create_pipeline(&self, device: &Device, key: &PipelineKey, material: &dyn Material, vertex_shader: &AssetHandle<WeslShader>, fragment_shader: &AssetHandle<WeslShader>) -> Result<CachedPipeline, PipelineCreationError> {
+        device.push_error_scope(ErrorFilter::Validation);
+        let vertex_shader_module = device.create_shader_module(ShaderModuleDescriptor {
+            label: Some("material-vertex-shader"),
+            source: ShaderSource::Wgsl(vertex_shader.read().to_string().into())
+        });
+        let fragment_shader_module = device.create_shader_module(ShaderModuleDescriptor {
+            label: Some("material-fragment-shader"),
+            source: ShaderSource::Wgsl(fragment_shader.read().to_string().into())
+        });
+        let per_material_bind_group_layout = material.bind_group_layout(device);
+        let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some("material-pipeline-layout"),
+            // Ordered by PER_VIEW_BIND_GROUP, PER_MATERIAL_BIND_GROUP, and
+            // PER_OBJECT_BIND_GROUP.
+            bind_group_layouts: &[&self.per_view_bind_group_layout, &per_material_bind_group_layout, &self.per_object_bind_group_layout],
+            push_constant_ranges: &[]
+        });
+        let vertex_layout = InterleavedVertexLayout::from_shader_location_mapping(key.attribute_to_shader_location_mapping, key.custom_attribute_formats);
+        let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+            label: Some("material-pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: VertexState {
+                module: &vertex_shader_module,
+                entry_point: key.vertex_shader_entry_point.as_deref(),
+                compilation_options: PipelineCompilationOptions::default(),
+                buffers: &[vertex_layout.vertex_buffer_layout()]
+            },
+            primitive: PrimitiveState {
+                topology: PrimitiveTopology::TriangleList,
+                front_face: FrontFace::Ccw,
+                cull_mode: Some(Face::Back),
+                ..Default::default()
+            },
+            depth_stencil: Some(DepthStencilState {
+                format: key.formats.depth_format,
+                depth_write_enabled: true,
+                depth_compare: key.formats.depth_compare,
+                stencil: StencilState::default(),
+                bias: DepthBiasState::default()
+            }),
+            multisample: MultisampleState::default(),
+            fragment: Some(FragmentState {
+                module: &fragment_shader_module,
+                entry_point: key.fragment_shader_entry_point.as_deref(),
+                compilation_options: PipelineCompilationOptions::default(),
+                targets: &[Some(ColorTargetState {
+                    format: key.formats.color_format,
+                    blend: Some(BlendState::ALPHA_BLENDING),
+                    write_mask: ColorWrites::ALL
+                })]
+            }),
+            multiview: None,
+            cache: None
+        });
+        if let Some(err) = block_on(device.pop_error_scope()) {
+            return Err(PipelineCreationError::Validation(err));
+        }
+        Ok(CachedPipeline { pipeline, vertex_layout })
+    }
+}
+
+/// Identifies a [`RenderPipeline`] in [`PipelineCacheResource`]. Two
+/// [`Material`]s that produce the same key share the same [`RenderPipeline`].
+/// [`Material`]s of the same type are assumed to always return the same
+/// [`Material::bind_group_layout`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PipelineKey {
+    material_type_id: TypeId,
+    vertex_shader_key: AssetHandleKey,
+    vertex_shader_entry_point: Option<String>,
+    fragment_shader_key: AssetHandleKey,
+    fragment_shader_entry_point: Option<String>,
+    attribute_to_shader_location_mapping: [Option<u32>; VertexAttributeKind::COUNT],
+    custom_attribute_formats: [VertexFormat; CUSTOM_VERTEX_ATTRIBUTE_COUNT],
+    formats: PipelineFormats
+}
+
+/// The formats of the attachments that a [`RenderPipeline`] renders to, and
+/// how it tests against the depth attachment.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PipelineFormats {
+    pub color_format: TextureFormat,
+    pub depth_format: TextureFormat,
+    /// See [`crate::camera::depth_compare_function`].
+    pub depth_compare: CompareFunction
+}
+
+/// The attachments that [`ForwardRendererResource::render`] renders to.
+pub struct RenderTargets<'a> {
+    pub color: &'a TextureView,
+    pub depth: &'a TextureView,
+    /// See [`crate::camera::depth_clear_value`].
+    pub depth_clear_value: f32
+}
+
+/// A [`RenderPipeline`] and the layout of the vertex buffer it expects.
+pub struct CachedPipeline {
+    pub pipeline: RenderPipeline,
+    pub vertex_layout: InterleavedVertexLayout
+}
+
+struct CachedPipelineEntry {
+    /// [`None`] if the [`RenderPipeline`] has never been created successfully.
+    cached_pipeline: Option<CachedPipeline>,
+    /// The [`ReloadId`]s of the vertex and fragment shaders when
+    /// [`Self::cached_pipeline`] was last (re)created.
+    shader_reload_ids: [ReloadId; 2]
+}
+
+#[derive(Debug, Error)]
+pub enum PipelineCreationError {
+    #[error(transparent)]
+    Validation(#[from] WgpuError)
+}
+
+fn create_uniform_bind_group_layout(device: &Device, label: &str, has_dynamic_offset: bool) -> BindGroupLayout {
+    device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+        label: Some(label),
+        entries: &[BindGroupLayoutEntry {
+            binding: 0,
+            visibility: ShaderStages::VERTEX_FRAGMENT,
+            ty: BindingType::Buffer {
+                ty: BufferBindingType::Uniform,
+                has_dynamic_offset,
+                min_binding_size: NonZeroU64::new(size_of::<Mat4>() as u64)
+            },
+            count: None
+        }]
+    })
+}
+
+fn create_per_object_buffer_and_bind_group(device: &Device, layout: &BindGroupLayout, size: u64) -> (Buffer, BindGroup) {
+    let buffer = device.create_buffer(&BufferDescriptor {
+        label: Some("per-object-buffer"),
+        size,
+        usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+        mapped_at_creation: false
+    });
+    let bind_group = device.create_bind_group(&BindGroupDescriptor {
+        label: Some("per-object-bind-group"),
+        layout,
+        entries: &[BindGroupEntry {
+            binding: 0,
+            resource: BindingResource::Buffer(BufferBinding {
+                buffer: &buffer,
+                offset: 0,
+                size: NonZeroU64::new(PER_OBJECT_UNIFORM_SIZE)
+            })
+        }]
+    });
+    (buffer, bind_group)
+}
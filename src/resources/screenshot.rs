@@ -0,0 +1,50 @@
+use bevy_ecs::resource::*;
+use image::*;
+use crate::screenshot::*;
+
+/// Keeps track of screenshots that have been requested but not saved yet. See
+/// [`crate::systems::screenshot`].
+#[derive(Resource, Default)]
+pub struct ScreenshotResource {
+    is_requested: bool,
+    /// Screenshots whose pixels are being copied back from the GPU.
+    pending_readbacks: Vec<TextureReadback>
+}
+
+impl ScreenshotResource {
+    /// Requests that the next frame be saved to [`SCREENSHOTS_PATH`] after it
+    /// has been rendered.
+    pub const fn request(&mut self) {
+        self.is_requested = true;
+    }
+
+    /// Returns whether a screenshot has been requested since the last call,
+    /// and resets the request.
+    pub const fn take_request(&mut self) -> bool {
+        let is_requested = self.is_requested;
+        self.is_requested = false;
+        is_requested
+    }
+
+    pub fn push_pending_readback(&mut self, readback: TextureReadback) {
+        self.pending_readbacks.push(readback);
+    }
+
+    /// Removes and returns the screenshots whose pixels have finished being
+    /// copied back from the GPU. This never blocks.
+    pub fn take_finished_readbacks(&mut self) -> Vec<Result<RgbaImage, ScreenshotError>> {
+        let mut finished_readbacks = Vec::new();
+        self.pending_readbacks.retain_mut(|readback| {
+            let Some(read_result) = readback.try_read() else {
+                return true;
+            };
+            finished_readbacks.push(read_result);
+            false
+        });
+        finished_readbacks
+    }
+
+    pub const fn has_pending_readbacks(&self) -> bool {
+        !self.pending_readbacks.is_empty()
+    }
+}
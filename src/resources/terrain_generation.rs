@@ -0,0 +1,39 @@
+use bevy_ecs::resource::*;
+use glam::*;
+use std::collections::*;
+use std::sync::*;
+use crate::background_jobs::*;
+use crate::terrain_generator::*;
+use crate::voxel_world::*;
+
+/// The state shared by the systems in [`crate::systems::terrain_generation`].
+#[derive(Resource)]
+pub struct TerrainGenerationResource {
+    /// Shared with the generation jobs, which is why it is in an [`Arc`].
+    pub generator: Arc<TerrainPipeline>,
+    /// Columns that should be generated and inserted into the
+    /// [`crate::voxel_world::VoxelWorld`], but are waiting for
+    /// [`Self::generation_jobs`] to have a free slot. They are generated from
+    /// the front.
+    pub requested_columns: VecDeque<IVec2>,
+    /// Keyed by column position.
+    pub generation_jobs: BackgroundJobs<IVec2, ProvidedColumn>,
+    /// The blocks that features of generated columns wrote into columns that
+    /// were not loaded.
+    pub pending_writes: PendingBlockWrites
+}
+
+impl TerrainGenerationResource {
+    /// Returns `true` if some columns are waiting to be generated or are being
+    /// generated.
+    pub fn is_generating(&self) -> bool {
+        !self.requested_columns.is_empty() || !self.generation_jobs.is_empty()
+    }
+}
+
+/// A column made by a generation job, which loads the column if it has been
+/// saved and generates it otherwise.
+pub enum ProvidedColumn {
+    Loaded(ChunkColumn),
+    Generated(GeneratedColumn)
+}
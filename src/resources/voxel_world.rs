@@ -0,0 +1,11 @@
+use bevy_ecs::resource::*;
+use crate::chunk_mesher::*;
+use crate::voxel_world::*;
+
+#[derive(Resource, Default)]
+pub struct VoxelWorldResource {
+    pub voxel_world: VoxelWorld,
+    /// How the sections of [`Self::voxel_world`] are meshed. Changing it only
+    /// affects sections that are meshed afterwards.
+    pub meshing_mode: MeshingMode
+}
@@ -1,27 +1,174 @@
 use bevy_ecs::resource::*;
 use wgpu::*;
+use crate::constants::*;
 
 #[derive(Resource)]
 pub struct WgpuResource {
-    /// This has to be dropped before
-    /// [`crate::resources::winit::WinitResource::window`] so that it is dropped
-    /// before it in order to not cause a segfault. See
-    /// <https://github.com/gfx-rs/wgpu/pull/1792>.
-    /// 
-    /// The lifetime parameter of [`Surface`] is the lifetime of the
-    /// [`winit::window::Window`] used to create it, and since we wrapped the
-    /// [`crate::resources::winit::WinitResource::window`] in an
-    /// [`std::sync::Arc`], it can be `'static`. It has to be `'static` anyway,
-    /// because a [`Resource`] must be `'static`.
-    pub surface: Surface<'static>,
-    pub surface_config: SurfaceConfiguration,
+    pub render_target: RenderTarget,
+    /// Always has the same size as [`Self::render_target`]. Render systems
+    /// share it so that everything rendered to the render target is
+    /// depth-tested against everything else.
+    pub depth_texture: DepthTexture,
     pub device: Device,
     pub command_queue: Queue
 }
 
+impl WgpuResource {
+    /// Resizes [`Self::render_target`] and [`Self::depth_texture`] together.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.render_target.resize(&self.device, width, height);
+        self.depth_texture.resize(&self.device, width, height);
+    }
+}
+
+/// What the frames are rendered into. Render systems should only use the
+/// methods of this `enum` and [`WgpuFrameResource::output_texture`] so that
+/// they work the same whether a window exists or not.
+pub enum RenderTarget {
+    /// Frames are presented to a window.
+    Surface {
+        /// This has to be dropped before
+        /// [`crate::resources::winit::WinitResource::window`] so that it is
+        /// dropped before it in order to not cause a segfault. See
+        /// <https://github.com/gfx-rs/wgpu/pull/1792>.
+        ///
+        /// The lifetime parameter of [`Surface`] is the lifetime of the
+        /// [`winit::window::Window`] used to create it, and since we wrapped
+        /// the [`crate::resources::winit::WinitResource::window`] in an
+        /// [`std::sync::Arc`], it can be `'static`. It has to be `'static`
+        /// anyway, because a [`Resource`] must be `'static`.
+        surface: Surface<'static>,
+        surface_config: SurfaceConfiguration
+    },
+    /// Frames are rendered into a [`Texture`] that is never presented. Used
+    /// when running headlessly.
+    Offscreen(Texture)
+}
+
+impl RenderTarget {
+    /// Creates an [`RenderTarget::Offscreen`] whose format is
+    /// [`OFFSCREEN_TEXTURE_FORMAT`].
+    pub fn new_offscreen(device: &Device, width: u32, height: u32) -> Self {
+        Self::Offscreen(device.create_texture(&TextureDescriptor {
+            label: Some("offscreen-texture"),
+            size: Extent3d {
+                width: width.max(1),
+                height: height.max(1),
+                depth_or_array_layers: 1
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: OFFSCREEN_TEXTURE_FORMAT,
+            // COPY_SRC is needed to take screenshots.
+            usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC,
+            view_formats: &[]
+        }))
+    }
+
+    pub fn format(&self) -> TextureFormat {
+        match self {
+            Self::Surface { surface_config, .. } => surface_config.format,
+            Self::Offscreen(texture) => texture.format()
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        match self {
+            Self::Surface { surface_config, .. } => surface_config.width,
+            Self::Offscreen(texture) => texture.width()
+        }
+    }
+
+    pub fn height(&self) -> u32 {
+        match self {
+            Self::Surface { surface_config, .. } => surface_config.height,
+            Self::Offscreen(texture) => texture.height()
+        }
+    }
+
+    /// Reconfigures the [`Surface`] or recreates the offscreen [`Texture`].
+    pub fn resize(&mut self, device: &Device, width: u32, height: u32) {
+        match self {
+            Self::Surface { surface, surface_config } => {
+                surface_config.width = width;
+                surface_config.height = height;
+                surface.configure(device, surface_config);
+            }
+            Self::Offscreen(_) => {
+                *self = Self::new_offscreen(device, width, height);
+            }
+        }
+    }
+}
+
+/// The depth attachment that is used together with [`RenderTarget`].
+pub struct DepthTexture {
+    texture: Texture,
+    view: TextureView
+}
+
+impl DepthTexture {
+    /// * `format`: Must be a depth format, such as
+    ///   [`crate::constants::DEPTH_TEXTURE_FORMAT`].
+    pub fn new(device: &Device, format: TextureFormat, width: u32, height: u32) -> Self {
+        let texture = device.create_texture(&TextureDescriptor {
+            label: Some("depth-texture"),
+            // A Texture cannot have a size of 0, which is what the window
+            // reports when it is minimized.
+            size: Extent3d {
+                width: width.max(1),
+                height: height.max(1),
+                depth_or_array_layers: 1
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format,
+            usage: TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[]
+        });
+        let view = texture.create_view(&TextureViewDescriptor {
+            label: Some("depth-texture-view"),
+            ..Default::default()
+        });
+        Self { texture, view }
+    }
+
+    /// Recreates the [`Texture`] with a new size, keeping its format.
+    pub fn resize(&mut self, device: &Device, width: u32, height: u32) {
+        *self = Self::new(device, self.format(), width, height);
+    }
+
+    pub fn format(&self) -> TextureFormat {
+        self.texture.format()
+    }
+
+    pub const fn view(&self) -> &TextureView {
+        &self.view
+    }
+}
+
 /// These resources need to be recreated per frame, so it's easier to put them
 /// in a separate [`Resource`].
 #[derive(Resource)]
 pub struct WgpuFrameResource {
-    pub output_surface_texture: SurfaceTexture
+    pub output_texture: FrameOutputTexture
+}
+
+/// The [`Texture`] to render the current frame into, acquired from
+/// [`WgpuResource::render_target`].
+pub enum FrameOutputTexture {
+    /// Has to be presented after rendering.
+    Surface(SurfaceTexture),
+    Offscreen(Texture)
+}
+
+impl FrameOutputTexture {
+    pub const fn texture(&self) -> &Texture {
+        match self {
+            Self::Surface(surface_texture) => &surface_texture.texture,
+            Self::Offscreen(texture) => texture
+        }
+    }
 }
@@ -4,6 +4,6 @@ use winit::window::*;
 
 #[derive(Resource)]
 pub struct WinitResource {
-    /// <https://www.reddit.com/r/rust/comments/1csjakb/comment/l45os9v>
+    /// See <https://www.reddit.com/r/rust/comments/1csjakb/comment/l45os9v>.
     pub window: Arc<Window>
 }
\ No newline at end of file
@@ -0,0 +1,20 @@
+use bevy_ecs::resource::*;
+use std::time::*;
+use crate::world_storage::*;
+
+/// The state of the systems in [`crate::systems::world_save`].
+#[derive(Resource)]
+pub struct WorldSaveResource {
+    pub storage: WorldStorage,
+    /// Saved along with the columns. [`WorldMetadata::player_position`] is
+    /// updated every time the world is saved.
+    pub metadata: WorldMetadata,
+    /// When the world was last saved, which decides when it is autosaved next.
+    pub last_saved: Instant
+}
+
+impl WorldSaveResource {
+    pub fn new(storage: WorldStorage, metadata: WorldMetadata) -> Self {
+        Self { storage, metadata, last_saved: Instant::now() }
+    }
+}
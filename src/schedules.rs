@@ -1,6 +1,25 @@
 use bevy_ecs::schedule::*;
+use bevy_ecs::schedule::common_conditions::*;
+use bevy_ecs::world::*;
+use crate::resources::block::*;
+use crate::resources::chunk_meshing::*;
+use crate::resources::chunk_streaming::*;
+use crate::resources::egui::*;
+use crate::resources::lighting::*;
+use crate::resources::terrain_generation::*;
+use crate::resources::winit::*;
+use crate::resources::world_save::*;
+use crate::systems::block::*;
+use crate::systems::camera::*;
+use crate::systems::chunk_meshing::*;
+use crate::systems::chunk_streaming::*;
 use crate::systems::egui::*;
+use crate::systems::lighting::*;
+use crate::systems::render::*;
+use crate::systems::screenshot::*;
+use crate::systems::terrain_generation::*;
 use crate::systems::transform::*;
+use crate::systems::world_save::*;
 
 const COMMON_SCHEDULE_BUILD_SETTINGS: ScheduleBuildSettings = ScheduleBuildSettings {
     ambiguity_detection: LogLevel::Warn,
@@ -19,7 +38,17 @@ impl UpdateSchedule {
     /// systems and build settings.
     pub fn create_schedule() -> Schedule {
         let mut schedule = Schedule::new(Self);
-        schedule.set_build_settings(COMMON_SCHEDULE_BUILD_SETTINGS);
+        schedule.set_build_settings(COMMON_SCHEDULE_BUILD_SETTINGS)
+            .add_systems((
+                update_block_registry_system.run_if(resource_exists::<BlockRegistryResource>),
+                stream_columns_system.run_if(resource_exists::<ChunkStreamingResource>.and(resource_exists::<TerrainGenerationResource>)),
+                apply_generated_columns_system.run_if(resource_exists::<TerrainGenerationResource>),
+                spawn_terrain_generation_jobs_system.run_if(resource_exists::<TerrainGenerationResource>),
+                save_world_system.run_if(resource_exists::<WorldSaveResource>),
+                update_light_system.run_if(resource_exists::<LightingResource>),
+                apply_finished_meshing_jobs_system.run_if(resource_exists::<ChunkMeshingResource>),
+                spawn_meshing_jobs_system.run_if(resource_exists::<ChunkMeshingResource>)
+            ).chain());
         schedule
     }
 }
@@ -34,7 +63,14 @@ impl StartupSchedule {
     pub fn create_schedule() -> Schedule {
         let mut schedule = Schedule::new(Self);
         schedule.set_build_settings(COMMON_SCHEDULE_BUILD_SETTINGS)
-            .add_systems(initialize_egui_system);
+            .add_systems((
+                // egui needs a window, which does not exist when running
+                // headlessly.
+                initialize_egui_system.run_if(resource_exists::<WinitResource>),
+                initialize_forward_renderer_system,
+                initialize_chunk_meshing_system.run_if(resource_exists::<BlockRegistryResource>),
+                initialize_screenshot_system
+            ));
         schedule
     }
 }
@@ -60,7 +96,71 @@ impl RenderSchedule {
     pub fn create_schedule() -> Schedule {
         let mut schedule = Schedule::new(Self);
         schedule.set_build_settings(COMMON_SCHEDULE_BUILD_SETTINGS)
-            .add_systems(render_egui_system);
+            .add_systems((
+                clear_frame_system,
+                update_camera_view_system,
+                render_mesh_renderers_system,
+                render_egui_system.run_if(resource_exists::<EguiRendererResource>),
+                capture_requested_screenshot_system,
+                save_finished_screenshots_system
+            ).chain());
         schedule
     }
 }
+
+/// The [`Schedule`] that is only ever run once when the app exits.
+#[derive(ScheduleLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ShutdownSchedule;
+
+impl ShutdownSchedule {
+    pub fn create_schedule() -> Schedule {
+        let mut schedule = Schedule::new(Self);
+        schedule.set_build_settings(COMMON_SCHEDULE_BUILD_SETTINGS)
+            .add_systems(save_world_on_exit_system.run_if(resource_exists::<WorldSaveResource>));
+        schedule
+    }
+}
+
+/// All the [`Schedule`]s that the app runs, in the order they are run. Both
+/// [`crate::application_handler::App`] and
+/// [`crate::headless::HeadlessApp`] use this so that they run the same systems.
+pub struct AppSchedules {
+    startup: Schedule,
+    update: Schedule,
+    post_update: Schedule,
+    render: Schedule,
+    shutdown: Schedule
+}
+
+impl AppSchedules {
+    pub fn new() -> Self {
+        Self {
+            startup: StartupSchedule::create_schedule(),
+            update: UpdateSchedule::create_schedule(),
+            post_update: PostUpdateSchedule::create_schedule(),
+            render: RenderSchedule::create_schedule(),
+            shutdown: ShutdownSchedule::create_schedule()
+        }
+    }
+
+    /// Runs [`StartupSchedule`]. This should only be called once, after the
+    /// [`crate::resources::wgpu::WgpuResource`] has been inserted.
+    pub fn run_startup(&mut self, world: &mut World) {
+        self.startup.run(world);
+    }
+
+    /// Runs [`UpdateSchedule`], [`PostUpdateSchedule`], and
+    /// [`RenderSchedule`]. [`crate::resources::wgpu::WgpuFrameResource`] must
+    /// have been inserted.
+    pub fn run_frame(&mut self, world: &mut World) {
+        self.update.run(world);
+        self.post_update.run(world);
+        self.render.run(world);
+    }
+
+    /// Runs [`ShutdownSchedule`]. This should only be called once, right
+    /// before exiting.
+    pub fn run_shutdown(&mut self, world: &mut World) {
+        self.shutdown.run(world);
+    }
+}
@@ -0,0 +1,185 @@
+//! Contains code that reads rendered frames back from the GPU and saves them
+//! as PNG files.
+
+use futures::channel::oneshot::*;
+use futures::executor::*;
+use image::*;
+use log::*;
+use std::fs;
+use std::io;
+use std::path::*;
+use std::time::*;
+use thiserror::*;
+use wgpu::*;
+
+/// The directory that screenshots are saved in. This path is relative to the
+/// location of the executable.
+pub const SCREENSHOTS_PATH: &str = "screenshots";
+
+/// A [`Buffer`] that a [`Texture`] has been copied into, waiting to be mapped
+/// so that its pixels can be read on the CPU.
+pub struct TextureReadback {
+    buffer: Buffer,
+    width: u32,
+    height: u32,
+    /// [`COPY_BYTES_PER_ROW_ALIGNMENT`] requires that each row be padded.
+    padded_bytes_per_row: u32,
+    format: TextureFormat,
+    /// Receives the result of [`BufferSlice::map_async`].
+    map_result_receiver: Receiver<Result<(), BufferAsyncError>>
+}
+
+impl TextureReadback {
+    /// Submits a copy of `texture` into a new [`Buffer`] and starts mapping
+    /// it. `texture` must have been created with [`TextureUsages::COPY_SRC`].
+    /// This does not wait for the copy to finish; use [`Self::try_read`] or
+    /// [`Self::read_blocking`] to get the pixels.
+    pub fn new(device: &Device, queue: &Queue, texture: &Texture) -> Result<Self, ScreenshotError> {
+        let format = texture.format();
+        if !texture.usage().contains(TextureUsages::COPY_SRC) {
+            let err = ScreenshotError::TextureNotCopyable;
+            error!("{err}");
+            return Err(err);
+        }
+        if !is_supported_format(format) {
+            let err = ScreenshotError::UnsupportedFormat(format);
+            error!("{err}");
+            return Err(err);
+        }
+        let width = texture.width();
+        let height = texture.height();
+        // All supported formats have 4 bytes per pixel.
+        let padded_bytes_per_row = (width * 4).next_multiple_of(COPY_BYTES_PER_ROW_ALIGNMENT);
+        let buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("screenshot-buffer"),
+            size: u64::from(padded_bytes_per_row) * u64::from(height),
+            usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+            mapped_at_creation: false
+        });
+        let mut command_encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("screenshot-command-encoder")
+        });
+        command_encoder.copy_texture_to_buffer(
+            TexelCopyTextureInfo {
+                texture,
+                mip_level: 0,
+                origin: Origin3d::ZERO,
+                aspect: TextureAspect::All
+            },
+            TexelCopyBufferInfo {
+                buffer: &buffer,
+                layout: TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(height)
+                }
+            },
+            texture.size()
+        );
+        queue.submit([command_encoder.finish()]);
+        let (map_result_sender, map_result_receiver) = channel();
+        buffer.slice(..).map_async(MapMode::Read, move |map_result| {
+            // The receiver is gone if the TextureReadback has been dropped, in
+            // which case nobody cares about the result.
+            drop(map_result_sender.send(map_result));
+        });
+        Ok(Self { buffer, width, height, padded_bytes_per_row, format, map_result_receiver })
+    }
+
+    /// Returns [`None`] if the [`Buffer`] has not been mapped yet. This never
+    /// blocks, but [`Device::poll`] must be called for the mapping to make
+    /// progress.
+    pub fn try_read(&mut self) -> Option<Result<RgbaImage, ScreenshotError>> {
+        match self.map_result_receiver.try_recv() {
+            Ok(Some(map_result)) => Some(self.read_mapped(map_result)),
+            Ok(None) => None,
+            Err(Canceled) => Some(Err(ScreenshotError::MapCallbackDropped))
+        }
+    }
+
+    /// Blocks until the [`Buffer`] is mapped and returns its pixels.
+    pub fn read_blocking(mut self, device: &Device) -> Result<RgbaImage, ScreenshotError> {
+        if let Err(err) = device.poll(PollType::Wait) {
+            error!("Could not wait for the screenshot buffer to be mapped. {err:#?}");
+            return Err(ScreenshotError::Poll(err));
+        }
+        match block_on(&mut self.map_result_receiver) {
+            Ok(map_result) => self.read_mapped(map_result),
+            Err(Canceled) => Err(ScreenshotError::MapCallbackDropped)
+        }
+    }
+
+    /// Copies the mapped [`Buffer`] into an [`RgbaImage`], removing the row
+    /// padding and converting the channel order if necessary.
+    fn read_mapped(&self, map_result: Result<(), BufferAsyncError>) -> Result<RgbaImage, ScreenshotError> {
+        if let Err(err) = map_result {
+            error!("Could not map the screenshot buffer. {err:#?}");
+            return Err(ScreenshotError::Map(err));
+        }
+        let mapped_range = self.buffer.slice(..).get_mapped_range();
+        let unpadded_bytes_per_row = self.width as usize * 4;
+        let mut pixels = Vec::with_capacity(unpadded_bytes_per_row * self.height as usize);
+        for row in mapped_range.chunks_exact(self.padded_bytes_per_row as usize) {
+            pixels.extend_from_slice(&row[..unpadded_bytes_per_row]);
+        }
+        drop(mapped_range);
+        self.buffer.unmap();
+        if matches!(self.format, TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb) {
+            for pixel in pixels.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+        RgbaImage::from_raw(self.width, self.height, pixels).ok_or(ScreenshotError::SizeMismatch)
+    }
+}
+
+/// Saves `image` as a PNG file named after the current time in `directory`,
+/// creating `directory` if it does not exist. Returns the path of the file.
+pub fn save_png(image: &RgbaImage, directory: &Path) -> Result<PathBuf, ScreenshotError> {
+    if let Err(err) = fs::create_dir_all(directory) {
+        error!("Could not create the directory {} to save the screenshot in. {err:#?}", directory.display());
+        return Err(ScreenshotError::Io(err));
+    }
+    let milliseconds_since_epoch = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis();
+    let path = directory.join(format!("screenshot-{milliseconds_since_epoch}.png"));
+    if let Err(err) = image.save_with_format(&path, ImageFormat::Png) {
+        error!("Could not save the screenshot to {}. {err:#?}", path.display());
+        return Err(ScreenshotError::Image(err));
+    }
+    info!("Saved a screenshot to {}.", path.display());
+    Ok(path)
+}
+
+/// Only 8-bit RGBA and BGRA formats are supported, which covers every surface
+/// format that is commonly used.
+const fn is_supported_format(format: TextureFormat) -> bool {
+    matches!(format,
+        TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb | TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb)
+}
+
+#[derive(Debug, Error)]
+pub enum ScreenshotError {
+    #[error("The texture to take a screenshot of was not created with TextureUsages::COPY_SRC.")]
+    TextureNotCopyable,
+
+    #[error("Taking screenshots of textures of the format {0:?} is not supported.")]
+    UnsupportedFormat(TextureFormat),
+
+    #[error(transparent)]
+    Poll(#[from] PollError),
+
+    #[error(transparent)]
+    Map(#[from] BufferAsyncError),
+
+    #[error("The callback passed to BufferSlice::map_async was dropped without being called.")]
+    MapCallbackDropped,
+
+    #[error("The size of the pixels read from the GPU does not match the size of the texture.")]
+    SizeMismatch,
+
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    #[error(transparent)]
+    Image(#[from] ImageError)
+}
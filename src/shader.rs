@@ -31,7 +31,7 @@ impl Asset for WeslShader {
             return Err(WeslShaderLoadError::CannotLocateShaderBasePath.into())
         };
         let compiler = Wesl::new(shader_base_path);
-        let compile_result = match compiler.compile(&ModulePath::new(PathOrigin::Absolute, id.split('.').map(ToString::to_string).collect())) {
+        let compile_result = match compiler.compile(&ModulePath::new(PathOrigin::Absolute, id.split('.').map(str::to_owned).collect())) {
             Ok(compile_result) => compile_result,
             Err(err) => {
                 error!("Error while loading shader {id}: {err:#?}");
@@ -72,6 +72,7 @@ fn get_assets_base_path(cache: &AssetCache) -> Option<&Path> {
 
 /// Uses the ID of an asset and returns an absolute path to the asset. If the
 /// [`Source`] used by `cache` is not [`FileSystem`], returns [`None`].
+#[expect(dead_code, reason = "Kept for logging the paths of shaders in the future.")]
 fn asset_cache_id_to_path(cache: &AssetCache, id: &SharedString) -> Option<PathBuf> {
     let assets_base_path = get_assets_base_path(cache)?;
     let result = assets_base_path.join(id.split('.').collect::<PathBuf>());
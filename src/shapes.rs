@@ -1,6 +1,12 @@
 use crate::mesh::{*, mesh_builder::*};
 use glam::*;
-use std::borrow::*;
+use std::collections::*;
+use std::f32::consts::*;
+
+/// The state of the [`MeshBuilder`]s created by the shapes in this module,
+/// which have [`MeshBuilder::vertices`], [`MeshBuilder::indices`],
+/// [`MeshBuilder::uv`] and [`MeshBuilder::normals`] set.
+pub type ShapeMeshBuilderState = SetNormals<SetUv<SetIndices<SetVertices>>>;
 
 pub struct Cuboid {
     /// The size of the cuboid in the order of X, Y, and Z. When any of them is
@@ -8,18 +14,20 @@ pub struct Cuboid {
     pub dimensions: Vec3
 }
 
-impl<T: Borrow<Cuboid>> AsMeshBuilder<SetUv<SetIndices<SetVertices>>> for T {
-    /// Creates a [`MeshBuilder`] that has [`MeshBuilder::vertices`] and
-    /// [`MeshBuilder::uv`] set with 24 vertices and UVs of the cuboid, 4 for
-    /// each face, and [`MeshBuilder::indices`] set with 36 indices, 6 for each
-    /// face. The UVs are arranged such that the +X, -X, +Z, -Z faces have (0,
-    /// 0) as the UV in the bottom left corner, and (1, 1) in the upper right
-    /// corner when the cuboid is viewed from the side. The faces are in the
-    /// order of +X, -X, +Y, -Y, +Z, -Z.
+impl AsMeshBuilder<ShapeMeshBuilderState> for Cuboid {
+    /// Creates a [`MeshBuilder`] that has [`MeshBuilder::vertices`],
+    /// [`MeshBuilder::uv`] and [`MeshBuilder::normals`] set with 24 vertices,
+    /// UVs and normals of the cuboid, 4 for each face, and
+    /// [`MeshBuilder::indices`] set with 36 indices, 6 for each face. The UVs
+    /// are arranged such that the +X, -X, +Z, -Z faces have (0, 0) as the UV
+    /// in the bottom left corner, and (1, 1) in the upper right corner when the
+    /// cuboid is viewed from the side. The faces are in the order of +X, -X,
+    /// +Y, -Y, +Z, -Z. The normals point out of the faces, so they point the
+    /// opposite way along the axes that the cuboid is mirrored along.
     /// 
     /// Refer to `diagrams/cuboid.png` for a diagram of the mesh being built.
-    fn as_mesh_builder(&self) -> MeshBuilder<SetUv<SetIndices<SetVertices>>> {
-        let max = self.borrow().dimensions * 0.5;
+    fn as_mesh_builder(&self) -> MeshBuilder<ShapeMeshBuilderState> {
+        let max = self.dimensions * 0.5;
         let min = -max;
         let vertices = vec![
             // +X
@@ -67,6 +75,583 @@ impl<T: Borrow<Cuboid>> AsMeshBuilder<SetUv<SetIndices<SetVertices>>> for T {
             Vec2::from_array([1.0, 1.0]),
             Vec2::from_array([0.0, 1.0])
         ].into_iter().cycle().take(24).collect();
-        Mesh::builder().vertices(vertices).indices(indices).uv(uv)
+        let mirroring = self.dimensions.signum();
+        let normals = [Vec3::X, Vec3::NEG_X, Vec3::Y, Vec3::NEG_Y, Vec3::Z, Vec3::NEG_Z]
+            .into_iter()
+            .flat_map(|normal| [normal * mirroring; 4])
+            .collect();
+        Mesh::builder().vertices(vertices).indices(indices).uv(uv).normals(normals)
+    }
+}
+
+/// A flat rectangle in the XZ plane that faces +Y, centered at the origin.
+pub struct Plane {
+    /// The size of the plane along X and Z.
+    pub size: Vec2,
+    /// The number of cells that the plane is divided into along X and Z. Zero
+    /// is treated as one.
+    pub subdivisions: UVec2
+}
+
+impl AsMeshBuilder<ShapeMeshBuilderState> for Plane {
+    /// Creates a [`MeshBuilder`] for a grid of `subdivisions.x + 1` by
+    /// `subdivisions.y + 1` vertices. The UVs are laid out like the +Y face of
+    /// a [`Cuboid`], with U going along +X and V going along +Z.
+    fn as_mesh_builder(&self) -> MeshBuilder<ShapeMeshBuilderState> {
+        let size = Vec3::new(self.size.x, 0.0, self.size.y);
+        let mut shape = ShapeBuilder::default();
+        shape.push_grid(size * -0.5, size * Vec3::X, size * Vec3::Z, self.subdivisions);
+        shape.build()
+    }
+}
+
+/// A flat rectangle in the XY plane that faces -Z, centered at the origin.
+/// With the default camera orientation, which looks along +Z, it faces the
+/// camera.
+pub struct Quad {
+    /// The size of the quad along X and Y.
+    pub size: Vec2,
+    /// The number of cells that the quad is divided into along X and Y. Zero
+    /// is treated as one.
+    pub subdivisions: UVec2
+}
+
+impl AsMeshBuilder<ShapeMeshBuilderState> for Quad {
+    /// Creates a [`MeshBuilder`] for a grid of `subdivisions.x + 1` by
+    /// `subdivisions.y + 1` vertices. The UVs are laid out like the -Z face of
+    /// a [`Cuboid`], with (0, 0) in the bottom left corner and (1, 1) in the
+    /// upper right corner.
+    fn as_mesh_builder(&self) -> MeshBuilder<ShapeMeshBuilderState> {
+        let size = self.size.extend(0.0);
+        let mut shape = ShapeBuilder::default();
+        shape.push_grid(size * -0.5, size * Vec3::X, size * Vec3::Y, self.subdivisions);
+        shape.build()
+    }
+}
+
+/// A sphere made of rings of latitude and sectors of longitude, centered at the
+/// origin.
+pub struct UvSphere {
+    pub radius: f32,
+    /// The number of sectors around the Y axis. Values below 3 are treated as
+    /// 3.
+    pub sectors: u32,
+    /// The number of rings from the bottom pole to the top pole. Values below 2
+    /// are treated as 2.
+    pub rings: u32
+}
+
+impl AsMeshBuilder<ShapeMeshBuilderState> for UvSphere {
+    /// Creates a [`MeshBuilder`] for the sphere with an equirectangular UV
+    /// layout. U goes from 0 to 1 around the Y axis, starting at +X and turning
+    /// towards +Z, and V goes from 0 at the bottom pole to 1 at the top pole.
+    /// The vertices along the seam at +X and at the poles are repeated so that
+    /// every triangle gets continuous UVs.
+    #[expect(clippy::float_arithmetic, reason = "Computes angles of latitude.")]
+    #[expect(clippy::cast_precision_loss, reason = "Spheres have few rings.")]
+    fn as_mesh_builder(&self) -> MeshBuilder<ShapeMeshBuilderState> {
+        let rings = self.rings.max(2);
+        let profile: Vec<_> = (0..=rings)
+            .map(|ring| {
+                let (sin, cos) = (ring as f32 / rings as f32 * PI).sin_cos();
+                let normal = Vec2::new(sin, -cos);
+                ProfilePoint { position: normal * self.radius, normal }
+            })
+            .collect();
+        let mut shape = ShapeBuilder::default();
+        shape.push_revolution(&profile, self.sectors);
+        shape.build()
+    }
+}
+
+/// A sphere made by repeatedly subdividing the triangles of an icosahedron,
+/// centered at the origin. Unlike a [`UvSphere`], its triangles are all about
+/// the same size.
+pub struct IcoSphere {
+    pub radius: f32,
+    /// How many times each triangle of the icosahedron is split into 4, so the
+    /// sphere has `20 * 4^subdivisions` triangles.
+    pub subdivisions: u32
+}
+
+impl AsMeshBuilder<ShapeMeshBuilderState> for IcoSphere {
+    /// Creates a [`MeshBuilder`] for the sphere with the same equirectangular
+    /// UV layout as a [`UvSphere`]. The triangles that cross the seam at +X get
+    /// their own copies of the vertices before it, with U past 1, and every
+    /// triangle at a pole gets its own copy of the pole, with U halfway between
+    /// its other two vertices.
+    #[expect(clippy::float_arithmetic, reason = "Computes the golden ratio and the U halfway between two vertices.")]
+    fn as_mesh_builder(&self) -> MeshBuilder<ShapeMeshBuilderState> {
+        let golden_ratio = f32::midpoint(1.0, 5.0_f32.sqrt());
+        let mut directions: Vec<Vec3> = [
+            [-1.0, golden_ratio, 0.0], [1.0, golden_ratio, 0.0], [-1.0, -golden_ratio, 0.0], [1.0, -golden_ratio, 0.0],
+            [0.0, -1.0, golden_ratio], [0.0, 1.0, golden_ratio], [0.0, -1.0, -golden_ratio], [0.0, 1.0, -golden_ratio],
+            [golden_ratio, 0.0, -1.0], [golden_ratio, 0.0, 1.0], [-golden_ratio, 0.0, -1.0], [-golden_ratio, 0.0, 1.0]
+        ].map(|direction| Vec3::from_array(direction).normalize()).to_vec();
+        let mut triangles = vec![
+            [0, 5, 11], [0, 1, 5], [0, 7, 1], [0, 10, 7], [0, 11, 10],
+            [1, 9, 5], [5, 4, 11], [11, 2, 10], [10, 6, 7], [7, 8, 1],
+            [3, 4, 9], [3, 2, 4], [3, 6, 2], [3, 8, 6], [3, 9, 8],
+            [4, 5, 9], [2, 11, 4], [6, 10, 2], [8, 7, 6], [9, 1, 8]
+        ];
+        for _ in 0..self.subdivisions {
+            let mut midpoints = HashMap::new();
+            triangles = triangles
+                .into_iter()
+                .flat_map(|[a, b, c]| {
+                    let [ab, bc, ca] = [(a, b), (b, c), (c, a)].map(|(from, to)| {
+                        *midpoints.entry((from.min(to), from.max(to))).or_insert_with(|| {
+                            directions.push((directions[from] + directions[to]).normalize());
+                            directions.len() - 1
+                        })
+                    });
+                    [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+                })
+                .collect();
+        }
+        let mut uv: Vec<_> = directions.iter().map(|&direction| equirectangular_uv(direction)).collect();
+        let mut wrapped_vertices = HashMap::new();
+        for triangle in &mut triangles {
+            let is_pole = triangle.map(|vertex| directions[vertex].xz() == Vec2::ZERO);
+            let u_values = (0..3).filter(|&corner| !is_pole[corner]).map(|corner| uv[triangle[corner]].x);
+            let (min_u, max_u) = u_values.fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), u| (min.min(u), max.max(u)));
+            if max_u - min_u > 0.5 {
+                for (vertex, is_vertex_pole) in triangle.iter_mut().zip(is_pole) {
+                    if !is_vertex_pole && uv[*vertex].x < 0.5 {
+                        *vertex = *wrapped_vertices.entry(*vertex).or_insert_with(|| {
+                            directions.push(directions[*vertex]);
+                            uv.push(uv[*vertex] + Vec2::X);
+                            directions.len() - 1
+                        });
+                    }
+                }
+            }
+            for corner in (0..3).filter(|&corner| is_pole[corner]) {
+                let others = [triangle[(corner + 1) % 3], triangle[(corner + 2) % 3]];
+                let u = f32::midpoint(uv[others[0]].x, uv[others[1]].x);
+                directions.push(directions[triangle[corner]]);
+                uv.push(Vec2::new(u, uv[triangle[corner]].y));
+                triangle[corner] = directions.len() - 1;
+            }
+        }
+        let vertices = directions.iter().map(|&direction| direction * self.radius).collect();
+        Mesh::builder().vertices(vertices).indices(triangles.into_flattened()).uv(uv).normals(directions)
+    }
+}
+
+/// A cylinder along the Y axis with flat caps, centered at the origin.
+pub struct Cylinder {
+    pub radius: f32,
+    pub height: f32,
+    /// The number of sectors around the Y axis. Values below 3 are treated as
+    /// 3.
+    pub sectors: u32,
+    /// The number of segments that the side is divided into along the Y axis.
+    /// Zero is treated as one.
+    pub segments: u32
+}
+
+impl AsMeshBuilder<ShapeMeshBuilderState> for Cylinder {
+    /// Creates a [`MeshBuilder`] for the bottom cap, the side and the top cap
+    /// of the cylinder in that order. The side has U going from 0 to 1 around
+    /// the Y axis like a [`UvSphere`], and V going from 0 at the bottom to 1 at
+    /// the top. The caps map the square from (0, 0) to (1, 1) onto themselves
+    /// like the -Y and +Y faces of a [`Cuboid`].
+    #[expect(clippy::float_arithmetic, reason = "Computes the heights of the segments.")]
+    #[expect(clippy::cast_precision_loss, reason = "Cylinders have few segments.")]
+    fn as_mesh_builder(&self) -> MeshBuilder<ShapeMeshBuilderState> {
+        let segments = self.segments.max(1);
+        let half_height = self.height * 0.5;
+        let profile: Vec<_> = (0..=segments)
+            .map(|segment| ProfilePoint {
+                position: Vec2::new(self.radius, (segment as f32 / segments as f32).mul_add(self.height, -half_height)),
+                normal: Vec2::X
+            })
+            .collect();
+        let mut shape = ShapeBuilder::default();
+        shape.push_disk(-half_height, self.radius, self.sectors, false);
+        shape.push_revolution(&profile, self.sectors);
+        shape.push_disk(half_height, self.radius, self.sectors, true);
+        shape.build()
+    }
+}
+
+/// A cone along the Y axis with its base at the bottom and its apex at the top,
+/// centered at the origin.
+pub struct Cone {
+    /// The radius of the base.
+    pub radius: f32,
+    pub height: f32,
+    /// The number of sectors around the Y axis. Values below 3 are treated as
+    /// 3.
+    pub sectors: u32,
+    /// The number of segments that the side is divided into along the Y axis.
+    /// Zero is treated as one.
+    pub segments: u32
+}
+
+impl AsMeshBuilder<ShapeMeshBuilderState> for Cone {
+    /// Creates a [`MeshBuilder`] for the base and the side of the cone in that
+    /// order, with UVs like those of a [`Cylinder`]. The apex is repeated for
+    /// every sector so that each one gets its own U and normal.
+    #[expect(clippy::float_arithmetic, reason = "Computes the heights and radii of the segments.")]
+    #[expect(clippy::cast_precision_loss, reason = "Cones have few segments.")]
+    fn as_mesh_builder(&self) -> MeshBuilder<ShapeMeshBuilderState> {
+        let segments = self.segments.max(1);
+        let half_height = self.height * 0.5;
+        let base = Vec2::new(self.radius, -half_height);
+        let apex = Vec2::new(0.0, half_height);
+        // The side is perpendicular to the line from the base to the apex.
+        let normal = -(apex - base).perp().normalize_or_zero();
+        let profile: Vec<_> = (0..=segments)
+            .map(|segment| ProfilePoint { position: base.lerp(apex, segment as f32 / segments as f32), normal })
+            .collect();
+        let mut shape = ShapeBuilder::default();
+        shape.push_disk(-half_height, self.radius, self.sectors, false);
+        shape.push_revolution(&profile, self.sectors);
+        shape.build()
+    }
+}
+
+/// A cylinder along the Y axis with hemispheres on both ends, centered at the
+/// origin.
+pub struct Capsule {
+    pub radius: f32,
+    /// The length of the cylinder between the hemispheres, so the whole capsule
+    /// is `length + 2 * radius` tall.
+    pub length: f32,
+    /// The number of sectors around the Y axis. Values below 3 are treated as
+    /// 3.
+    pub sectors: u32,
+    /// The number of rings in each hemisphere. Zero is treated as one.
+    pub hemisphere_rings: u32,
+    /// The number of segments that the cylinder is divided into along the Y
+    /// axis. Zero is treated as one.
+    pub segments: u32
+}
+
+impl AsMeshBuilder<ShapeMeshBuilderState> for Capsule {
+    /// Creates a [`MeshBuilder`] for the capsule as one smooth surface with UVs
+    /// like those of a [`UvSphere`]. V is proportional to the distance along
+    /// the surface from the bottom pole, so a texture is not stretched more on
+    /// the cylinder than on the hemispheres.
+    #[expect(clippy::float_arithmetic, reason = "Computes angles of latitude and the heights of the segments.")]
+    #[expect(clippy::cast_precision_loss, reason = "Capsules have few rings and segments.")]
+    fn as_mesh_builder(&self) -> MeshBuilder<ShapeMeshBuilderState> {
+        let hemisphere_rings = self.hemisphere_rings.max(1);
+        let segments = self.segments.max(1);
+        let half_length = self.length * 0.5;
+        let hemisphere_point = |ring: u32, center: f32| {
+            let (sin, cos) = (ring as f32 / hemisphere_rings as f32 * FRAC_PI_2).sin_cos();
+            let normal = Vec2::new(sin, -cos);
+            ProfilePoint { position: normal * self.radius + Vec2::new(0.0, center), normal }
+        };
+        // Without a cylinder, the hemispheres meet at the equator, which should
+        // not be repeated.
+        let (inner_segments, top_first_ring) = if self.length > 0.0 { (1..segments, hemisphere_rings) } else { (1..1, hemisphere_rings + 1) };
+        let bottom = (0..=hemisphere_rings).map(|ring| hemisphere_point(ring, -half_length));
+        let cylinder = inner_segments.map(|segment| ProfilePoint {
+            position: Vec2::new(self.radius, (segment as f32 / segments as f32).mul_add(self.length, -half_length)),
+            normal: Vec2::X
+        });
+        let top = (top_first_ring..=hemisphere_rings * 2).map(|ring| hemisphere_point(ring, half_length));
+        let profile: Vec<_> = bottom.chain(cylinder).chain(top).collect();
+        let mut shape = ShapeBuilder::default();
+        shape.push_revolution(&profile, self.sectors);
+        shape.build()
+    }
+}
+
+/// A ring around the Y axis with a circular cross section, centered at the
+/// origin.
+pub struct Torus {
+    /// The distance from the Y axis to the center of the tube.
+    pub major_radius: f32,
+    /// The radius of the tube. It should be smaller than `major_radius` for the
+    /// torus to have a hole.
+    pub minor_radius: f32,
+    /// The number of sectors around the Y axis. Values below 3 are treated as
+    /// 3.
+    pub sectors: u32,
+    /// The number of segments around the tube. Values below 3 are treated as 3.
+    pub rings: u32
+}
+
+impl AsMeshBuilder<ShapeMeshBuilderState> for Torus {
+    /// Creates a [`MeshBuilder`] for the torus with U going from 0 to 1 around
+    /// the Y axis like a [`UvSphere`], and V going from 0 to 1 around the tube,
+    /// starting and ending at the inner edge and passing the bottom first.
+    #[expect(clippy::float_arithmetic, reason = "Computes angles around the tube.")]
+    #[expect(clippy::cast_precision_loss, reason = "Tori have few rings.")]
+    fn as_mesh_builder(&self) -> MeshBuilder<ShapeMeshBuilderState> {
+        let rings = self.rings.max(3);
+        let profile: Vec<_> = (0..=rings)
+            .map(|ring| {
+                let (sin, cos) = (ring as f32 / rings as f32).mul_add(TAU, -PI).sin_cos();
+                let normal = Vec2::new(cos, sin);
+                ProfilePoint { position: normal * self.minor_radius + Vec2::new(self.major_radius, 0.0), normal }
+            })
+            .collect();
+        let mut shape = ShapeBuilder::default();
+        shape.push_revolution(&profile, self.sectors);
+        shape.build()
+    }
+}
+
+/// Returns the UV of `direction` on a [`UvSphere`].
+#[expect(clippy::float_arithmetic, reason = "Converts angles into UVs.")]
+fn equirectangular_uv(direction: Vec3) -> Vec2 {
+    Vec2::new((direction.z.atan2(direction.x) / TAU).rem_euclid(1.0), direction.y.atan2(direction.xz().length()) / PI + 0.5)
+}
+
+/// A point on the outline of a shape of revolution, in a plane whose X is the
+/// distance from the Y axis.
+struct ProfilePoint {
+    position: Vec2,
+    /// The normal of the surface at the point, in the same plane.
+    normal: Vec2
+}
+
+/// Accumulates the vertex attributes and indices of a shape that is made of
+/// several parts.
+#[derive(Default)]
+struct ShapeBuilder {
+    vertices: Vec<Vec3>,
+    uv: Vec<Vec2>,
+    normals: Vec<Vec3>,
+    indices: Vec<usize>
+}
+
+impl ShapeBuilder {
+    /// Adds a vertex and returns its index.
+    fn push_vertex(&mut self, position: Vec3, uv: Vec2, normal: Vec3) -> usize {
+        self.vertices.push(position);
+        self.uv.push(uv);
+        self.normals.push(normal);
+        self.vertices.len() - 1
+    }
+
+    /// Adds a flat grid of `subdivisions.x` by `subdivisions.y` cells that goes
+    /// from `corner` by `u_edge` along U and by `v_edge` along V. Like the
+    /// faces of a [`Cuboid`], it faces the side from which U goes right and V
+    /// goes up.
+    fn push_grid(&mut self, corner: Vec3, u_edge: Vec3, v_edge: Vec3, subdivisions: UVec2) {
+        let cells = subdivisions.max(UVec2::ONE);
+        let normal = v_edge.cross(u_edge).normalize_or_zero();
+        let first = self.vertices.len();
+        for row in 0..=cells.y {
+            for column in 0..=cells.x {
+                let uv = UVec2::new(column, row).as_vec2() / cells.as_vec2();
+                self.push_vertex(corner + u_edge * uv.x + v_edge * uv.y, uv, normal);
+            }
+        }
+        let index = |column: u32, row: u32| first + (row * (cells.x + 1) + column) as usize;
+        for row in 0..cells.y {
+            for column in 0..cells.x {
+                let [a, b, c, d] = [index(column, row), index(column + 1, row), index(column + 1, row + 1), index(column, row + 1)];
+                self.indices.extend([a, b, c, c, d, a]);
+            }
+        }
+    }
+
+    /// Adds the surface swept by `profile` around the Y axis in `sectors`
+    /// steps, or 3 if there are fewer. U goes from 0 to 1 around the axis,
+    /// starting at +X and turning towards +Z, and V goes from 0 to 1 along the
+    /// profile in proportion to the distance along it. The surface faces out
+    /// when the profile goes around the shape counterclockwise in its plane,
+    /// such as from the bottom of a sphere to its top.
+    ///
+    /// The points of the profile are repeated for every sector so that the
+    /// seam gets two different Us. Points on the axis only form one triangle
+    /// per sector with the points next to them, so that no triangle is
+    /// degenerate.
+    #[expect(clippy::float_arithmetic, reason = "Computes the angles of the sectors and the distance along the profile.")]
+    #[expect(clippy::cast_precision_loss, reason = "Shapes have few sectors.")]
+    fn push_revolution(&mut self, profile: &[ProfilePoint], min_sectors: u32) {
+        let sectors = min_sectors.max(3);
+        let distances: Vec<f32> = profile
+            .iter()
+            .scan((0.0, None), |(distance, previous): &mut (f32, Option<Vec2>), point| {
+                *distance += previous.map_or(0.0, |previous_position| previous_position.distance(point.position));
+                *previous = Some(point.position);
+                Some(*distance)
+            })
+            .collect();
+        let length = distances.last().copied().unwrap_or_default();
+        let first = self.vertices.len();
+        for (point, distance) in profile.iter().zip(&distances) {
+            let v = if length > 0.0 { distance / length } else { 0.0 };
+            for sector in 0..=sectors {
+                let u = sector as f32 / sectors as f32;
+                let (sin, cos) = (u * TAU).sin_cos();
+                let outward = Vec3::new(cos, 0.0, sin);
+                let position = outward * point.position.x + Vec3::Y * point.position.y;
+                let normal = (outward * point.normal.x + Vec3::Y * point.normal.y).normalize_or_zero();
+                self.push_vertex(position, Vec2::new(u, v), normal);
+            }
+        }
+        let columns = sectors as usize + 1;
+        let index = |ring: usize, sector: usize| first + ring * columns + sector;
+        for (ring, [lower, upper]) in profile.array_windows().enumerate() {
+            for sector in 0..sectors as usize {
+                let [a, b, c, d] = [index(ring, sector), index(ring, sector + 1), index(ring + 1, sector + 1), index(ring + 1, sector)];
+                if lower.position.x > 0.0 {
+                    self.indices.extend([a, b, c]);
+                }
+                if upper.position.x > 0.0 {
+                    self.indices.extend([c, d, a]);
+                }
+            }
+        }
+    }
+
+    /// Adds a disk at `height` with `sectors` sides, or 3 if there are fewer,
+    /// facing +Y if `faces_up` and -Y otherwise. The square from (0, 0) to
+    /// (1, 1) is mapped onto it like onto the +Y or -Y face of a [`Cuboid`].
+    #[expect(clippy::float_arithmetic, reason = "Computes the angles of the sectors.")]
+    #[expect(clippy::cast_precision_loss, reason = "Shapes have few sectors.")]
+    fn push_disk(&mut self, height: f32, radius: f32, min_sectors: u32, faces_up: bool) {
+        let sectors = min_sectors.max(3) as usize;
+        let normal = if faces_up { Vec3::Y } else { Vec3::NEG_Y };
+        let center = self.push_vertex(Vec3::Y * height, Vec2::splat(0.5), normal);
+        for sector in 0..sectors {
+            let (sin, cos) = (sector as f32 / sectors as f32 * TAU).sin_cos();
+            // V goes along +Z when seen from above, and along -Z when seen
+            // from below.
+            let uv = Vec2::new(cos, if faces_up { sin } else { -sin }).mul_add(Vec2::splat(0.5), Vec2::splat(0.5));
+            self.push_vertex(Vec3::new(cos * radius, height, sin * radius), uv, normal);
+        }
+        for sector in 0..sectors {
+            let [rim, next_rim] = [sector, (sector + 1) % sectors].map(|rim_sector| center + 1 + rim_sector);
+            self.indices.extend(if faces_up { [rim, next_rim, center] } else { [next_rim, rim, center] });
+        }
+    }
+
+    fn build(self) -> MeshBuilder<ShapeMeshBuilderState> {
+        Mesh::builder().vertices(self.vertices).indices(self.indices).uv(self.uv).normals(self.normals)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The normal of every vertex should point to the side that its triangles
+    /// are wound counterclockwise from, which is the front face. The world is
+    /// left-handed, so the cross product of two edges of a triangle points
+    /// away from that side.
+    #[test]
+    fn cuboid_normals_point_out_of_the_front_faces() {
+        for dimensions in [Vec3::ONE, Vec3::new(2.0, 0.5, 3.0), Vec3::new(-1.0, 1.0, 1.0)] {
+            let mesh = Cuboid { dimensions }.as_mesh_builder().build().expect("A cuboid should always be a valid mesh.");
+            for triangle in mesh.indices().as_chunks::<3>().0 {
+                let [a, b, c] = triangle.map(|index| mesh.vertices()[index]);
+                let front = (c - a).cross(b - a);
+                // Mirroring along an odd number of axes turns the cuboid
+                // inside out.
+                let facing = front.dot(mesh.normals()[triangle[0]]) * dimensions.x.signum() * dimensions.y.signum() * dimensions.z.signum();
+                assert!(facing > 0.0, "The normal of a triangle of a cuboid with dimensions {dimensions} points into it.");
+            }
+        }
+    }
+
+    /// Checks that every triangle of `mesh` is wound counterclockwise from the
+    /// side that the normals of its vertices point to.
+    fn assert_well_wound(name: &str, mesh: &Mesh) {
+        for normal in mesh.normals() {
+            assert!(normal.is_normalized(), "{name} has a normal {normal} that is not normalized.");
+        }
+        for triangle in mesh.indices().as_chunks::<3>().0 {
+            let [a, b, c] = triangle.map(|index| mesh.vertices()[index]);
+            let front = (c - a).cross(b - a);
+            assert!(front.length() > 0.0, "{name} has a degenerate triangle {triangle:?}.");
+            for &index in triangle {
+                assert!(front.dot(mesh.normals()[index]) > 0.0, "A triangle {triangle:?} of {name} faces away from its normals.");
+            }
+        }
+    }
+
+    #[test]
+    fn flat_shapes_face_their_normals() {
+        for subdivisions in [UVec2::ZERO, UVec2::ONE, UVec2::new(3, 2)] {
+            let size = Vec2::new(2.0, 3.0);
+            let cells = subdivisions.max(UVec2::ONE);
+            let plane = Plane { size, subdivisions }.as_mesh_builder().build().expect("A plane should always be a valid mesh.");
+            let quad = Quad { size, subdivisions }.as_mesh_builder().build().expect("A quad should always be a valid mesh.");
+            for (name, mesh, normal) in [("A plane", plane, Vec3::Y), ("A quad", quad, Vec3::NEG_Z)] {
+                assert_well_wound(name, &mesh);
+                assert_eq!(mesh.vertices().len(), ((cells.x + 1) * (cells.y + 1)) as usize);
+                assert_eq!(mesh.indices().len(), (cells.x * cells.y * 6) as usize);
+                assert!(mesh.normals().iter().all(|&vertex_normal| vertex_normal == normal));
+                // The UVs cover the square from (0, 0) to (1, 1) exactly, with
+                // (0, 0) at the corner with the smallest coordinates.
+                for (&position, &uv) in mesh.vertices().iter().zip(mesh.uv()) {
+                    let flattened = if normal == Vec3::Y { position.xz() } else { position.xy() };
+                    assert!((flattened / size + 0.5).abs_diff_eq(uv, 1e-6), "{name} has a UV {uv} at {position}.");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn round_shapes_face_outwards() {
+        let mut meshes = Vec::new();
+        for (sectors, rings) in [(0, 0), (3, 2), (32, 16)] {
+            meshes.push(("A UV sphere", UvSphere { radius: 2.0, sectors, rings }.as_mesh_builder()));
+            meshes.push(("A cylinder", Cylinder { radius: 1.0, height: 3.0, sectors, segments: rings }.as_mesh_builder()));
+            meshes.push(("A cone", Cone { radius: 1.0, height: 3.0, sectors, segments: rings }.as_mesh_builder()));
+            meshes.push(("A capsule", Capsule { radius: 1.0, length: 2.0, sectors, hemisphere_rings: rings, segments: rings }.as_mesh_builder()));
+            meshes.push(("A capsule without a cylinder", Capsule { radius: 1.0, length: 0.0, sectors, hemisphere_rings: rings, segments: rings }.as_mesh_builder()));
+            meshes.push(("A torus", Torus { major_radius: 2.0, minor_radius: 0.5, sectors, rings }.as_mesh_builder()));
+        }
+        for subdivisions in 0..4 {
+            meshes.push(("An icosphere", IcoSphere { radius: 2.0, subdivisions }.as_mesh_builder()));
+        }
+        for (name, builder) in meshes {
+            let mesh = builder.build().expect("A shape should always be a valid mesh.");
+            assert_well_wound(name, &mesh);
+            for (&position, &normal) in mesh.vertices().iter().zip(mesh.normals()) {
+                // Every shape but the torus is convex around the origin, and
+                // the torus is convex around the circle at the center of its
+                // tube.
+                let center = if name == "A torus" { position.with_y(0.0).normalize() * 2.0 } else { Vec3::ZERO };
+                assert!(normal.dot(position - center) > 0.0, "{name} has a normal {normal} at {position} that points inwards.");
+            }
+        }
+    }
+
+    /// Checks that no triangle of `mesh` wraps its UVs around the seam, which
+    /// would squeeze a whole row of the texture into it.
+    #[expect(clippy::float_arithmetic, reason = "Measures how far the UVs of a triangle spread.")]
+    fn assert_continuous_uv(name: &str, mesh: &Mesh) {
+        for triangle in mesh.indices().as_chunks::<3>().0 {
+            let u = triangle.map(|index| mesh.uv()[index].x);
+            let (min_u, max_u) = (u[0].min(u[1]).min(u[2]), u[0].max(u[1]).max(u[2]));
+            assert!(max_u - min_u <= 0.5, "A triangle {triangle:?} of {name} wraps around the seam of the UVs.");
+        }
+    }
+
+    #[test]
+    fn spheres_have_the_same_uvs() {
+        for subdivisions in 0..4 {
+            let mesh = IcoSphere { radius: 2.0, subdivisions }.as_mesh_builder().build().expect("An icosphere should always be a valid mesh.");
+            assert_continuous_uv("An icosphere", &mesh);
+            for (&position, &uv) in mesh.vertices().iter().zip(mesh.uv()) {
+                assert!((position.length() - 2.0).abs() < 1e-5, "An icosphere has a vertex {position} that is not on its surface.");
+                assert!((0.0..1.5).contains(&uv.x), "An icosphere has a U of {} at {position}.", uv.x);
+                assert!((uv.y - equirectangular_uv(position.normalize()).y).abs() < 1e-5, "An icosphere has a V of {} at {position}.", uv.y);
+            }
+        }
+        let mesh = UvSphere { radius: 2.0, sectors: 32, rings: 16 }.as_mesh_builder().build().expect("A UV sphere should always be a valid mesh.");
+        assert_continuous_uv("A UV sphere", &mesh);
+        for (&position, &uv) in mesh.vertices().iter().zip(mesh.uv()) {
+            assert!((position.length() - 2.0).abs() < 1e-5, "A UV sphere has a vertex {position} that is not on its surface.");
+            // The U of the poles is arbitrary.
+            if position.xz().length() > 1e-3 {
+                let expected_u = equirectangular_uv(position.normalize()).x;
+                assert!((uv.x - expected_u).abs() < 1e-5 || (uv.x - expected_u - 1.0).abs() < 1e-5, "A UV sphere has a U of {} at {position}.", uv.x);
+            }
+            assert!((uv.y - equirectangular_uv(position.normalize()).y).abs() < 1e-5, "A UV sphere has a V of {} at {position}.", uv.y);
+        }
     }
 }
@@ -0,0 +1,145 @@
+//! Contains [`StructureTemplate`], a small arrangement of blocks such as a tree
+//! that terrain generation places in the world. Templates are loaded from RON
+//! files in the `assets/structures` directory.
+
+use assets_manager::*;
+use assets_manager::asset::*;
+use glam::*;
+use log::*;
+use serde::*;
+use std::borrow::*;
+use std::collections::*;
+use thiserror::*;
+use crate::block::*;
+
+/// The ID of the directory in the [`AssetCache`] that [`StructureTemplates`]
+/// are loaded from.
+pub const STRUCTURES_ASSET_ID: &str = "structures";
+
+/// The characters in [`StructureTemplate::layers`] that leave the block there
+/// as it is.
+const EMPTY_CHARACTERS: [char; 2] = ['.', ' '];
+
+/// Which blocks the blocks of a structure may replace.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReplaceMode {
+    /// Only air, so that structures never cut into the terrain or into each
+    /// other.
+    #[default]
+    Air,
+    /// Any block.
+    Any
+}
+
+/// The blocks of a structure. Each one is loaded from a RON file in the
+/// `assets/structures` directory, and is named after the file.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StructureTemplate {
+    /// The blocks that the characters in [`Self::layers`] stand for.
+    pub palette: HashMap<char, BlockName>,
+    /// The horizontal layers of the structure from the bottom to the top.
+    /// Each layer is a list of rows along Z, and each row has one character
+    /// per block along X. `.` and spaces leave the block there as it is.
+    pub layers: Vec<Vec<String>>,
+    /// The X, Y and Z in [`Self::layers`] of the block that is placed right
+    /// above the ground that the structure stands on.
+    pub anchor: (i32, i32, i32),
+    #[serde(default)]
+    pub replace_mode: ReplaceMode
+}
+
+impl StructureTemplate {
+    /// Iterates over the blocks that the structure sets, as their offsets
+    /// from [`Self::anchor`] along with their names.
+    pub fn blocks(&self) -> impl Iterator<Item = (IVec3, &BlockName)> {
+        let anchor = IVec3::from(self.anchor);
+        (0..).zip(&self.layers).flat_map(move |(y, layer)| {
+            (0..).zip(layer).flat_map(move |(z, row)| {
+                (0..).zip(row.chars()).filter_map(move |(x, char)| Some((IVec3::new(x, y, z) - anchor, self.palette.get(&char)?)))
+            })
+        })
+    }
+
+    fn validate(&self) -> Result<(), StructureTemplateError> {
+        if let Some(char) = EMPTY_CHARACTERS.into_iter().find(|char| self.palette.contains_key(char)) {
+            return Err(StructureTemplateError::EmptyCharacterInPalette(char));
+        }
+        for char in self.layers.iter().flatten().flat_map(|row| row.chars()) {
+            if !EMPTY_CHARACTERS.contains(&char) && !self.palette.contains_key(&char) {
+                return Err(StructureTemplateError::UnknownCharacter(char));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FileAsset for StructureTemplate {
+    const EXTENSION: &'static str = "ron";
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Result<Self, BoxedError> {
+        let template: Self = load_ron(&bytes)?;
+        if let Err(err) = template.validate() {
+            error!("{err}");
+            return Err(err.into());
+        }
+        Ok(template)
+    }
+}
+
+/// Every [`StructureTemplate`] directly inside a directory, by name.
+pub struct StructureTemplates {
+    templates: HashMap<String, StructureTemplate>
+}
+
+impl StructureTemplates {
+    pub fn get(&self, name: &str) -> Option<&StructureTemplate> {
+        self.templates.get(name)
+    }
+}
+
+impl Asset for StructureTemplates {
+    fn load(cache: &AssetCache, id: &SharedString) -> Result<Self, BoxedError> {
+        info!("Loading structure templates: {id}");
+        let directory = cache.load_dir::<StructureTemplate>(id)?;
+        let mut templates = HashMap::new();
+        for asset_id in directory.read().ids() {
+            let name = asset_id.rsplit_once('.').map_or(asset_id.as_str(), |(_, name)| name);
+            templates.insert(name.to_owned(), cache.load::<StructureTemplate>(asset_id)?.cloned());
+        }
+        info!("Loaded {} structure templates.", templates.len());
+        Ok(Self { templates })
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum StructureTemplateError {
+    #[error("The character '{0}' is used in the layers, but is not in the palette.")]
+    UnknownCharacter(char),
+
+    #[error("The character '{0}' cannot be in the palette, because it leaves blocks as they are.")]
+    EmptyCharacterInPalette(char)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocks_are_offset_from_the_anchor() {
+        let ron = br#"(
+            palette: { 'L': "mycraft:oak_log" },
+            anchor: (1, 0, 0),
+            layers: [[".L", " ."], ["..", ".L"]],
+        )"#;
+        let template = StructureTemplate::from_bytes(Cow::Borrowed(ron)).expect("The template should be valid.");
+        let blocks: Vec<_> = template.blocks().map(|(offset, name)| (offset, name.as_str())).collect();
+        assert_eq!(blocks, [(IVec3::new(0, 0, 0), "mycraft:oak_log"), (IVec3::new(0, 1, 1), "mycraft:oak_log")]);
+    }
+
+    #[test]
+    fn unknown_characters_are_rejected() {
+        let ron = br#"(palette: { 'L': "mycraft:oak_log" }, anchor: (0, 0, 0), layers: [["LX"]])"#;
+        assert!(StructureTemplate::from_bytes(Cow::Borrowed(ron)).is_err(), "X is not in the palette.");
+    }
+}
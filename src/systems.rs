@@ -1,4 +1,13 @@
 #![expect(clippy::needless_pass_by_value, reason = "bevy_ecs requires that system parameters be passed by value.")]
 #![expect(clippy::type_complexity, reason = "Query parameters often trigger thsi lint, but it is harmless.")]
+pub mod block;
+pub mod camera;
+pub mod chunk_meshing;
+pub mod chunk_streaming;
 pub mod egui;
+pub mod lighting;
+pub mod render;
+pub mod screenshot;
+pub mod terrain_generation;
 pub mod transform;
+pub mod world_save;
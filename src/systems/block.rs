@@ -0,0 +1,17 @@
+use bevy_ecs::system::*;
+use crate::resources::block::*;
+
+/// Updates [`BlockRegistryResource::registry`] when the block definitions
+/// have been hot-reloaded. The resource is only marked as changed if the
+/// definitions have been reloaded.
+pub fn update_block_registry_system(mut block_registry_resource: ResMut<'_, BlockRegistryResource>) {
+    let reload_id = block_registry_resource.definitions.last_reload_id();
+    if reload_id == block_registry_resource.definitions_reload_id {
+        return;
+    }
+    let BlockRegistryResource { registry, definitions, definitions_reload_id } = &mut *block_registry_resource;
+    // On failure, the old definitions are kept. The error has already been
+    // logged.
+    drop(registry.update(&definitions.read()));
+    *definitions_reload_id = reload_id;
+}
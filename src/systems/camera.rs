@@ -0,0 +1,31 @@
+use bevy_ecs::system::*;
+use glam::*;
+use crate::components::core::*;
+use crate::resources::camera::*;
+use crate::resources::wgpu::*;
+
+/// Computes the view-projection matrix of the first [`CameraComponent`] found
+/// and uploads it to [`CameraViewResource`]. The aspect ratio is read from
+/// [`WgpuResource::render_target`] every frame, so the projection follows the
+/// window when it is resized. This must run after the
+/// [`GlobalTransformComponent`]s have been propagated.
+pub fn update_camera_view_system(
+    mut camera_view_resource: ResMut<'_, CameraViewResource>,
+    wgpu_resource: Res<'_, WgpuResource>,
+    cameras: Query<'_, '_, (&CameraComponent, &GlobalTransformComponent)>
+) {
+    let Some((camera, camera_global_transform)) = cameras.iter().next() else {
+        camera_view_resource.clear();
+        return;
+    };
+    let WgpuResource { command_queue, render_target, .. } = &*wgpu_resource;
+    let view = Mat4::from(camera_global_transform.global_transform().inverse());
+    let projection = camera.projection_mode.projection_matrix(aspect_ratio(render_target.width(), render_target.height()), camera.reverse_z);
+    camera_view_resource.update(command_queue, projection * view, camera.reverse_z);
+}
+
+#[expect(clippy::float_arithmetic, reason = "Calculating the aspect ratio requires a division.")]
+#[expect(clippy::cast_precision_loss, reason = "Render targets are never large enough to lose precision.")]
+fn aspect_ratio(width: u32, height: u32) -> f32 {
+    width as f32 / height.max(1) as f32
+}
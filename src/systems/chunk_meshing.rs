@@ -0,0 +1,188 @@
+//! Chunks are meshed one [`crate::voxel_world::ChunkSection`] at a time. Each
+//! section with visible faces gets its own entity with a
+//! [`MeshRendererComponent`] placed at the origin of the section, which is
+//! spawned, updated or despawned whenever the section is marked dirty in the
+//! [`crate::voxel_world::VoxelWorld`].
+//!
+//! Meshing runs in the background: [`spawn_meshing_jobs_system`] starts a job
+//! for each dirty section on the tokio runtime, and
+//! [`apply_finished_meshing_jobs_system`] applies the meshes once they are
+//! done, usually a frame or more later.
+
+use bevy_ecs::change_detection::*;
+use bevy_ecs::query::*;
+use bevy_ecs::system::*;
+use glam::*;
+use log::*;
+use std::collections::*;
+use std::sync::*;
+use tokio::runtime::*;
+use crate::asset::*;
+use crate::background_jobs::*;
+use crate::block_registry::*;
+use crate::block_texture_atlas::*;
+use crate::chunk_mesher::*;
+use crate::components::core::*;
+use crate::material::*;
+use crate::materials::*;
+use crate::resources::block::*;
+use crate::resources::chunk_meshing::*;
+use crate::resources::core::*;
+use crate::resources::voxel_world::*;
+use crate::resources::wgpu::*;
+use crate::voxel_world::*;
+
+pub fn initialize_chunk_meshing_system(
+    wgpu_resource: Res<'_, WgpuResource>,
+    asset_cache_resource: Res<'_, AssetCacheResource>,
+    block_registry_resource: Res<'_, BlockRegistryResource>,
+    mut commands: Commands<'_, '_>
+) {
+    let Ok(runtime) = Handle::try_current() else {
+        error!("Chunks cannot be meshed because there is no tokio runtime to run the meshing jobs on.");
+        return;
+    };
+    let (meshing_table, material) = create_meshing_table_and_material(&wgpu_resource, &asset_cache_resource, &block_registry_resource.registry);
+    commands.insert_resource(ChunkMeshingResource {
+        meshing_table: Arc::new(meshing_table),
+        material,
+        section_entities: HashMap::new(),
+        pending_sections: HashSet::new(),
+        meshing_jobs: BackgroundJobs::with_default_limit(runtime)
+    });
+}
+
+/// Applies the meshes made by the meshing jobs that have finished. Before
+/// that, the jobs of sections whose columns have been unloaded are cancelled
+/// and their entities are despawned, so that they are never brought back by a
+/// late result.
+pub fn apply_finished_meshing_jobs_system(
+    mut chunk_meshing_resource: ResMut<'_, ChunkMeshingResource>,
+    voxel_world_resource: Res<'_, VoxelWorldResource>,
+    mut commands: Commands<'_, '_>
+) {
+    let voxel_world = &voxel_world_resource.voxel_world;
+    let is_loaded = |section_position: &IVec3| voxel_world.column(section_to_column_position(*section_position)).is_some();
+    let ChunkMeshingResource { material, section_entities, pending_sections, meshing_jobs, .. } = &mut *chunk_meshing_resource;
+    meshing_jobs.retain(is_loaded);
+    pending_sections.retain(is_loaded);
+    section_entities.retain(|section_position, entity| {
+        let is_kept = is_loaded(section_position);
+        if !is_kept {
+            commands.entity(*entity).despawn();
+        }
+        is_kept
+    });
+    for (section_position, mesh_result) in meshing_jobs.take_finished() {
+        match mesh_result {
+            Ok(mesh) if mesh.index_count() != 0 => {
+                let components = (
+                    MeshRendererComponent {
+                        material: material.clone(),
+                        mesh: AssetHandle::Dynamic(Arc::new(mesh))
+                    },
+                    TransformComponent {
+                        position: section_to_block_position(section_position).as_vec3(),
+                        ..Default::default()
+                    }
+                );
+                if let Some(entity) = section_entities.get(&section_position) {
+                    commands.entity(*entity).insert(components);
+                }
+                else {
+                    section_entities.insert(section_position, commands.spawn(components).id());
+                }
+            }
+            // The section has no visible faces.
+            Ok(_) => {
+                if let Some(entity) = section_entities.remove(&section_position) {
+                    commands.entity(entity).despawn();
+                }
+            }
+            Err(err) => error!("The section at {section_position} could not be meshed. {err}")
+        }
+    }
+}
+
+/// Queues the sections that have been marked dirty for
+/// [`DirtySectionKind::Mesh`], and spawns meshing jobs for as many of them as
+/// [`ChunkMeshingResource::meshing_jobs`] has free slots for, closest to the
+/// camera first. Each job meshes a [`SectionSnapshot`] taken when it is
+/// spawned. If the block definitions have been hot-reloaded, the texture atlas
+/// is recreated and every section is remeshed.
+pub fn spawn_meshing_jobs_system(
+    mut chunk_meshing_resource: ResMut<'_, ChunkMeshingResource>,
+    mut voxel_world_resource: ResMut<'_, VoxelWorldResource>,
+    block_registry_resource: Res<'_, BlockRegistryResource>,
+    asset_cache_resource: Res<'_, AssetCacheResource>,
+    wgpu_resource: Res<'_, WgpuResource>,
+    cameras: Query<'_, '_, &GlobalTransformComponent, With<CameraComponent>>,
+    mut commands: Commands<'_, '_>
+) {
+    let VoxelWorldResource { voxel_world, meshing_mode } = &mut *voxel_world_resource;
+    if block_registry_resource.is_changed() && !block_registry_resource.is_added() {
+        info!("Remeshing every section because the block definitions have changed.");
+        let (meshing_table, material) = create_meshing_table_and_material(&wgpu_resource, &asset_cache_resource, &block_registry_resource.registry);
+        chunk_meshing_resource.meshing_table = Arc::new(meshing_table);
+        chunk_meshing_resource.material = material;
+        let section_positions: Vec<_> = voxel_world.sections().map(|(section_position, _)| section_position).collect();
+        for section_position in section_positions {
+            voxel_world.mark_section_dirty(section_position);
+        }
+    }
+    let ChunkMeshingResource { meshing_table, section_entities, pending_sections, meshing_jobs, .. } = &mut *chunk_meshing_resource;
+    for section_position in voxel_world.take_dirty_sections(DirtySectionKind::Mesh) {
+        // Sections that only contain air never have visible faces, so they are
+        // handled right away instead of taking up a job.
+        if voxel_world.section(section_position).is_some_and(ChunkSection::is_empty) {
+            pending_sections.remove(&section_position);
+            meshing_jobs.cancel(&section_position);
+            if let Some(entity) = section_entities.remove(&section_position) {
+                commands.entity(entity).despawn();
+            }
+        }
+        else {
+            pending_sections.insert(section_position);
+        }
+    }
+    let free_slots = meshing_jobs.free_slots();
+    if free_slots == 0 || pending_sections.is_empty() {
+        return;
+    }
+    // The camera of the last frame is close enough.
+    let camera_position = cameras.iter().next().map_or(Vec3::ZERO, |camera_global_transform| camera_global_transform.global_transform().translation.into());
+    // Sections that are already being meshed are left pending until their job
+    // has been collected, instead of restarting it, so that sections that keep
+    // getting dirty still get a mesh.
+    let mut closest_sections: Vec<_> = pending_sections.iter().copied().filter(|section_position| !meshing_jobs.contains(section_position)).collect();
+    let by_distance = |a: &IVec3, b: &IVec3| section_distance_squared(*a, camera_position).total_cmp(&section_distance_squared(*b, camera_position));
+    if closest_sections.len() > free_slots {
+        closest_sections.select_nth_unstable_by(free_slots, by_distance);
+        closest_sections.truncate(free_slots);
+    }
+    closest_sections.sort_unstable_by(by_distance);
+    for section_position in closest_sections {
+        pending_sections.remove(&section_position);
+        let Some(snapshot) = SectionSnapshot::new(voxel_world, section_position) else {
+            continue;
+        };
+        let job_meshing_table = Arc::clone(meshing_table);
+        let job_meshing_mode = *meshing_mode;
+        meshing_jobs.spawn(section_position, move || mesh_section(&snapshot, &job_meshing_table, job_meshing_mode));
+    }
+}
+
+/// The squared distance from the center of the section at `section_position`
+/// to `position`.
+#[expect(clippy::float_arithmetic, reason = "The center of a section is half a section away from its origin.")]
+#[expect(clippy::cast_precision_loss, reason = "SECTION_SIZE is small.")]
+fn section_distance_squared(section_position: IVec3, position: Vec3) -> f32 {
+    let section_center = section_to_block_position(section_position).as_vec3() + Vec3::splat(SECTION_SIZE as f32 * 0.5);
+    section_center.distance_squared(position)
+}
+
+fn create_meshing_table_and_material(wgpu_resource: &WgpuResource, asset_cache_resource: &AssetCacheResource, block_registry: &BlockRegistry) -> (BlockMeshingTable, AssetHandle<dyn Material + Send + Sync>) {
+    let atlas = BlockTextureAtlas::new(&asset_cache_resource.asset_cache, block_registry);
+    let material = BlockMaterial::new(&wgpu_resource.device, &wgpu_resource.command_queue, &atlas);
+    (BlockMeshingTable::new(block_registry, &atlas), AssetHandle::Dynamic(Arc::new(material)))
+}
@@ -0,0 +1,74 @@
+//! Columns are loaded and unloaded around the entity with
+//! [`ChunkLoaderComponent`], or the first [`CameraComponent`] if there is
+//! none. Whenever it moves into another column, [`stream_columns_system`]
+//! requests the missing columns within the render distance from
+//! [`crate::systems::terrain_generation`] in spiral order, and unloads the
+//! columns that are too far away. The entities of the sections of unloaded
+//! columns are despawned by [`crate::systems::chunk_meshing`], which frees
+//! their meshes, and unloaded columns that have changed since they were last
+//! saved are saved if there is a [`WorldSaveResource`].
+
+use bevy_ecs::query::*;
+use bevy_ecs::system::*;
+use glam::*;
+use log::*;
+use crate::chunk_streaming::*;
+use crate::components::core::*;
+use crate::resources::chunk_streaming::*;
+use crate::resources::terrain_generation::*;
+use crate::resources::voxel_world::*;
+use crate::resources::world_save::*;
+use crate::voxel_world::*;
+
+/// Updates the loaded columns when the chunk loader has moved into another
+/// column or [`ChunkStreamingResource::distances`] have changed. Nothing
+/// happens if there is neither a chunk loader nor a camera.
+pub fn stream_columns_system(
+    mut chunk_streaming_resource: ResMut<'_, ChunkStreamingResource>,
+    mut terrain_generation_resource: ResMut<'_, TerrainGenerationResource>,
+    mut voxel_world_resource: ResMut<'_, VoxelWorldResource>,
+    mut world_save_resource: Option<ResMut<'_, WorldSaveResource>>,
+    chunk_loaders: Query<'_, '_, &GlobalTransformComponent, With<ChunkLoaderComponent>>,
+    cameras: Query<'_, '_, &GlobalTransformComponent, With<CameraComponent>>
+) {
+    let Some(loader_position) = chunk_loader_position(&chunk_loaders, &cameras) else {
+        return;
+    };
+    let center = block_to_column_position(loader_position.floor().as_ivec3());
+    let distances = chunk_streaming_resource.distances;
+    if chunk_streaming_resource.streamed == Some((center, distances)) {
+        return;
+    }
+    chunk_streaming_resource.streamed = Some((center, distances));
+    let voxel_world = &mut voxel_world_resource.voxel_world;
+    let TerrainGenerationResource { requested_columns, generation_jobs, .. } = &mut *terrain_generation_resource;
+    let far_column_positions: Vec<_> = voxel_world.columns()
+        .map(|(column_position, _)| column_position)
+        .filter(|column_position| distances.should_unload(center, *column_position))
+        .collect();
+    for column_position in &far_column_positions {
+        let is_unsaved = voxel_world.mark_column_saved(*column_position);
+        let Some(column) = voxel_world.remove_column(*column_position) else {
+            continue;
+        };
+        if is_unsaved && let Some(save) = world_save_resource.as_mut() {
+            save.storage.save_column(*column_position, column);
+        }
+    }
+    generation_jobs.retain(|column_position| !distances.should_unload(center, *column_position));
+    requested_columns.clear();
+    requested_columns.extend(spiral_column_positions(center, distances.render_distance)
+        .filter(|column_position| voxel_world.column(*column_position).is_none() && !generation_jobs.contains(column_position)));
+    debug!("Streaming columns around {center}: {} requested, {} unloaded.", requested_columns.len(), far_column_positions.len());
+}
+
+/// The position of the entity with [`ChunkLoaderComponent`], or of the first
+/// [`CameraComponent`] if there is none, as of the last frame, which is close
+/// enough.
+pub fn chunk_loader_position(
+    chunk_loaders: &Query<'_, '_, &GlobalTransformComponent, With<ChunkLoaderComponent>>,
+    cameras: &Query<'_, '_, &GlobalTransformComponent, With<CameraComponent>>
+) -> Option<Vec3> {
+    let loader_global_transform = chunk_loaders.iter().next().or_else(|| cameras.iter().next())?;
+    Some(Vec3::from(loader_global_transform.global_transform().translation))
+}
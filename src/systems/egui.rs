@@ -15,8 +15,8 @@ pub fn initialize_egui_system(
     let egui_renderer_resource = EguiRendererResource::new(&EguiRendererResourceDescriptor {
         device: &wgpu_resource.device,
         msaa_samples: 1,
-        output_color_format: wgpu_resource.surface_config.format,
-        output_depth_format: None,
+        output_color_format: wgpu_resource.render_target.format(),
+        output_depth_format: Some(wgpu_resource.depth_texture.format()),
         window: &winit_resource.window
     });
     commands.insert_resource(egui_renderer_resource);
@@ -30,8 +30,8 @@ pub fn render_egui_system(
     mut egui_state_resource: ResMut<'_, EguiStateResource>,
     egui_renderers: Query<'_, '_, &mut EguiRendererComponent>
 ) {
-    let output_surface_texture_view = wgpu_frame_resource.output_surface_texture.texture.create_view(&TextureViewDescriptor {
-        label: Some("egui-surface-texture-view"),
+    let output_texture_view = wgpu_frame_resource.output_texture.texture().create_view(&TextureViewDescriptor {
+        label: Some("egui-output-texture-view"),
         ..Default::default()
     });
     let mut command_encoder = wgpu_resource.device.create_command_encoder(&CommandEncoderDescriptor {
@@ -40,13 +40,23 @@ pub fn render_egui_system(
     let mut render_pass = command_encoder.begin_render_pass(&RenderPassDescriptor {
         label: Some("egui-render-pass"),
         color_attachments: &[Some(RenderPassColorAttachment {
-            view: &output_surface_texture_view,
+            view: &output_texture_view,
             resolve_target: None,
             ops: Operations {
                 load: LoadOp::Load,
                 store: StoreOp::Store
             }
         })],
+        // egui neither tests against nor writes to the depth, but its pipeline
+        // is created with the depth format, so the attachment must be present.
+        depth_stencil_attachment: Some(RenderPassDepthStencilAttachment {
+            view: wgpu_resource.depth_texture.view(),
+            depth_ops: Some(Operations {
+                load: LoadOp::Load,
+                store: StoreOp::Store
+            }),
+            stencil_ops: None
+        }),
         ..Default::default()
     }).forget_lifetime();
     for mut egui_renderer in egui_renderers {
@@ -0,0 +1,31 @@
+//! Keeps the light of the [`crate::voxel_world::VoxelWorld`] up to date. It
+//! runs after columns are inserted and blocks are set, and before sections are
+//! meshed, so that meshes are always lit with up-to-date light.
+
+use bevy_ecs::change_detection::*;
+use log::*;
+use crate::lighting::*;
+use crate::resources::block::*;
+use crate::resources::lighting::*;
+use crate::resources::voxel_world::*;
+
+/// Updates the light for the columns that have been inserted and the blocks
+/// that have been set since the last frame. If the block definitions have
+/// been hot-reloaded, every column is lit again, since blocks may have become
+/// opaque or started emitting light.
+pub fn update_light_system(
+    mut lighting_resource: ResMut<'_, LightingResource>,
+    mut voxel_world_resource: ResMut<'_, VoxelWorldResource>,
+    block_registry_resource: Res<'_, BlockRegistryResource>
+) {
+    let voxel_world = &mut voxel_world_resource.voxel_world;
+    let mut updates = voxel_world.take_light_updates();
+    if block_registry_resource.is_changed() && !block_registry_resource.is_added() {
+        info!("Relighting every column because the block definitions have changed.");
+        lighting_resource.lighting_table = BlockLightingTable::new(&block_registry_resource.registry);
+        updates.columns.extend(voxel_world.columns().map(|(column_position, _)| column_position));
+    }
+    if !updates.is_empty() {
+        update_light(voxel_world, &lighting_resource.lighting_table, &updates);
+    }
+}
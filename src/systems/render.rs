@@ -0,0 +1,101 @@
+use bevy_ecs::system::*;
+use glam::*;
+use std::collections::*;
+use std::iter::*;
+use wgpu::*;
+use crate::asset::*;
+use crate::camera::*;
+use crate::components::core::*;
+use crate::resources::camera::*;
+use crate::resources::core::*;
+use crate::resources::render::*;
+use crate::resources::wgpu::*;
+
+pub fn initialize_forward_renderer_system(
+    wgpu_resource: Res<'_, WgpuResource>,
+    mut commands: Commands<'_, '_>
+) {
+    let pipeline_cache_resource = PipelineCacheResource::new(&wgpu_resource.device);
+    commands.insert_resource(CameraViewResource::new(&wgpu_resource.device, pipeline_cache_resource.per_view_bind_group_layout()));
+    commands.insert_resource(ForwardRendererResource::new(&wgpu_resource.device, &pipeline_cache_resource));
+    commands.insert_resource(pipeline_cache_resource);
+    commands.init_resource::<GpuMeshCacheResource>();
+}
+
+/// Clears the color of [`WgpuFrameResource::output_texture`] so that the
+/// render systems after this can simply load it.
+pub fn clear_frame_system(
+    wgpu_resource: Res<'_, WgpuResource>,
+    wgpu_frame_resource: Res<'_, WgpuFrameResource>
+) {
+    let output_texture_view = wgpu_frame_resource.output_texture.texture().create_view(&TextureViewDescriptor {
+        label: Some("main-output-texture-view"),
+        ..Default::default()
+    });
+    let mut command_encoder = wgpu_resource.device.create_command_encoder(&CommandEncoderDescriptor {
+        label: Some("main-command-encoder")
+    });
+    let render_pass = command_encoder.begin_render_pass(&RenderPassDescriptor {
+        label: Some("main-render-pass"),
+        color_attachments: &[Some(RenderPassColorAttachment {
+            view: &output_texture_view,
+            resolve_target: None,
+            ops: Operations {
+                load: LoadOp::Clear(Color::default()),
+                store: StoreOp::Store
+            }
+        })],
+        ..Default::default()
+    });
+    drop(render_pass); // Drops render_pass so command_encoder can be moved below.
+    wgpu_resource.command_queue.submit(once(command_encoder.finish()));
+}
+
+/// Renders every [`MeshRendererComponent`] from the point of view set in
+/// [`CameraViewResource`]. Nothing is rendered if there are no cameras.
+#[expect(clippy::too_many_arguments, reason = "bevy_ecs systems take every resource they need as an argument.")]
+pub fn render_mesh_renderers_system(
+    mut forward_renderer_resource: ResMut<'_, ForwardRendererResource>,
+    mut gpu_mesh_cache_resource: ResMut<'_, GpuMeshCacheResource>,
+    mut pipeline_cache_resource: ResMut<'_, PipelineCacheResource>,
+    camera_view_resource: Res<'_, CameraViewResource>,
+    wgpu_resource: Res<'_, WgpuResource>,
+    wgpu_frame_resource: Res<'_, WgpuFrameResource>,
+    asset_cache_resource: Res<'_, AssetCacheResource>,
+    mesh_renderers: Query<'_, '_, (&MeshRendererComponent, &GlobalTransformComponent)>
+) {
+    if camera_view_resource.view_projection().is_none() {
+        return;
+    }
+    let WgpuResource { device, command_queue, render_target, depth_texture } = &*wgpu_resource;
+    let reverse_z = camera_view_resource.reverse_z();
+    let pipeline_formats = PipelineFormats {
+        color_format: render_target.format(),
+        depth_format: depth_texture.format(),
+        depth_compare: depth_compare_function(reverse_z)
+    };
+    // Materials are usually shared by many entities, so each of them is only
+    // prepared once per frame.
+    let mut prepared_materials: HashMap<AssetHandleKey, Option<PreparedMaterial>> = HashMap::new();
+    let mut draw_commands = Vec::new();
+    for (mesh_renderer, global_transform) in &mesh_renderers {
+        let prepared_material = prepared_materials.entry(mesh_renderer.material.key()).or_insert_with(|| {
+            forward_renderer_resource.prepare_material(device, &asset_cache_resource.asset_cache, &mut pipeline_cache_resource, &pipeline_formats, &mesh_renderer.material)
+        });
+        let Some(material) = prepared_material.clone() else {
+            continue;
+        };
+        draw_commands.push(DrawCommand { mesh: mesh_renderer.mesh.clone(), material, model: Mat4::from(*global_transform.global_transform()) });
+    }
+    forward_renderer_resource.retain_material_bind_groups(&prepared_materials.into_keys().collect());
+    let output_texture_view = wgpu_frame_resource.output_texture.texture().create_view(&TextureViewDescriptor {
+        label: Some("forward-renderer-output-texture-view"),
+        ..Default::default()
+    });
+    gpu_mesh_cache_resource.evict_unused();
+    forward_renderer_resource.render(device, command_queue, &pipeline_cache_resource, &mut gpu_mesh_cache_resource, camera_view_resource.per_view_bind_group(), &RenderTargets {
+        color: &output_texture_view,
+        depth: depth_texture.view(),
+        depth_clear_value: depth_clear_value(reverse_z)
+    }, &draw_commands);
+}
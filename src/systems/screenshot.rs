@@ -0,0 +1,56 @@
+//! Screenshots are taken in two steps so that the frame is never stalled.
+//! [`capture_requested_screenshot_system`] copies the rendered frame into a
+//! buffer, and [`save_finished_screenshots_system`] saves it on another thread
+//! once the GPU is done with the copy, which is usually a frame or two later.
+
+use bevy_ecs::system::*;
+use log::*;
+use std::path::*;
+use std::thread;
+use wgpu::*;
+use crate::resources::screenshot::*;
+use crate::resources::wgpu::*;
+use crate::screenshot::*;
+
+pub fn initialize_screenshot_system(mut commands: Commands<'_, '_>) {
+    commands.init_resource::<ScreenshotResource>();
+}
+
+/// Starts copying [`WgpuFrameResource::output_texture`] back from the GPU if a
+/// screenshot has been requested using [`ScreenshotResource::request`]. This
+/// must run after everything has been rendered.
+pub fn capture_requested_screenshot_system(
+    mut screenshot_resource: ResMut<'_, ScreenshotResource>,
+    wgpu_resource: Res<'_, WgpuResource>,
+    wgpu_frame_resource: Res<'_, WgpuFrameResource>
+) {
+    if !screenshot_resource.take_request() {
+        return;
+    }
+    if let Ok(readback) = TextureReadback::new(&wgpu_resource.device, &wgpu_resource.command_queue, wgpu_frame_resource.output_texture.texture()) {
+        screenshot_resource.push_pending_readback(readback);
+    }
+}
+
+/// Saves the screenshots started by [`capture_requested_screenshot_system`]
+/// that have finished being copied back from the GPU to [`SCREENSHOTS_PATH`].
+pub fn save_finished_screenshots_system(
+    mut screenshot_resource: ResMut<'_, ScreenshotResource>,
+    wgpu_resource: Res<'_, WgpuResource>
+) {
+    if !screenshot_resource.has_pending_readbacks() {
+        return;
+    }
+    if let Err(err) = wgpu_resource.device.poll(PollType::Poll) {
+        error!("Could not poll the device for finished screenshots. {err:#?}");
+        return;
+    }
+    for read_result in screenshot_resource.take_finished_readbacks() {
+        let Ok(image) = read_result else {
+            continue;
+        };
+        // Encoding a PNG takes long enough to cause a visible stutter, so it is
+        // done on another thread. Errors are already logged by save_png.
+        thread::spawn(move || save_png(&image, Path::new(SCREENSHOTS_PATH)));
+    }
+}
@@ -0,0 +1,71 @@
+//! Columns are generated in the background, the same way as chunks are meshed
+//! in [`crate::systems::chunk_meshing`]:
+//! [`spawn_terrain_generation_jobs_system`] starts a job for each column
+//! requested by [`crate::systems::chunk_streaming`] on the tokio runtime, and
+//! [`apply_generated_columns_system`] inserts the columns into the
+//! [`crate::voxel_world::VoxelWorld`] once they are done. Columns that have
+//! been saved are loaded by the jobs instead of being generated again.
+
+use bevy_ecs::system::*;
+use log::*;
+use std::sync::*;
+use crate::resources::terrain_generation::*;
+use crate::resources::voxel_world::*;
+use crate::resources::world_save::*;
+use crate::terrain_generator::*;
+use crate::world_storage::*;
+
+/// Inserts the columns loaded or generated by the generation jobs that have
+/// finished into the [`crate::voxel_world::VoxelWorld`], which marks them
+/// dirty so that they get meshed. Blocks that features of generated columns
+/// wrote into other columns are handled by
+/// [`TerrainGenerationResource::pending_writes`]. Loaded columns are marked
+/// saved unless pending writes changed them.
+pub fn apply_generated_columns_system(
+    mut terrain_generation_resource: ResMut<'_, TerrainGenerationResource>,
+    mut voxel_world_resource: ResMut<'_, VoxelWorldResource>
+) {
+    let TerrainGenerationResource { generation_jobs, pending_writes, .. } = &mut *terrain_generation_resource;
+    let voxel_world = &mut voxel_world_resource.voxel_world;
+    for (column_position, provided_column) in generation_jobs.take_finished() {
+        match provided_column {
+            ProvidedColumn::Loaded(column) => {
+                let has_pending_writes = pending_writes.has_writes_into(column_position);
+                pending_writes.insert_generated_column(voxel_world, column_position, GeneratedColumn { column, outside_writes: Vec::new() });
+                if !has_pending_writes {
+                    voxel_world.mark_column_saved(column_position);
+                }
+            }
+            ProvidedColumn::Generated(generated_column) => pending_writes.insert_generated_column(voxel_world, column_position, generated_column)
+        }
+    }
+}
+
+/// Spawns generation jobs for as many requested columns as
+/// [`TerrainGenerationResource::generation_jobs`] has free slots for, in the
+/// order they were requested in. If there is a [`WorldSaveResource`], the jobs
+/// load the columns that have been saved, and only generate the others.
+pub fn spawn_terrain_generation_jobs_system(
+    mut terrain_generation_resource: ResMut<'_, TerrainGenerationResource>,
+    world_save_resource: Option<Res<'_, WorldSaveResource>>
+) {
+    let TerrainGenerationResource { generator, requested_columns, generation_jobs, .. } = &mut *terrain_generation_resource;
+    let storage = world_save_resource.as_deref().map(|save| &save.storage);
+    let free_slots = generation_jobs.free_slots();
+    for column_position in requested_columns.drain(..free_slots.min(requested_columns.len())) {
+        if let Some(column) = storage.and_then(|world_storage| world_storage.unwritten_column(column_position)) {
+            generation_jobs.spawn(column_position, move || ProvidedColumn::Loaded(Arc::unwrap_or_clone(column)));
+            continue;
+        }
+        let job_generator = Arc::clone(generator);
+        let job_loader = storage.map(WorldStorage::column_loader);
+        generation_jobs.spawn(column_position, move || {
+            match job_loader.map(|column_loader| column_loader.load(column_position)) {
+                Some(Ok(Some(column))) => return ProvidedColumn::Loaded(column),
+                Some(Err(err)) => error!("The saved column at {column_position} could not be loaded, so it is generated again. {err}"),
+                Some(Ok(None)) | None => ()
+            }
+            ProvidedColumn::Generated(job_generator.generate(column_position))
+        });
+    }
+}
@@ -0,0 +1,73 @@
+//! The world is saved through [`WorldSaveResource`]: columns are saved when
+//! they are unloaded by [`crate::systems::chunk_streaming`], everything that
+//! has changed is saved every [`AUTOSAVE_INTERVAL`] by [`save_world_system`],
+//! and once more by [`save_world_on_exit_system`] when the app exits.
+
+use bevy_ecs::query::*;
+use bevy_ecs::system::*;
+use std::time::*;
+use crate::components::core::*;
+use crate::resources::terrain_generation::*;
+use crate::resources::voxel_world::*;
+use crate::resources::world_save::*;
+use crate::systems::chunk_streaming::*;
+
+/// How often the loaded columns that have changed are saved, along with the
+/// metadata and the pending block writes.
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Collects the finished writes, autosaves the world every
+/// [`AUTOSAVE_INTERVAL`], and starts writing whatever has been saved.
+pub fn save_world_system(
+    mut world_save_resource: ResMut<'_, WorldSaveResource>,
+    mut voxel_world_resource: ResMut<'_, VoxelWorldResource>,
+    terrain_generation_resource: Option<Res<'_, TerrainGenerationResource>>,
+    chunk_loaders: Query<'_, '_, &GlobalTransformComponent, With<ChunkLoaderComponent>>,
+    cameras: Query<'_, '_, &GlobalTransformComponent, With<CameraComponent>>
+) {
+    world_save_resource.storage.collect_finished_writes();
+    if world_save_resource.last_saved.elapsed() >= AUTOSAVE_INTERVAL {
+        save_world(&mut world_save_resource, &mut voxel_world_resource, terrain_generation_resource.as_deref(), &chunk_loaders, &cameras);
+    }
+    world_save_resource.storage.flush();
+}
+
+/// Saves the world and blocks until it has been written. This should be run
+/// right before exiting.
+pub fn save_world_on_exit_system(
+    mut world_save_resource: ResMut<'_, WorldSaveResource>,
+    mut voxel_world_resource: ResMut<'_, VoxelWorldResource>,
+    terrain_generation_resource: Option<Res<'_, TerrainGenerationResource>>,
+    chunk_loaders: Query<'_, '_, &GlobalTransformComponent, With<ChunkLoaderComponent>>,
+    cameras: Query<'_, '_, &GlobalTransformComponent, With<CameraComponent>>
+) {
+    save_world(&mut world_save_resource, &mut voxel_world_resource, terrain_generation_resource.as_deref(), &chunk_loaders, &cameras);
+    world_save_resource.storage.flush_blocking();
+}
+
+/// Saves the loaded columns that have changed since they were last saved, the
+/// metadata with the current position of the chunk loader, and the pending
+/// block writes.
+fn save_world(
+    world_save_resource: &mut WorldSaveResource,
+    voxel_world_resource: &mut VoxelWorldResource,
+    terrain_generation: Option<&TerrainGenerationResource>,
+    chunk_loaders: &Query<'_, '_, &GlobalTransformComponent, With<ChunkLoaderComponent>>,
+    cameras: &Query<'_, '_, &GlobalTransformComponent, With<CameraComponent>>
+) {
+    let voxel_world = &mut voxel_world_resource.voxel_world;
+    for column_position in voxel_world.take_unsaved_columns() {
+        if let Some(column) = voxel_world.column(column_position) {
+            world_save_resource.storage.save_column(column_position, column.clone());
+        }
+    }
+    if let Some(player_position) = chunk_loader_position(chunk_loaders, cameras) {
+        world_save_resource.metadata.player_position = player_position.into();
+    }
+    let WorldSaveResource { storage, metadata, last_saved } = world_save_resource;
+    storage.save_metadata(metadata);
+    if let Some(terrain_generation_resource) = terrain_generation {
+        storage.save_pending_writes(&terrain_generation_resource.pending_writes);
+    }
+    *last_saved = Instant::now();
+}
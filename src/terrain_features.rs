@@ -0,0 +1,226 @@
+//! Contains the [`TerrainFeature`]s that place structures, such as trees, on
+//! generated terrain.
+
+use glam::*;
+use log::*;
+use std::sync::*;
+use crate::biome::*;
+use crate::block::*;
+use crate::block_registry::*;
+use crate::structure::*;
+use crate::terrain_generator::*;
+use crate::terrain_passes::*;
+use crate::voxel_world::*;
+
+/// The salt that [`BiomeStructureFeature`] gives [`ColumnRandom::for_column`].
+const BIOME_STRUCTURE_SALT: u64 = 0x200;
+
+/// A [`StructureTemplate`] with its blocks looked up.
+#[derive(Debug)]
+pub struct TerrainStructure {
+    /// The offsets of the blocks from the anchor of the template, along with
+    /// the blocks.
+    blocks: Vec<(IVec3, BlockId)>,
+    replace_mode: ReplaceMode
+}
+
+impl TerrainStructure {
+    pub fn from_template(template: &StructureTemplate, block_registry: &BlockRegistry) -> Result<Self, TerrainGeneratorError> {
+        let blocks = template.blocks()
+            .map(|(offset, name)| Ok((offset, block_id(block_registry, name)?)))
+            .collect::<Result<_, TerrainGeneratorError>>()?;
+        Ok(Self { blocks, replace_mode: template.replace_mode })
+    }
+
+    /// Writes the blocks of the structure with its anchor at `anchor`.
+    pub fn place(&self, anchor: IVec3, writer: &mut FeatureWriter<'_>) {
+        for (offset, block) in &self.blocks {
+            writer.write(BlockWrite { block_position: anchor + offset, block: *block, replace_mode: self.replace_mode });
+        }
+    }
+}
+
+/// A [`BiomeFeature`] with its structure and blocks looked up.
+#[derive(Debug)]
+pub struct BiomeStructure {
+    pub biome: BiomeId,
+    /// Shared by every biome that places the same structure.
+    pub structure: Arc<TerrainStructure>,
+    pub per_column: f64,
+    pub ground_blocks: Vec<BlockId>
+}
+
+/// Places the structures listed in the [`BiomeDefinition::features`] of
+/// biomes on their surface. Each structure is tried at random places in every
+/// column as often as its [`BiomeFeature::per_column`] says, and is placed
+/// where the biome is the one it belongs to and the highest block is one of
+/// its ground blocks. Structures near the border of a column reach into its
+/// neighbours through the [`FeatureWriter`].
+pub struct BiomeStructureFeature {
+    seed: u64,
+    structures: Vec<BiomeStructure>
+}
+
+impl BiomeStructureFeature {
+    pub const fn new(seed: u64, structures: Vec<BiomeStructure>) -> Self {
+        Self { seed, structures }
+    }
+
+    /// Looks up the features of every biome in `biome_definitions`.
+    pub fn from_definitions(
+        seed: u64,
+        biome_definitions: &BiomeDefinitions,
+        structure_templates: &StructureTemplates,
+        block_registry: &BlockRegistry
+    ) -> Result<Self, TerrainGeneratorError> {
+        let mut structures = Vec::new();
+        for (biome, _, definition) in biome_definitions.iter() {
+            for feature in &definition.features {
+                let Some(template) = structure_templates.get(&feature.structure) else {
+                    let err = TerrainGeneratorError::MissingStructure(feature.structure.clone());
+                    error!("{err}");
+                    return Err(err);
+                };
+                structures.push(BiomeStructure {
+                    biome,
+                    structure: Arc::new(TerrainStructure::from_template(template, block_registry)?),
+                    per_column: feature.per_column,
+                    ground_blocks: feature.ground_blocks.iter().map(|name| block_id(block_registry, name)).collect::<Result<_, _>>()?
+                });
+            }
+        }
+        Ok(Self::new(seed, structures))
+    }
+}
+
+impl TerrainFeature for BiomeStructureFeature {
+    #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss, reason = "The number of attempts is small and non-negative.")]
+    fn place(&self, column_position: IVec2, writer: &mut FeatureWriter<'_>) {
+        let mut random = ColumnRandom::for_column(self.seed, BIOME_STRUCTURE_SALT, column_position);
+        let origin = column_position * SECTION_SIZE;
+        for biome_structure in &self.structures {
+            let attempt_count = biome_structure.per_column as u32 + u32::from(random.chance(biome_structure.per_column.fract()));
+            for _ in 0..attempt_count {
+                let local_position = IVec2::new(random.range(0..=SECTION_SIZE - 1), random.range(0..=SECTION_SIZE - 1));
+                let column = writer.column();
+                if column.biome(local_position.as_uvec2()) != biome_structure.biome {
+                    continue;
+                }
+                let Some(ground_y) = (COLUMN_MIN_Y..=COLUMN_MAX_Y).rev()
+                    .find(|y| column.get_block(local_position.extend(*y).xzy()).is_some_and(|block| !block.is_air())) else {
+                    continue;
+                };
+                let ground_block = column.get_block(local_position.extend(ground_y).xzy());
+                if !ground_block.is_some_and(|block| biome_structure.ground_blocks.contains(&block)) {
+                    continue;
+                }
+                let anchor = IVec3::new(origin.x + local_position.x, ground_y + 1, origin.y + local_position.y);
+                biome_structure.structure.place(anchor, writer);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::iter;
+
+    const GRASS_BLOCK: BlockId = BlockId(1);
+    const LEAVES: BlockId = BlockId(2);
+    const COLUMN_RADIUS: i32 = 1;
+
+    /// Grass at Y = 0 and air above it.
+    struct FlatGenerator;
+
+    impl TerrainGenerator for FlatGenerator {
+        fn generate_column(&self, _column_position: IVec2) -> ChunkColumn {
+            let mut column = ChunkColumn::default();
+            for z in 0..SECTION_SIZE {
+                for x in 0..SECTION_SIZE {
+                    column.set_block(IVec3::new(x, 0, z), GRASS_BLOCK);
+                }
+            }
+            column
+        }
+    }
+
+    /// A cross of leaves 7 blocks wide, so that it reaches into neighbouring
+    /// columns. Every block of it is the same, so overlapping crosses give
+    /// the same blocks no matter which is placed first.
+    fn test_pipeline() -> TerrainPipeline {
+        let blocks = (-3..=3).flat_map(|offset| [(IVec3::new(offset, 0, 0), LEAVES), (IVec3::new(0, 0, offset), LEAVES)]).collect();
+        let structure = BiomeStructure {
+            biome: BiomeId::default(),
+            structure: Arc::new(TerrainStructure { blocks, replace_mode: ReplaceMode::Air }),
+            per_column: 6.0,
+            ground_blocks: vec![GRASS_BLOCK]
+        };
+        TerrainPipeline::new(FlatGenerator).with_feature(BiomeStructureFeature::new(42, vec![structure]))
+    }
+
+    /// Generates the columns at `column_positions` in order into
+    /// `voxel_world`, and returns the number of writes that crossed into other
+    /// columns.
+    fn generate_in_order(
+        pipeline: &TerrainPipeline,
+        voxel_world: &mut VoxelWorld,
+        pending_writes: &mut PendingBlockWrites,
+        column_positions: impl Iterator<Item = IVec2>
+    ) -> usize {
+        let mut outside_write_count = 0;
+        for column_position in column_positions {
+            let generated_column = pipeline.generate(column_position);
+            outside_write_count += generated_column.outside_writes.len();
+            pending_writes.insert_generated_column(voxel_world, column_position, generated_column);
+        }
+        outside_write_count
+    }
+
+    /// The blocks right above the ground of every column within
+    /// [`COLUMN_RADIUS`] of the origin.
+    fn blocks_above_ground(voxel_world: &VoxelWorld) -> Vec<Option<BlockId>> {
+        let block_range = -COLUMN_RADIUS * SECTION_SIZE..(COLUMN_RADIUS + 1) * SECTION_SIZE;
+        block_range.clone()
+            .flat_map(|z| block_range.clone().map(move |x| IVec3::new(x, 1, z)))
+            .map(|block_position| voxel_world.get_block(block_position))
+            .collect()
+    }
+
+    fn column_positions() -> Vec<IVec2> {
+        (-COLUMN_RADIUS..=COLUMN_RADIUS)
+            .flat_map(|z| (-COLUMN_RADIUS..=COLUMN_RADIUS).map(move |x| IVec2::new(x, z)))
+            .collect()
+    }
+
+    #[test]
+    fn structures_crossing_borders_do_not_depend_on_generation_order() {
+        let pipeline = test_pipeline();
+        let mut forwards_world = VoxelWorld::default();
+        let outside_write_count = generate_in_order(&pipeline, &mut forwards_world, &mut PendingBlockWrites::default(), column_positions().into_iter());
+        let mut backwards_world = VoxelWorld::default();
+        generate_in_order(&pipeline, &mut backwards_world, &mut PendingBlockWrites::default(), column_positions().into_iter().rev());
+        let forwards_blocks = blocks_above_ground(&forwards_world);
+        assert!(outside_write_count > 0, "Some structures should cross the borders of their columns.");
+        assert!(forwards_blocks.contains(&Some(LEAVES)), "Some structures should be placed.");
+        assert_eq!(forwards_blocks, blocks_above_ground(&backwards_world), "The blocks should be the same no matter which column is generated first.");
+    }
+
+    #[test]
+    fn saved_columns_get_the_structures_of_neighbours_generated_while_unloaded() {
+        let pipeline = test_pipeline();
+        let mut expected_world = VoxelWorld::default();
+        generate_in_order(&pipeline, &mut expected_world, &mut PendingBlockWrites::default(), column_positions().into_iter());
+        let saved_column_position = IVec2::X;
+        let mut voxel_world = VoxelWorld::default();
+        let mut pending_writes = PendingBlockWrites::default();
+        generate_in_order(&pipeline, &mut voxel_world, &mut pending_writes, iter::once(saved_column_position));
+        let saved_column = voxel_world.remove_column(saved_column_position).expect("The column should have been generated.");
+        generate_in_order(&pipeline, &mut voxel_world, &mut pending_writes,
+            column_positions().into_iter().filter(|column_position| *column_position != saved_column_position));
+        assert!(pending_writes.has_writes_into(saved_column_position), "The neighbours should have written into the saved column.");
+        pending_writes.insert_generated_column(&mut voxel_world, saved_column_position, GeneratedColumn { column: saved_column, outside_writes: Vec::new() });
+        assert_eq!(blocks_above_ground(&voxel_world), blocks_above_ground(&expected_world),
+            "Loading a saved column should give the same blocks as if it had never been unloaded.");
+    }
+}
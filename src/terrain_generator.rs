@@ -0,0 +1,648 @@
+//! Contains [`TerrainGenerator`], which decides the blocks and biomes of newly
+//! created [`ChunkColumn`]s, and [`LayeredNoiseTerrainGenerator`], the default
+//! one.
+
+use glam::*;
+use log::*;
+use noise::*;
+use std::collections::*;
+use thiserror::*;
+use crate::biome::*;
+use crate::block::*;
+use crate::block_registry::*;
+use crate::structure::*;
+use crate::voxel_world::*;
+
+/// The Y of the surface of oceans and lakes.
+pub const SEA_LEVEL: i32 = 0;
+
+/// Generates the blocks of [`ChunkColumn`]s. Implementations must be
+/// deterministic: the column generated at a column position may only depend on
+/// that position and on what the generator was created with, such as the
+/// world seed. The same column then comes out identically no matter when, in
+/// which order or on which thread it is generated, which is what lets columns
+/// be generated in background jobs and generated again after being unloaded.
+pub trait TerrainGenerator: Send + Sync {
+    fn generate_column(&self, column_position: IVec2) -> ChunkColumn;
+}
+
+/// A step that changes columns after a [`TerrainGenerator`] has shaped them,
+/// such as carving caves or placing ores. Like generators, passes must be
+/// deterministic: what a pass does to a column may only depend on the column
+/// position, on the blocks the column already has and on what the pass was
+/// created with. A pass that affects several columns, such as a cave that
+/// crosses borders, must therefore work out the whole feature from scratch in
+/// every column it reaches, and only change the part inside that column.
+pub trait TerrainPass: Send + Sync {
+    fn apply(&self, column_position: IVec2, column: &mut ChunkColumn);
+}
+
+/// A block that a [`TerrainFeature`] places.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BlockWrite {
+    pub block_position: IVec3,
+    pub block: BlockId,
+    pub replace_mode: ReplaceMode
+}
+
+impl BlockWrite {
+    /// Returns `true` if [`Self::replace_mode`] allows replacing `block`.
+    pub const fn can_replace(&self, block: BlockId) -> bool {
+        match self.replace_mode {
+            ReplaceMode::Air => block.is_air(),
+            ReplaceMode::Any => true
+        }
+    }
+
+    /// Applies the write to `column`, which must be the column at
+    /// `column_position` that contains [`Self::block_position`].
+    pub fn apply_to_column(&self, column_position: IVec2, column: &mut ChunkColumn) {
+        let origin = column_position * SECTION_SIZE;
+        let position = self.block_position - IVec3::new(origin.x, 0, origin.y);
+        if column.get_block(position).is_some_and(|block| self.can_replace(block)) {
+            column.set_block(position, self.block);
+        }
+    }
+
+    /// Applies the write to `voxel_world`, where the column containing
+    /// [`Self::block_position`] must be loaded.
+    pub fn apply_to_world(&self, voxel_world: &mut VoxelWorld) {
+        if voxel_world.get_block(self.block_position).is_some_and(|block| self.can_replace(block)) {
+            drop(voxel_world.set_block(self.block_position, self.block));
+        }
+    }
+}
+
+/// Lets a [`TerrainFeature`] write blocks anywhere in the world while it is
+/// placed in a column. The writes inside the column are applied right away,
+/// and the others are collected so that they can be applied to the columns
+/// they belong to once those are generated.
+pub struct FeatureWriter<'a> {
+    column_position: IVec2,
+    column: &'a mut ChunkColumn,
+    outside_writes: Vec<BlockWrite>
+}
+
+impl FeatureWriter<'_> {
+    /// The column that the feature is placed in, with the writes inside it
+    /// applied.
+    pub const fn column(&self) -> &ChunkColumn {
+        self.column
+    }
+
+    pub fn write(&mut self, write: BlockWrite) {
+        if block_to_column_position(write.block_position) == self.column_position {
+            write.apply_to_column(self.column_position, self.column);
+        }
+        else {
+            self.outside_writes.push(write);
+        }
+    }
+}
+
+/// A step that places features such as trees and other structures after every
+/// [`TerrainPass`] has run. Unlike passes, features can write blocks outside
+/// the column they are placed in through a [`FeatureWriter`], which is what
+/// lets them cross the borders of columns. Features must be deterministic in
+/// the same way as passes.
+pub trait TerrainFeature: Send + Sync {
+    fn place(&self, column_position: IVec2, writer: &mut FeatureWriter<'_>);
+}
+
+/// A column made by [`TerrainPipeline`], along with the blocks that its
+/// features wrote outside of it.
+pub struct GeneratedColumn {
+    pub column: ChunkColumn,
+    pub outside_writes: Vec<BlockWrite>
+}
+
+/// The blocks that features wrote into columns that were not loaded at the
+/// time, keyed by the column positions of those columns. They are applied
+/// once, the next time those columns are inserted, whether they are generated
+/// or loaded from a save, so they must be saved along with the world.
+#[derive(Default)]
+pub struct PendingBlockWrites {
+    writes: HashMap<IVec2, Vec<BlockWrite>>
+}
+
+impl PendingBlockWrites {
+    pub fn from_writes(writes: impl IntoIterator<Item = BlockWrite>) -> Self {
+        let mut pending_writes = Self::default();
+        for write in writes {
+            pending_writes.writes.entry(block_to_column_position(write.block_position)).or_default().push(write);
+        }
+        pending_writes
+    }
+
+    /// Iterates over every pending write in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = &BlockWrite> {
+        self.writes.values().flatten()
+    }
+
+    /// Returns `true` if some writes are waiting for the column at
+    /// `column_position` to be inserted.
+    pub fn has_writes_into(&self, column_position: IVec2) -> bool {
+        self.writes.contains_key(&column_position)
+    }
+
+    /// Inserts a column generated by [`TerrainPipeline`] or loaded from a save
+    /// into `voxel_world`. The writes that are waiting for it are applied to it
+    /// first. Its own outside writes are applied to `voxel_world` if the
+    /// columns they belong to are loaded, and are kept until those columns are
+    /// inserted otherwise.
+    pub fn insert_generated_column(&mut self, voxel_world: &mut VoxelWorld, column_position: IVec2, generated_column: GeneratedColumn) {
+        let GeneratedColumn { mut column, outside_writes } = generated_column;
+        for write in self.writes.remove(&column_position).into_iter().flatten() {
+            write.apply_to_column(column_position, &mut column);
+        }
+        voxel_world.insert_column(column_position, column);
+        for write in outside_writes {
+            let write_column_position = block_to_column_position(write.block_position);
+            if voxel_world.column(write_column_position).is_some() {
+                write.apply_to_world(voxel_world);
+            }
+            else {
+                self.writes.entry(write_column_position).or_default().push(write);
+            }
+        }
+    }
+}
+
+/// Generates columns with a [`TerrainGenerator`], runs [`TerrainPass`]es on
+/// them and then places [`TerrainFeature`]s in them, each in the order they
+/// were added. New kinds of decoration are added by implementing
+/// [`TerrainPass`] or [`TerrainFeature`], without changing the generator that
+/// shapes the terrain.
+pub struct TerrainPipeline {
+    base: Box<dyn TerrainGenerator>,
+    passes: Vec<Box<dyn TerrainPass>>,
+    features: Vec<Box<dyn TerrainFeature>>
+}
+
+impl TerrainPipeline {
+    pub fn new(base: impl TerrainGenerator + 'static) -> Self {
+        Self { base: Box::new(base), passes: Vec::new(), features: Vec::new() }
+    }
+
+    /// Adds `pass` to run after the passes that have already been added.
+    pub fn with_pass(mut self, pass: impl TerrainPass + 'static) -> Self {
+        self.passes.push(Box::new(pass));
+        self
+    }
+
+    /// Adds `feature` to be placed after the features that have already been
+    /// added.
+    pub fn with_feature(mut self, feature: impl TerrainFeature + 'static) -> Self {
+        self.features.push(Box::new(feature));
+        self
+    }
+
+    pub fn generate(&self, column_position: IVec2) -> GeneratedColumn {
+        let mut column = self.base.generate_column(column_position);
+        for pass in &self.passes {
+            pass.apply(column_position, &mut column);
+        }
+        let mut writer = FeatureWriter { column_position, column: &mut column, outside_writes: Vec::new() };
+        for feature in &self.features {
+            feature.place(column_position, &mut writer);
+        }
+        let outside_writes = writer.outside_writes;
+        GeneratedColumn { column, outside_writes }
+    }
+}
+
+/// The blocks that [`LayeredNoiseTerrainGenerator`] builds terrain out of
+/// everywhere, no matter the biome.
+#[derive(Clone, Copy, Debug)]
+pub struct TerrainBlocks {
+    pub stone: BlockId,
+    pub water: BlockId,
+    pub bedrock: BlockId
+}
+
+impl TerrainBlocks {
+    /// Looks the blocks up by their names in [`BlockName::DEFAULT_NAMESPACE`].
+    pub fn from_registry(block_registry: &BlockRegistry) -> Result<Self, TerrainGeneratorError> {
+        let block = |path: &str| block_id(block_registry, &BlockName::new(BlockName::DEFAULT_NAMESPACE, path)?);
+        Ok(Self {
+            stone: block("stone")?,
+            water: block("water")?,
+            bedrock: block("bedrock")?
+        })
+    }
+}
+
+/// A [`BiomeDefinition`] with its blocks looked up, as used by
+/// [`LayeredNoiseTerrainGenerator`].
+#[derive(Clone, Copy, Debug)]
+pub struct TerrainBiome {
+    pub id: BiomeId,
+    /// The temperature and humidity of the biome.
+    pub climate: DVec2,
+    pub surface_block: BlockId,
+    pub filler_block: BlockId,
+    pub filler_depth: i32,
+    pub shore_block: BlockId,
+    pub height_offset: f64,
+    pub height_scale: f64
+}
+
+impl TerrainBiome {
+    /// Converts every definition in `biome_definitions`, in order of their
+    /// IDs.
+    pub fn from_definitions(biome_definitions: &BiomeDefinitions, block_registry: &BlockRegistry) -> Result<Vec<Self>, TerrainGeneratorError> {
+        biome_definitions.iter()
+            .map(|(id, _, definition)| Ok(Self {
+                id,
+                climate: DVec2::new(definition.temperature, definition.humidity),
+                surface_block: block_id(block_registry, &definition.surface_block)?,
+                filler_block: block_id(block_registry, &definition.filler_block)?,
+                filler_depth: i32::from(definition.filler_depth),
+                shore_block: block_id(block_registry, &definition.shore_block)?,
+                height_offset: definition.height_offset,
+                height_scale: definition.height_scale
+            }))
+            .collect()
+    }
+}
+
+/// Looks up a block that terrain generation needs, logging an error if it is
+/// not defined.
+pub fn block_id(block_registry: &BlockRegistry, name: &BlockName) -> Result<BlockId, TerrainGeneratorError> {
+    block_registry.id(name).ok_or_else(|| {
+        let err = TerrainGeneratorError::MissingBlock(name.clone());
+        error!("{err}");
+        err
+    })
+}
+
+/// Shapes terrain with a few layers of noise, similarly to Minecraft:
+/// - Continentalness decides how far inland a place is, which sets the base
+///   height of the surface, from deep oceans to high plateaus.
+/// - Erosion decides how rugged a place is. Low erosion stretches the height
+///   and the 3D density into mountains, while high erosion flattens them into
+///   plains.
+/// - 3D density is added on top of the base height, so that the surface can
+///   fold over itself into overhangs and arches instead of being a heightmap.
+///
+/// - Temperature and humidity pick the [`TerrainBiome`] whose climate is the
+///   closest. The biome decides the blocks covering the surface, and the
+///   height modifiers of the biomes with a similar climate are blended, so
+///   that the terrain does not jump at the borders between biomes.
+///
+/// Solid terrain is stone, covered by the surface and filler blocks of the
+/// biome above sea level and by its shore block on beaches and sea floors.
+/// Water fills everything else up to [`SEA_LEVEL`], and the lowest layer is
+/// bedrock.
+pub struct LayeredNoiseTerrainGenerator {
+    blocks: TerrainBlocks,
+    /// Never empty.
+    biomes: Vec<TerrainBiome>,
+    continentalness: Fbm<Perlin>,
+    erosion: Fbm<Perlin>,
+    density: Fbm<Perlin>,
+    temperature: Fbm<Perlin>,
+    humidity: Fbm<Perlin>
+}
+
+/// How much farther from a place in climate space than the closest biome a
+/// biome can be while still affecting the height of the terrain there. The
+/// larger this is, the wider the transitions between biomes are.
+const BIOME_BLEND_DISTANCE: f64 = 0.2;
+
+/// What [`LayeredNoiseTerrainGenerator`] decides for each column of blocks
+/// before placing them.
+struct ColumnShape<'a> {
+    /// The height of the surface, before the 3D density is added.
+    height: f64,
+    /// How many blocks the 3D density can move the surface up or down by.
+    density_amplitude: f64,
+    biome: &'a TerrainBiome
+}
+
+/// Pairs of continentalness and the base height of the surface relative to
+/// [`SEA_LEVEL`] at that continentalness, ordered by continentalness. The
+/// height is interpolated linearly between them.
+const CONTINENTALNESS_HEIGHTS: [(f64, f64); 7] = [
+    (-1.0, -50.0),
+    (-0.35, -24.0),
+    (-0.15, -4.0),
+    (-0.05, 2.0),
+    (0.2, 12.0),
+    (0.5, 40.0),
+    (1.0, 90.0)
+];
+
+impl LayeredNoiseTerrainGenerator {
+    /// Returns [`TerrainGeneratorError::NoBiomes`] if `biomes` is empty.
+    #[expect(clippy::float_arithmetic, reason = "The frequencies are written as the reciprocals of the wavelengths in blocks.")]
+    pub fn new(seed: u64, blocks: TerrainBlocks, biomes: Vec<TerrainBiome>) -> Result<Self, TerrainGeneratorError> {
+        if biomes.is_empty() {
+            let err = TerrainGeneratorError::NoBiomes;
+            error!("{err}");
+            return Err(err);
+        }
+        Ok(Self {
+            blocks,
+            biomes,
+            continentalness: Fbm::<Perlin>::new(layer_seed(seed, 0)).set_octaves(5).set_frequency(1.0 / 600.0),
+            erosion: Fbm::<Perlin>::new(layer_seed(seed, 1)).set_octaves(3).set_frequency(1.0 / 300.0),
+            density: Fbm::<Perlin>::new(layer_seed(seed, 2)).set_octaves(3).set_frequency(1.0 / 48.0),
+            temperature: Fbm::<Perlin>::new(layer_seed(seed, 3)).set_octaves(3).set_frequency(1.0 / 700.0),
+            humidity: Fbm::<Perlin>::new(layer_seed(seed, 4)).set_octaves(3).set_frequency(1.0 / 500.0)
+        })
+    }
+
+    #[expect(clippy::float_arithmetic, reason = "The noise is mapped to heights.")]
+    fn shape(&self, x: f64, z: f64) -> ColumnShape<'_> {
+        let climate = DVec2::new(self.temperature.get([x, z]), self.humidity.get([x, z]));
+        let (biome, height_offset, height_scale) = self.blend_biomes(climate);
+        let continentalness = self.continentalness.get([x, z]);
+        let ruggedness = ((1.0 - self.erosion.get([x, z])) * 0.5).clamp(0.0, 1.0);
+        let base_height = continentalness_to_height(continentalness);
+        // Only land is stretched into mountains, so that oceans keep their
+        // depth.
+        let height = if base_height > 0.0 { base_height * ruggedness.mul_add(1.2, 0.5) * height_scale } else { base_height };
+        ColumnShape {
+            height: f64::from(SEA_LEVEL) + height + height_offset,
+            density_amplitude: ruggedness.mul_add(22.0, 2.0) * height_scale,
+            biome
+        }
+    }
+
+    /// Returns the biome closest to `climate`, along with the height offset
+    /// and scale blended from every biome within [`BIOME_BLEND_DISTANCE`] of
+    /// the closest one. Weights fall to zero at that distance, so the blended
+    /// values change smoothly as `climate` crosses from one biome to another.
+    #[expect(clippy::float_arithmetic, reason = "The height modifiers are a weighted average.")]
+    fn blend_biomes(&self, climate: DVec2) -> (&TerrainBiome, f64, f64) {
+        let distances: Vec<_> = self.biomes.iter().map(|biome| biome.climate.distance(climate)).collect();
+        let (closest_index, closest_distance) = distances.iter().copied().enumerate()
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .unwrap_or_default();
+        let mut total_weight = 0.0;
+        let mut height_offset = 0.0;
+        let mut height_scale = 0.0;
+        for (biome, distance) in self.biomes.iter().zip(distances) {
+            let weight = (1.0 - (distance - closest_distance) / BIOME_BLEND_DISTANCE).max(0.0).powi(2);
+            total_weight += weight;
+            height_offset = weight.mul_add(biome.height_offset, height_offset);
+            height_scale = weight.mul_add(biome.height_scale, height_scale);
+        }
+        // The closest biome always has a weight of 1, so the total is never 0.
+        (&self.biomes[closest_index], height_offset / total_weight, height_scale / total_weight)
+    }
+}
+
+impl TerrainGenerator for LayeredNoiseTerrainGenerator {
+    #[expect(clippy::float_arithmetic, reason = "Density is calculated from noise.")]
+    #[expect(clippy::cast_possible_truncation, reason = "The heights are clamped to the column.")]
+    fn generate_column(&self, column_position: IVec2) -> ChunkColumn {
+        let TerrainBlocks { stone, water, bedrock } = self.blocks;
+        let mut column = ChunkColumn::default();
+        let origin = column_position * SECTION_SIZE;
+        for local_z in 0..SECTION_SIZE {
+            for local_x in 0..SECTION_SIZE {
+                let (x, z) = (f64::from(origin.x + local_x), f64::from(origin.y + local_z));
+                let ColumnShape { height, density_amplitude, biome } = self.shape(x, z);
+                column.set_biome(UVec2::new(local_x.cast_unsigned(), local_z.cast_unsigned()), biome.id);
+                // Below this range everything is solid and above it everything
+                // is air, no matter what the density noise is, so it is only
+                // sampled inside it.
+                let min_density_y = ((height - density_amplitude).floor() as i32).clamp(COLUMN_MIN_Y, COLUMN_MAX_Y);
+                let max_density_y = ((height + density_amplitude).ceil() as i32).clamp(COLUMN_MIN_Y, COLUMN_MAX_Y);
+                // Going from the top down tells how deep each block is under
+                // the surface.
+                let mut depth = 0;
+                let mut surface_y = SEA_LEVEL;
+                for y in (COLUMN_MIN_Y..=max_density_y.max(SEA_LEVEL)).rev() {
+                    let is_solid = y < min_density_y
+                        || (y <= max_density_y && self.density.get([x, f64::from(y), z]).mul_add(density_amplitude, height - f64::from(y)) > 0.0);
+                    let block = if y == COLUMN_MIN_Y {
+                        bedrock
+                    }
+                    else if !is_solid {
+                        depth = 0;
+                        if y > SEA_LEVEL {
+                            continue;
+                        }
+                        water
+                    }
+                    else {
+                        if depth == 0 {
+                            surface_y = y;
+                        }
+                        depth += 1;
+                        let is_beach_or_sea_floor = surface_y <= SEA_LEVEL + 1;
+                        if depth > biome.filler_depth + 1 {
+                            stone
+                        }
+                        else if is_beach_or_sea_floor {
+                            biome.shore_block
+                        }
+                        else if depth == 1 {
+                            biome.surface_block
+                        }
+                        else {
+                            biome.filler_block
+                        }
+                    };
+                    column.set_block(IVec3::new(local_x, y, local_z), block);
+                }
+            }
+        }
+        column
+    }
+}
+
+/// Interpolates [`CONTINENTALNESS_HEIGHTS`].
+#[expect(clippy::float_arithmetic, reason = "The height is interpolated linearly.")]
+fn continentalness_to_height(continentalness: f64) -> f64 {
+    let clamped = continentalness.clamp(CONTINENTALNESS_HEIGHTS[0].0, CONTINENTALNESS_HEIGHTS[CONTINENTALNESS_HEIGHTS.len() - 1].0);
+    for &[(start, start_height), (end, end_height)] in CONTINENTALNESS_HEIGHTS.array_windows() {
+        if clamped <= end {
+            let t = (clamped - start) / (end - start);
+            return t.mul_add(end_height - start_height, start_height);
+        }
+    }
+    CONTINENTALNESS_HEIGHTS[CONTINENTALNESS_HEIGHTS.len() - 1].1
+}
+
+/// Derives an independent seed for each layer of noise from the world seed,
+/// so that the layers do not line up with each other. [`TerrainPass`]es pick
+/// their own layers, which must be different from the ones used here, which
+/// are 0 to 4.
+pub const fn layer_seed(seed: u64, layer: u64) -> u32 {
+    (mix_seed(seed.wrapping_add((layer + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15))) >> 32) as u32
+}
+
+/// The finalizer of `SplitMix64`, which spreads every bit of `value` over the
+/// result, so that similar inputs give unrelated seeds.
+pub const fn mix_seed(value: u64) -> u64 {
+    let mut hash = value;
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    hash ^ (hash >> 31)
+}
+
+#[derive(Debug, Error)]
+pub enum TerrainGeneratorError {
+    #[error("The block {0}, which terrain generation needs, is not defined.")]
+    MissingBlock(BlockName),
+
+    #[error("Terrain cannot be generated without any biomes.")]
+    NoBiomes,
+
+    #[error("The structure {0}, which terrain generation needs, is not defined.")]
+    MissingStructure(String),
+
+    #[error(transparent)]
+    InvalidBlockName(#[from] BlockNameParseError)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use super::*;
+
+    fn test_blocks() -> TerrainBlocks {
+        TerrainBlocks {
+            stone: BlockId(1),
+            water: BlockId(2),
+            bedrock: BlockId(3)
+        }
+    }
+
+    /// A cold, flat biome and a warm one that is raised and twice as high, so
+    /// that the border between them would be a cliff without blending.
+    fn test_biomes() -> Vec<TerrainBiome> {
+        vec![
+            TerrainBiome {
+                id: BiomeId(0),
+                climate: DVec2::new(-0.3, 0.0),
+                surface_block: BlockId(4),
+                filler_block: BlockId(5),
+                filler_depth: 3,
+                shore_block: BlockId(6),
+                height_offset: 0.0,
+                height_scale: 1.0
+            },
+            TerrainBiome {
+                id: BiomeId(1),
+                climate: DVec2::new(0.3, 0.0),
+                surface_block: BlockId(7),
+                filler_block: BlockId(8),
+                filler_depth: 3,
+                shore_block: BlockId(9),
+                height_offset: 20.0,
+                height_scale: 2.0
+            }
+        ]
+    }
+
+    fn test_generator(seed: u64) -> LayeredNoiseTerrainGenerator {
+        LayeredNoiseTerrainGenerator::new(seed, test_blocks(), test_biomes()).expect("The generator should be created.")
+    }
+
+    /// Every block of `column` from the bottom to the top, followed by the
+    /// biome of every column of blocks.
+    fn column_blocks(column: &ChunkColumn) -> Vec<BlockId> {
+        let mut blocks = Vec::new();
+        for y in COLUMN_MIN_Y..=COLUMN_MAX_Y {
+            for z in 0..SECTION_SIZE {
+                for x in 0..SECTION_SIZE {
+                    blocks.push(column.get_block(IVec3::new(x, y, z)).expect("The position should be inside the column."));
+                }
+            }
+        }
+        for z in 0..SECTION_SIZE.cast_unsigned() {
+            for x in 0..SECTION_SIZE.cast_unsigned() {
+                blocks.push(BlockId(column.biome(UVec2::new(x, z)).0.into()));
+            }
+        }
+        blocks
+    }
+
+    const COLUMN_POSITIONS: [IVec2; 4] = [IVec2::new(0, 0), IVec2::new(-3, 7), IVec2::new(40, -12), IVec2::new(-1000, 1000)];
+
+    #[test]
+    fn same_seed_and_position_give_the_same_blocks() {
+        let first_generator = test_generator(12345);
+        let second_generator = test_generator(12345);
+        for column_position in COLUMN_POSITIONS {
+            let first = column_blocks(&first_generator.generate_column(column_position));
+            // Generating other columns in between must not affect the result.
+            drop(second_generator.generate_column(column_position + IVec2::ONE));
+            let second = column_blocks(&second_generator.generate_column(column_position));
+            assert_eq!(first, second, "The column at {column_position} should be the same.");
+        }
+    }
+
+    #[test]
+    fn different_seeds_give_different_blocks() {
+        let first = column_blocks(&test_generator(1).generate_column(IVec2::ZERO));
+        let second = column_blocks(&test_generator(2).generate_column(IVec2::ZERO));
+        assert_ne!(first, second, "Different seeds should give different terrain.");
+    }
+
+    #[test]
+    fn results_do_not_depend_on_the_thread() {
+        let generator = test_generator(987_654_321);
+        let on_this_thread: Vec<_> = COLUMN_POSITIONS.iter().map(|column_position| column_blocks(&generator.generate_column(*column_position))).collect();
+        let on_other_threads: Vec<_> = thread::scope(|scope| {
+            // Generates in reverse order to also make sure the order does not
+            // matter.
+            #[expect(clippy::needless_collect, reason = "Every thread must be spawned before any of them is joined so that they run at the same time.")]
+            let handles: Vec<_> = COLUMN_POSITIONS.iter().rev()
+                .map(|column_position| scope.spawn(|| column_blocks(&generator.generate_column(*column_position))))
+                .collect();
+            handles.into_iter().rev().map(|handle| handle.join().expect("The generating thread should not panic.")).collect()
+        });
+        assert_eq!(on_this_thread, on_other_threads, "Columns generated on other threads should be the same.");
+    }
+
+    #[test]
+    fn terrain_has_bedrock_and_water_at_sea_level() {
+        let blocks = test_blocks();
+        let generator = test_generator(42);
+        let column = generator.generate_column(IVec2::ZERO);
+        for z in 0..SECTION_SIZE {
+            for x in 0..SECTION_SIZE {
+                assert_eq!(column.get_block(IVec3::new(x, COLUMN_MIN_Y, z)), Some(blocks.bedrock), "The bottom of the column should be bedrock.");
+                assert!(column.get_block(IVec3::new(x, SEA_LEVEL, z)).is_some_and(|block| !block.is_air()), "Nothing at sea level should be air.");
+            }
+        }
+    }
+
+    #[test]
+    fn biome_borders_are_blended() {
+        let generator = test_generator(7);
+        let shapes: Vec<_> = (0..4096).map(|x| generator.shape(f64::from(x), 0.0)).collect();
+        let mut border_count = 0;
+        for (x, [previous, next]) in shapes.array_windows().enumerate() {
+            if previous.biome.id != next.biome.id {
+                border_count += 1;
+                // Without blending, the height offsets alone would differ by
+                // 20 blocks.
+                let step = next.height - previous.height;
+                assert!(step.abs() < 2.0, "The height should not jump by {step} blocks at the border at X = {x}.");
+            }
+        }
+        assert!(border_count > 0, "There should be a border between the biomes along the line.");
+    }
+
+    #[test]
+    fn columns_store_their_biomes() {
+        let generator = test_generator(7);
+        for column_position in COLUMN_POSITIONS {
+            let column = generator.generate_column(column_position);
+            let origin = column_position * SECTION_SIZE;
+            for z in 0..SECTION_SIZE {
+                for x in 0..SECTION_SIZE {
+                    let expected = generator.shape(f64::from(origin.x + x), f64::from(origin.y + z)).biome.id;
+                    assert_eq!(column.biome(UVec2::new(x.cast_unsigned(), z.cast_unsigned())), expected, "The biome at ({x}, {z}) in the column at {column_position} should be stored.");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,441 @@
+//! Contains the [`TerrainPass`]es that decorate the terrain after its shape
+//! has been generated: worm caves, cheese caves and ore veins.
+
+use glam::*;
+use noise::*;
+use std::f64::consts::*;
+use std::ops::*;
+use crate::block::*;
+use crate::block_registry::*;
+use crate::ore::*;
+use crate::terrain_generator::*;
+use crate::voxel_world::*;
+
+/// A small, fast pseudorandom number generator based on `SplitMix64`, for the
+/// random choices that passes make. Every column gets its own generator from
+/// [`Self::for_column`], so what a pass decides for a column never depends on
+/// which columns were generated before it.
+pub struct ColumnRandom {
+    state: u64
+}
+
+impl ColumnRandom {
+    /// * `salt`: Different for every pass and every kind of feature, so that
+    ///   they do not all make the same choices in the same column.
+    pub fn for_column(seed: u64, salt: u64, column_position: IVec2) -> Self {
+        let position = (u64::from(column_position.x.cast_unsigned()) << 32) | u64::from(column_position.y.cast_unsigned());
+        Self { state: mix_seed(seed ^ mix_seed(salt ^ mix_seed(position))) }
+    }
+
+    pub const fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        mix_seed(self.state)
+    }
+
+    /// Returns a number from 0 inclusive to 1 exclusive.
+    #[expect(clippy::float_arithmetic, reason = "The top 53 bits are scaled into the range.")]
+    #[expect(clippy::cast_precision_loss, reason = "53 bits fit in an f64 exactly.")]
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1_u64 << 53) as f64
+    }
+
+    /// Returns a number in `range`, which must not be empty.
+    #[expect(clippy::cast_possible_truncation, reason = "The remainder is less than the length of the range, which fits in a u32.")]
+    pub fn range(&mut self, range: RangeInclusive<i32>) -> i32 {
+        let length = u64::from(range.end().abs_diff(*range.start())) + 1;
+        range.start().wrapping_add_unsigned((self.next_u64() % length) as u32)
+    }
+
+    /// Returns `true` with a chance of `probability`.
+    pub fn chance(&mut self, probability: f64) -> bool {
+        self.next_f64() < probability
+    }
+}
+
+/// The salts that the passes in this module give [`ColumnRandom::for_column`].
+const WORM_CAVE_SALT: u64 = 1;
+/// The salt of each ore is this plus its index.
+const ORE_VEIN_SALT: u64 = 0x100;
+
+/// The noise layers, as given to [`layer_seed`], of the passes in this module.
+const WORM_CAVE_STEERING_LAYER: u64 = 5;
+const CHEESE_CAVE_LAYER: u64 = 6;
+
+/// Turns the block at `position`, in the same coordinates as
+/// [`ChunkColumn::get_block`], into air, unless it is water or bedrock. Blocks
+/// right under water are also kept, so that caves never open into the bottom
+/// of seas and lakes.
+fn carve(blocks: TerrainBlocks, column: &mut ChunkColumn, position: IVec3) {
+    let Some(block) = column.get_block(position) else {
+        return;
+    };
+    if block.is_air() || block == blocks.water || block == blocks.bedrock || column.get_block(position + IVec3::Y) == Some(blocks.water) {
+        return;
+    }
+    column.set_block(position, BlockId::AIR);
+}
+
+/// Carves the blocks whose centers are inside the sphere at `center`, which
+/// is in block positions, with `radius`, but only the ones inside the column
+/// at `column_position`.
+#[expect(clippy::float_arithmetic, reason = "The sphere is tested against the centers of blocks.")]
+fn carve_sphere(blocks: TerrainBlocks, column_position: IVec2, column: &mut ChunkColumn, center: DVec3, radius: f64) {
+    let origin = column_position * SECTION_SIZE;
+    let min = (center - radius).floor().as_ivec3().max(IVec3::new(origin.x, COLUMN_MIN_Y, origin.y));
+    let max = (center + radius).floor().as_ivec3().min(IVec3::new(origin.x + SECTION_SIZE - 1, COLUMN_MAX_Y, origin.y + SECTION_SIZE - 1));
+    for y in min.y..=max.y {
+        for z in min.z..=max.z {
+            for x in min.x..=max.x {
+                if (IVec3::new(x, y, z).as_dvec3() + 0.5).distance_squared(center) < radius * radius {
+                    carve(blocks, column, IVec3::new(x - origin.x, y, z - origin.y));
+                }
+            }
+        }
+    }
+}
+
+/// The chance that a worm starts in a column.
+const WORM_CAVE_CHANCE: f64 = 0.12;
+
+/// How many steps, each one block long, worms take.
+const WORM_CAVE_LENGTH: RangeInclusive<i32> = 64..=112;
+
+/// The Ys that worms start at.
+const WORM_CAVE_START_Y: RangeInclusive<i32> = COLUMN_MIN_Y + 8..=40;
+
+/// The smallest and largest radius of the middle of worms. They are thinner
+/// at their ends.
+const WORM_CAVE_MIN_RADIUS: f64 = 1.5;
+const WORM_CAVE_MAX_RADIUS: f64 = 3.5;
+
+/// How far the steering noise moves along per step. Smaller values make
+/// worms turn more gradually.
+const WORM_CAVE_STEERING_FREQUENCY: f64 = 0.04;
+
+/// How many radians a worm turns left or right per step at most.
+const WORM_CAVE_MAX_TURN: f64 = 0.3;
+
+/// How many radians a worm points up or down at most.
+const WORM_CAVE_MAX_PITCH: f64 = 0.8;
+
+/// How many columns away from the one it starts in a worm can reach. Worms
+/// move one block per step, so this covers the longest worm and its radius.
+const WORM_CAVE_REACH_IN_COLUMNS: i32 = 8;
+
+/// Carves long, winding tunnels known as Perlin worms. A worm starts at a
+/// random place in some columns and moves forwards one block at a time,
+/// turning left, right, up and down as Perlin noise sampled along its path
+/// says, and carves a sphere around every point it passes. Since worms cross
+/// the borders of columns, every column works out every worm that can reach
+/// it and carves the part of it that is inside the column.
+pub struct WormCavePass {
+    seed: u64,
+    blocks: TerrainBlocks,
+    steering: Perlin
+}
+
+impl WormCavePass {
+    pub fn new(seed: u64, blocks: TerrainBlocks) -> Self {
+        Self { seed, blocks, steering: Perlin::new(layer_seed(seed, WORM_CAVE_STEERING_LAYER)) }
+    }
+
+    /// Carves the part inside the column at `column_position` of the worm
+    /// that starts in the column at `start_column_position`.
+    #[expect(clippy::float_arithmetic, reason = "Worms move in the direction given by the steering noise.")]
+    fn carve_worm(&self, random: &mut ColumnRandom, start_column_position: IVec2, column_position: IVec2, column: &mut ChunkColumn) {
+        let start = start_column_position * SECTION_SIZE;
+        let mut position = IVec3::new(
+            start.x + random.range(0..=SECTION_SIZE - 1),
+            random.range(WORM_CAVE_START_Y),
+            start.y + random.range(0..=SECTION_SIZE - 1)
+        ).as_dvec3() + 0.5;
+        let length = random.range(WORM_CAVE_LENGTH);
+        let radius = random.next_f64().mul_add(WORM_CAVE_MAX_RADIUS - WORM_CAVE_MIN_RADIUS, WORM_CAVE_MIN_RADIUS);
+        let mut yaw = random.next_f64() * TAU;
+        // Where each worm samples the steering noise, so that different worms
+        // turn differently.
+        let steering_offset = random.next_f64() * 10_000.0;
+        for step in 0..length {
+            let steering_position = f64::from(step) * WORM_CAVE_STEERING_FREQUENCY;
+            yaw = self.steering.get([steering_offset, steering_position]).mul_add(WORM_CAVE_MAX_TURN, yaw);
+            let pitch = self.steering.get([steering_offset + 1000.0, steering_position]) * WORM_CAVE_MAX_PITCH;
+            position += DVec3::new(pitch.cos() * yaw.cos(), pitch.sin(), pitch.cos() * yaw.sin());
+            let step_radius = radius * (PI * f64::from(step) / f64::from(length)).sin().mul_add(0.6, 0.4);
+            carve_sphere(self.blocks, column_position, column, position, step_radius);
+        }
+    }
+}
+
+impl TerrainPass for WormCavePass {
+    fn apply(&self, column_position: IVec2, column: &mut ChunkColumn) {
+        for z in -WORM_CAVE_REACH_IN_COLUMNS..=WORM_CAVE_REACH_IN_COLUMNS {
+            for x in -WORM_CAVE_REACH_IN_COLUMNS..=WORM_CAVE_REACH_IN_COLUMNS {
+                let start_column_position = column_position + IVec2::new(x, z);
+                let mut random = ColumnRandom::for_column(self.seed, WORM_CAVE_SALT, start_column_position);
+                if random.chance(WORM_CAVE_CHANCE) {
+                    self.carve_worm(&mut random, start_column_position, column_position, column);
+                }
+            }
+        }
+    }
+}
+
+/// The highest Y that cheese caves reach.
+const CHEESE_CAVE_MAX_Y: i32 = -4;
+
+/// How far the noise must be above 0 for a block to be carved, deep enough
+/// under [`CHEESE_CAVE_MAX_Y`].
+const CHEESE_CAVE_THRESHOLD: f64 = 0.3;
+
+/// Over how many blocks under [`CHEESE_CAVE_MAX_Y`] the threshold rises from
+/// [`CHEESE_CAVE_THRESHOLD`] to 1, which the noise never reaches, so that
+/// caverns get smaller towards the top instead of being cut off flat.
+const CHEESE_CAVE_FADE_HEIGHT: i32 = 24;
+
+/// Carves large caverns, known as cheese caves, wherever 3D noise is above a
+/// threshold.
+pub struct CheeseCavePass {
+    blocks: TerrainBlocks,
+    noise: Fbm<Perlin>
+}
+
+impl CheeseCavePass {
+    #[expect(clippy::float_arithmetic, reason = "The frequency is written as the reciprocal of the wavelength in blocks.")]
+    pub fn new(seed: u64, blocks: TerrainBlocks) -> Self {
+        Self { blocks, noise: Fbm::<Perlin>::new(layer_seed(seed, CHEESE_CAVE_LAYER)).set_octaves(2).set_frequency(1.0 / 40.0) }
+    }
+}
+
+impl TerrainPass for CheeseCavePass {
+    #[expect(clippy::float_arithmetic, reason = "The threshold is interpolated.")]
+    fn apply(&self, column_position: IVec2, column: &mut ChunkColumn) {
+        let origin = column_position * SECTION_SIZE;
+        for y in COLUMN_MIN_Y..=CHEESE_CAVE_MAX_Y {
+            let fade = (f64::from(y - (CHEESE_CAVE_MAX_Y - CHEESE_CAVE_FADE_HEIGHT)) / f64::from(CHEESE_CAVE_FADE_HEIGHT)).clamp(0.0, 1.0);
+            let threshold = fade.mul_add(1.0 - CHEESE_CAVE_THRESHOLD, CHEESE_CAVE_THRESHOLD);
+            for z in 0..SECTION_SIZE {
+                for x in 0..SECTION_SIZE {
+                    let position = IVec3::new(x, y, z);
+                    if column.get_block(position).is_none_or(BlockId::is_air) {
+                        continue;
+                    }
+                    // Y is sampled at twice the frequency, which flattens the
+                    // caverns.
+                    let noise_position = [f64::from(origin.x + x), f64::from(y) * 2.0, f64::from(origin.y + z)];
+                    if self.noise.get(noise_position) > threshold {
+                        carve(self.blocks, column, position);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// An [`OreDefinition`] with its block looked up, as used by [`OreVeinPass`].
+#[derive(Clone, Copy, Debug)]
+pub struct TerrainOre {
+    pub block: BlockId,
+    pub min_y: i32,
+    pub max_y: i32,
+    pub veins_per_column: f64,
+    pub vein_size: u8
+}
+
+impl TerrainOre {
+    /// Converts every definition in `ore_definitions`, in order of their
+    /// names.
+    pub fn from_definitions(ore_definitions: &OreDefinitions, block_registry: &BlockRegistry) -> Result<Vec<Self>, TerrainGeneratorError> {
+        ore_definitions.iter()
+            .map(|(_, definition)| Ok(Self {
+                block: block_id(block_registry, &definition.block)?,
+                min_y: definition.min_y,
+                max_y: definition.max_y,
+                veins_per_column: definition.veins_per_column,
+                vein_size: definition.vein_size
+            }))
+            .collect()
+    }
+}
+
+/// The directions that veins grow in, one of which is picked at random for
+/// every block.
+const VEIN_STEPS: [IVec3; 6] = [IVec3::X, IVec3::NEG_X, IVec3::Y, IVec3::NEG_Y, IVec3::Z, IVec3::NEG_Z];
+
+/// Places veins of ores in stone. A vein starts at a random place in a column
+/// and grows by a random walk, so it can reach into the columns next to it;
+/// [`MAX_VEIN_SIZE`] keeps it from going any further.
+pub struct OreVeinPass {
+    seed: u64,
+    stone: BlockId,
+    ores: Vec<TerrainOre>
+}
+
+impl OreVeinPass {
+    /// Ores are placed in the order of `ores`.
+    pub const fn new(seed: u64, stone: BlockId, ores: Vec<TerrainOre>) -> Self {
+        Self { seed, stone, ores }
+    }
+
+    /// Places the part inside the column at `column_position` of the veins of
+    /// `ore` that start in the column at `start_column_position`.
+    #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss, reason = "The number of veins is small and non-negative.")]
+    fn place_veins(&self, random: &mut ColumnRandom, ore: &TerrainOre, start_column_position: IVec2, column_position: IVec2, column: &mut ChunkColumn) {
+        let vein_count = ore.veins_per_column as u32 + u32::from(random.chance(ore.veins_per_column.fract()));
+        let start = start_column_position * SECTION_SIZE;
+        let origin = column_position * SECTION_SIZE;
+        for _ in 0..vein_count {
+            let mut position = IVec3::new(
+                start.x + random.range(0..=SECTION_SIZE - 1),
+                random.range(ore.min_y..=ore.max_y),
+                start.y + random.range(0..=SECTION_SIZE - 1)
+            );
+            for _ in 0..ore.vein_size {
+                let local_position = position - IVec3::new(origin.x, 0, origin.y);
+                let is_inside_column = (0..SECTION_SIZE).contains(&local_position.x) && (0..SECTION_SIZE).contains(&local_position.z);
+                if is_inside_column && column.get_block(local_position) == Some(self.stone) {
+                    column.set_block(local_position, ore.block);
+                }
+                // Every step is taken even outside the column, so that the
+                // vein has the same shape no matter which column places it.
+                #[expect(clippy::cast_sign_loss, reason = "The index is from 0 to 5.")]
+                let step = VEIN_STEPS[random.range(0..=5) as usize];
+                position += step;
+            }
+        }
+    }
+}
+
+impl TerrainPass for OreVeinPass {
+    fn apply(&self, column_position: IVec2, column: &mut ChunkColumn) {
+        for (salt, ore) in (ORE_VEIN_SALT..).zip(&self.ores) {
+            for z in -1..=1 {
+                for x in -1..=1 {
+                    let start_column_position = column_position + IVec2::new(x, z);
+                    let mut random = ColumnRandom::for_column(self.seed, salt, start_column_position);
+                    self.place_veins(&mut random, ore, start_column_position, column_position, column);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLOCKS: TerrainBlocks = TerrainBlocks { stone: BlockId(1), water: BlockId(2), bedrock: BlockId(3) };
+    const ORE: BlockId = BlockId(4);
+    const WATER_MIN_Y: i32 = 0;
+    const WATER_MAX_Y: i32 = 3;
+
+    /// Bedrock at the bottom, stone up to [`WATER_MIN_Y`] and water above it,
+    /// so that every pass has something to work on.
+    struct FlatGenerator;
+
+    impl TerrainGenerator for FlatGenerator {
+        fn generate_column(&self, _column_position: IVec2) -> ChunkColumn {
+            let mut column = ChunkColumn::default();
+            for z in 0..SECTION_SIZE {
+                for x in 0..SECTION_SIZE {
+                    for y in COLUMN_MIN_Y..=WATER_MAX_Y {
+                        let block = match y {
+                            COLUMN_MIN_Y => BLOCKS.bedrock,
+                            WATER_MIN_Y.. => BLOCKS.water,
+                            _ => BLOCKS.stone
+                        };
+                        column.set_block(IVec3::new(x, y, z), block);
+                    }
+                }
+            }
+            column
+        }
+    }
+
+    fn test_ore() -> TerrainOre {
+        TerrainOre { block: ORE, min_y: -20, max_y: -10, veins_per_column: 4.5, vein_size: 8 }
+    }
+
+    fn test_pipeline(seed: u64) -> TerrainPipeline {
+        TerrainPipeline::new(FlatGenerator)
+            .with_pass(CheeseCavePass::new(seed, BLOCKS))
+            .with_pass(WormCavePass::new(seed, BLOCKS))
+            .with_pass(OreVeinPass::new(seed, BLOCKS.stone, vec![test_ore()]))
+    }
+
+    /// Every block of `column` along with its position, from the bottom to
+    /// the top.
+    fn column_blocks(column: &ChunkColumn) -> Vec<(IVec3, BlockId)> {
+        let mut blocks = Vec::new();
+        for y in COLUMN_MIN_Y..=COLUMN_MAX_Y {
+            for z in 0..SECTION_SIZE {
+                for x in 0..SECTION_SIZE {
+                    let position = IVec3::new(x, y, z);
+                    blocks.push((position, column.get_block(position).expect("The position should be inside the column.")));
+                }
+            }
+        }
+        blocks
+    }
+
+    const COLUMN_POSITIONS: [IVec2; 4] = [IVec2::new(0, 0), IVec2::new(1, 0), IVec2::new(-5, 9), IVec2::new(300, -300)];
+
+    #[test]
+    fn passes_are_deterministic_per_seed_and_column() {
+        let first_pipeline = test_pipeline(123);
+        let second_pipeline = test_pipeline(123);
+        for column_position in COLUMN_POSITIONS {
+            let first = column_blocks(&first_pipeline.generate(column_position).column);
+            // Generating other columns in between must not affect the result.
+            drop(second_pipeline.generate(column_position - IVec2::ONE));
+            let second = column_blocks(&second_pipeline.generate(column_position).column);
+            assert_eq!(first, second, "The column at {column_position} should be the same.");
+        }
+        let first = column_blocks(&test_pipeline(1).generate(IVec2::ZERO).column);
+        let second = column_blocks(&test_pipeline(2).generate(IVec2::ZERO).column);
+        assert_ne!(first, second, "Different seeds should give different caves and ores.");
+    }
+
+    #[test]
+    fn caves_keep_water_and_bedrock() {
+        let pipeline = test_pipeline(456);
+        let mut carved_block_count = 0;
+        for column_position in COLUMN_POSITIONS {
+            for (position, block) in column_blocks(&pipeline.generate(column_position).column) {
+                match position.y {
+                    COLUMN_MIN_Y => assert_eq!(block, BLOCKS.bedrock, "Bedrock at {position} should not be carved."),
+                    WATER_MIN_Y..=WATER_MAX_Y => assert_eq!(block, BLOCKS.water, "Water at {position} should not be carved."),
+                    // The blocks right under the water keep it from leaking
+                    // into caves.
+                    y if y == WATER_MIN_Y - 1 => assert_ne!(block, BlockId::AIR, "The block under the water at {position} should not be carved."),
+                    y if y < WATER_MIN_Y => carved_block_count += usize::from(block.is_air()),
+                    _ => ()
+                }
+            }
+        }
+        assert!(carved_block_count > 0, "Some caves should be carved.");
+    }
+
+    #[test]
+    fn ore_veins_replace_stone_near_their_height_range() {
+        let pass = OreVeinPass::new(789, BLOCKS.stone, vec![test_ore()]);
+        let mut ore_count = 0;
+        for column_position in COLUMN_POSITIONS {
+            let mut column = FlatGenerator.generate_column(column_position);
+            pass.apply(column_position, &mut column);
+            let original_blocks = column_blocks(&FlatGenerator.generate_column(column_position));
+            for ((position, block), (_, original_block)) in column_blocks(&column).into_iter().zip(original_blocks) {
+                if block == ORE {
+                    assert_eq!(original_block, BLOCKS.stone, "Only stone should be replaced, but the block at {position} was.");
+                    ore_count += 1;
+                    let ore = test_ore();
+                    let max_distance = i32::from(ore.vein_size) - 1;
+                    assert!((ore.min_y - max_distance..=ore.max_y + max_distance).contains(&position.y), "The ore at {position} is too far from its height range.");
+                }
+                else {
+                    assert_eq!(block, original_block, "Only stone should be replaced, but the block at {position} was.");
+                }
+            }
+        }
+        assert!(ore_count > 0, "Some ore should be placed.");
+    }
+}
@@ -0,0 +1,827 @@
+//! Contains the block storage of the world. The world is divided into
+//! [`ChunkColumn`]s, each of which is a vertical stack of
+//! [`COLUMN_SECTION_COUNT`] [`ChunkSection`]s of 16×16×16 blocks.
+//!
+//! Three kinds of positions are used throughout:
+//! - Block positions are [`IVec3`]s in blocks.
+//! - Section positions are [`IVec3`]s in sections, so the section at (1, 2, 3)
+//!   contains the blocks from (16, 32, 48) to (31, 47, 63).
+//! - Column positions are [`IVec2`]s of the X and Z of section positions.
+//!
+//! Besides its blocks, every section stores the sky light and block light of
+//! each of them in [`LightArray`]s. They are not saved, and are filled in by
+//! [`crate::lighting`] after columns are inserted or blocks are set.
+
+use glam::*;
+use std::collections::*;
+use std::mem;
+use strum::*;
+use thiserror::*;
+use crate::biome::*;
+use crate::block::*;
+
+/// The length of each side of a [`ChunkSection`] in blocks.
+pub const SECTION_SIZE: i32 = 16;
+
+/// The number of blocks in a [`ChunkSection`].
+pub const SECTION_VOLUME: usize = 4096;
+
+/// The number of columns of blocks in a [`ChunkColumn`], each of which has its
+/// own [`BiomeId`].
+pub const COLUMN_AREA: usize = 256;
+
+/// The Y of the section position of the lowest [`ChunkSection`] of every
+/// [`ChunkColumn`].
+pub const COLUMN_MIN_SECTION_Y: i32 = -4;
+
+/// The number of [`ChunkSection`]s in every [`ChunkColumn`]. Together with
+/// [`COLUMN_MIN_SECTION_Y`], this makes the world span from Y = -64 to Y = 319
+/// in blocks.
+pub const COLUMN_SECTION_COUNT: usize = 24;
+
+/// The Y of the lowest block of every [`ChunkColumn`].
+pub const COLUMN_MIN_Y: i32 = COLUMN_MIN_SECTION_Y * SECTION_SIZE;
+
+/// The Y of the highest block of every [`ChunkColumn`].
+#[expect(clippy::cast_possible_truncation, clippy::cast_possible_wrap, reason = "COLUMN_SECTION_COUNT is small.")]
+pub const COLUMN_MAX_Y: i32 = COLUMN_MIN_Y + COLUMN_SECTION_COUNT as i32 * SECTION_SIZE - 1;
+
+/// Stores [`SECTION_VOLUME`] [`BlockId`]s compactly. Instead of storing every
+/// [`BlockId`] directly, each entry is an index into a palette of the distinct
+/// [`BlockId`]s in the container, packed into as few bits as the size of the
+/// palette allows. A container that only contains a single kind of block, which
+/// is the case for most sections high in the sky or deep underground, uses no
+/// storage besides its palette.
+#[derive(Clone, Debug)]
+pub struct PalettedContainer {
+    /// Only grows when a new kind of block is set. Use [`Self::compact`] to
+    /// remove [`BlockId`]s that are no longer used.
+    palette: Vec<BlockId>,
+    /// 0 if [`Self::palette`] only has a single entry.
+    bits_per_entry: u32,
+    /// The palette indices, packed so that an entry never spans two `u64`s.
+    /// Empty if [`Self::bits_per_entry`] is 0.
+    data: Vec<u64>
+}
+
+impl PalettedContainer {
+    /// Creates a container filled with `block`.
+    pub fn new(block: BlockId) -> Self {
+        Self { palette: vec![block], bits_per_entry: 0, data: Vec::new() }
+    }
+
+    /// * `index`: Must be less than [`SECTION_VOLUME`].
+    pub fn get(&self, index: usize) -> BlockId {
+        self.palette[self.palette_index(index)]
+    }
+
+    /// Returns the [`BlockId`] that was replaced.
+    ///
+    /// * `index`: Must be less than [`SECTION_VOLUME`].
+    pub fn set(&mut self, index: usize, block: BlockId) -> BlockId {
+        let palette_index = if let Some(palette_index) = self.palette.iter().position(|palette_block| *palette_block == block) {
+            palette_index
+        }
+        else {
+            self.palette.push(block);
+            let required_bits_per_entry = bits_per_entry_for_palette_length(self.palette.len());
+            if required_bits_per_entry > self.bits_per_entry {
+                self.repack(required_bits_per_entry);
+            }
+            self.palette.len() - 1
+        };
+        let old_block = self.get(index);
+        self.set_palette_index(index, palette_index);
+        old_block
+    }
+
+    /// The distinct [`BlockId`]s in this container. This may contain
+    /// [`BlockId`]s that are no longer used until [`Self::compact`] is
+    /// called.
+    pub fn palette(&self) -> &[BlockId] {
+        &self.palette
+    }
+
+    pub const fn bits_per_entry(&self) -> u32 {
+        self.bits_per_entry
+    }
+
+    /// The packed palette indices. See [`Self::from_raw_parts`].
+    pub fn data(&self) -> &[u64] {
+        &self.data
+    }
+
+    /// Creates a container from the parts returned by [`Self::palette`],
+    /// [`Self::bits_per_entry`] and [`Self::data`], such as when loading a
+    /// saved section. Returns [`None`] if they are inconsistent, including if
+    /// any entry is not an index into `palette`.
+    pub fn from_raw_parts(palette: Vec<BlockId>, bits_per_entry: u32, data: Vec<u64>) -> Option<Self> {
+        if palette.is_empty()
+            || bits_per_entry < bits_per_entry_for_palette_length(palette.len())
+            || bits_per_entry > u16::BITS
+            || data.len() != data_length(bits_per_entry) {
+            return None;
+        }
+        let container = Self { palette, bits_per_entry, data };
+        (0..SECTION_VOLUME).all(|index| container.palette_index(index) < container.palette.len()).then_some(container)
+    }
+
+    /// Removes the [`BlockId`]s that are no longer used from the palette and
+    /// uses as few bits per entry as possible.
+    pub fn compact(&mut self) {
+        let mut used_palette_indices = vec![false; self.palette.len()];
+        for index in 0..SECTION_VOLUME {
+            used_palette_indices[self.palette_index(index)] = true;
+        }
+        if used_palette_indices.iter().all(|is_used| *is_used) {
+            return;
+        }
+        // Maps the old palette indices to the new ones.
+        let mut new_palette_indices = vec![0; self.palette.len()];
+        let mut palette = Vec::new();
+        for (old_palette_index, block) in self.palette.iter().enumerate() {
+            if used_palette_indices[old_palette_index] {
+                new_palette_indices[old_palette_index] = palette.len();
+                palette.push(*block);
+            }
+        }
+        let bits_per_entry = bits_per_entry_for_palette_length(palette.len());
+        let mut compacted = Self { palette, bits_per_entry, data: vec![0; data_length(bits_per_entry)] };
+        for index in 0..SECTION_VOLUME {
+            compacted.set_palette_index(index, new_palette_indices[self.palette_index(index)]);
+        }
+        *self = compacted;
+    }
+
+    fn palette_index(&self, index: usize) -> usize {
+        if self.bits_per_entry == 0 {
+            return 0;
+        }
+        let (data_index, bit_offset) = self.entry_location(index);
+        let mask = (1_u64 << self.bits_per_entry) - 1;
+        #[expect(clippy::cast_possible_truncation, reason = "bits_per_entry is at most 16, so the masked value fits in usize.")]
+        let palette_index = ((self.data[data_index] >> bit_offset) & mask) as usize;
+        palette_index
+    }
+
+    fn set_palette_index(&mut self, index: usize, palette_index: usize) {
+        if self.bits_per_entry == 0 {
+            return;
+        }
+        let (data_index, bit_offset) = self.entry_location(index);
+        let mask = (1_u64 << self.bits_per_entry) - 1;
+        let entry = &mut self.data[data_index];
+        *entry = (*entry & !(mask << bit_offset)) | ((palette_index as u64) << bit_offset);
+    }
+
+    /// Returns the index into [`Self::data`] and the bit offset inside it of
+    /// the entry at `index`.
+    #[expect(clippy::integer_division, reason = "The remainder is the position inside the u64.")]
+    const fn entry_location(&self, index: usize) -> (usize, u32) {
+        let entries_per_u64 = (u64::BITS / self.bits_per_entry) as usize;
+        #[expect(clippy::cast_possible_truncation, reason = "The remainder is less than 64.")]
+        let bit_offset = (index % entries_per_u64) as u32 * self.bits_per_entry;
+        (index / entries_per_u64, bit_offset)
+    }
+
+    /// Repacks [`Self::data`] with more bits per entry.
+    fn repack(&mut self, bits_per_entry: u32) {
+        let mut repacked = Self { palette: mem::take(&mut self.palette), bits_per_entry, data: vec![0; data_length(bits_per_entry)] };
+        for index in 0..SECTION_VOLUME {
+            repacked.set_palette_index(index, self.palette_index(index));
+        }
+        *self = repacked;
+    }
+}
+
+/// The number of bits needed to index a palette of `palette_length` entries.
+const fn bits_per_entry_for_palette_length(palette_length: usize) -> u32 {
+    usize::BITS - (palette_length.saturating_sub(1)).leading_zeros()
+}
+
+/// The number of `u64`s needed to store [`SECTION_VOLUME`] entries.
+#[expect(clippy::integer_division, reason = "Entries never span two u64s, so the leftover bits are unused.")]
+const fn data_length(bits_per_entry: u32) -> usize {
+    if bits_per_entry == 0 {
+        return 0;
+    }
+    let entries_per_u64 = (u64::BITS / bits_per_entry) as usize;
+    SECTION_VOLUME.div_ceil(entries_per_u64)
+}
+
+/// The two kinds of light that every block has a level of.
+#[derive(EnumCount, EnumIter, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(usize)]
+pub enum LightKind {
+    /// Light that comes down from the sky.
+    Sky,
+    /// Light that is emitted by blocks such as glowstone.
+    Block
+}
+
+/// The number of bytes that a [`LightArray`] needs to store a level for every
+/// block, at two levels per byte.
+#[expect(clippy::integer_division, reason = "SECTION_VOLUME is even.")]
+const LIGHT_ARRAY_LENGTH: usize = SECTION_VOLUME / 2;
+
+/// Stores a light level from 0 to [`MAX_LIGHT_LEVEL`] for each of the
+/// [`SECTION_VOLUME`] blocks of a section, packed two to a byte. A section
+/// whose blocks all have the same level, which is the case for most sections
+/// in the sky or deep underground, only stores that level.
+#[derive(Clone, Debug)]
+pub struct LightArray {
+    /// The level of every block while [`Self::levels`] is [`None`].
+    uniform_level: u8,
+    /// The level of the block at an even index is in the low 4 bits of the
+    /// byte at half the index, and the next block is in the high 4 bits.
+    levels: Option<Box<[u8; LIGHT_ARRAY_LENGTH]>>
+}
+
+impl LightArray {
+    /// Creates an array where every block has `level`.
+    pub const fn new(level: u8) -> Self {
+        Self { uniform_level: level, levels: None }
+    }
+
+    /// * `index`: Must be less than [`SECTION_VOLUME`].
+    pub fn get(&self, index: usize) -> u8 {
+        self.levels.as_ref().map_or(self.uniform_level, |levels| (levels[index >> 1] >> ((index & 1) * 4)) & 0xF)
+    }
+
+    /// * `index`: Must be less than [`SECTION_VOLUME`].
+    /// * `level`: Must be at most [`MAX_LIGHT_LEVEL`].
+    pub fn set(&mut self, index: usize, level: u8) {
+        debug_assert!(level <= MAX_LIGHT_LEVEL, "The light level must be at most MAX_LIGHT_LEVEL.");
+        if self.levels.is_none() && level == self.uniform_level {
+            return;
+        }
+        let uniform_level = self.uniform_level;
+        let levels = self.levels.get_or_insert_with(|| Box::new([uniform_level | (uniform_level << 4); LIGHT_ARRAY_LENGTH]));
+        let shift = (index & 1) * 4;
+        levels[index >> 1] = (levels[index >> 1] & !(0xF << shift)) | (level << shift);
+    }
+
+    /// Sets every block to `level`.
+    pub fn fill(&mut self, level: u8) {
+        *self = Self::new(level);
+    }
+
+    /// Returns the level of every block if they all have the same one. It may
+    /// return [`None`] even then, if they were set one by one.
+    pub const fn uniform_level(&self) -> Option<u8> {
+        match self.levels {
+            Some(_) => None,
+            None => Some(self.uniform_level)
+        }
+    }
+}
+
+/// A 16×16×16 cube of blocks.
+#[derive(Clone, Debug)]
+pub struct ChunkSection {
+    blocks: PalettedContainer,
+    /// Kept up to date so that empty sections can be skipped quickly.
+    non_air_block_count: u16,
+    /// Indexed by [`LightKind`]. Both are dark until the section is lit.
+    light: [LightArray; LightKind::COUNT]
+}
+
+impl ChunkSection {
+    pub fn new(blocks: PalettedContainer) -> Self {
+        #[expect(clippy::cast_possible_truncation, reason = "There are at most SECTION_VOLUME blocks, which fits in a u16.")]
+        let non_air_block_count = (0..SECTION_VOLUME).filter(|index| !blocks.get(*index).is_air()).count() as u16;
+        Self { blocks, non_air_block_count, light: [LightArray::new(0), LightArray::new(0)] }
+    }
+
+    /// Returns the [`BlockId`] at `local_position`, whose components must be
+    /// less than [`SECTION_SIZE`].
+    pub fn get_block(&self, local_position: UVec3) -> BlockId {
+        self.blocks.get(local_index(local_position))
+    }
+
+    /// Sets the [`BlockId`] at `local_position`, whose components must be
+    /// less than [`SECTION_SIZE`], and returns the one that was replaced.
+    pub fn set_block(&mut self, local_position: UVec3, block: BlockId) -> BlockId {
+        let old_block = self.blocks.set(local_index(local_position), block);
+        match (old_block.is_air(), block.is_air()) {
+            (true, false) => self.non_air_block_count += 1,
+            (false, true) => self.non_air_block_count -= 1,
+            _ => ()
+        }
+        old_block
+    }
+
+    /// Returns `true` if the section only contains air.
+    pub const fn is_empty(&self) -> bool {
+        self.non_air_block_count == 0
+    }
+
+    pub const fn blocks(&self) -> &PalettedContainer {
+        &self.blocks
+    }
+
+    /// See [`PalettedContainer::compact`].
+    #[cfg_attr(not(test), expect(dead_code, reason = "Nothing compacts the voxel world yet."))]
+    pub fn compact(&mut self) {
+        self.blocks.compact();
+    }
+
+    /// Returns the level of `kind` of light at `local_position`, whose
+    /// components must be less than [`SECTION_SIZE`].
+    pub fn get_light(&self, kind: LightKind, local_position: UVec3) -> u8 {
+        self.light[kind as usize].get(local_index(local_position))
+    }
+
+    pub const fn light(&self, kind: LightKind) -> &LightArray {
+        &self.light[kind as usize]
+    }
+}
+
+impl Default for ChunkSection {
+    #[inline]
+    fn default() -> Self {
+        Self::new(PalettedContainer::new(BlockId::AIR))
+    }
+}
+
+/// The index of `local_position` in a [`PalettedContainer`] or a
+/// [`LightArray`]. X changes the fastest, then Z, then Y.
+pub const fn local_index(local_position: UVec3) -> usize {
+    debug_assert!(local_position.x < SECTION_SIZE as u32 && local_position.y < SECTION_SIZE as u32 && local_position.z < SECTION_SIZE as u32,
+        "The local position must be inside the section.");
+    ((local_position.y as usize * SECTION_SIZE as usize) + local_position.z as usize) * SECTION_SIZE as usize + local_position.x as usize
+}
+
+/// A vertical stack of [`COLUMN_SECTION_COUNT`] [`ChunkSection`]s, which is
+/// the unit that the world is loaded and unloaded in.
+#[derive(Clone, Debug)]
+pub struct ChunkColumn {
+    /// Ordered from the bottom to the top.
+    sections: Vec<ChunkSection>,
+    /// Indexed by [`column_local_index`].
+    biomes: [BiomeId; COLUMN_AREA]
+}
+
+impl ChunkColumn {
+    /// * `section_y`: The Y of the section position.
+    pub fn section(&self, section_y: i32) -> Option<&ChunkSection> {
+        self.sections.get(section_y_to_index(section_y)?)
+    }
+
+    /// * `section_y`: The Y of the section position.
+    pub fn section_mut(&mut self, section_y: i32) -> Option<&mut ChunkSection> {
+        self.sections.get_mut(section_y_to_index(section_y)?)
+    }
+
+    /// * `position`: X and Z are relative to the column and must be less than
+    ///   [`SECTION_SIZE`], and Y is the Y of the block position.
+    ///
+    /// Returns [`None`] if Y is outside the column.
+    pub fn get_block(&self, position: IVec3) -> Option<BlockId> {
+        Some(self.section(position.y >> 4)?.get_block(block_to_local_position(position)))
+    }
+
+    /// Sets the block at `position`, which is the same as in
+    /// [`Self::get_block`], and returns the one that was replaced. Returns
+    /// [`None`] without doing anything if Y is outside the column.
+    pub fn set_block(&mut self, position: IVec3, block: BlockId) -> Option<BlockId> {
+        Some(self.section_mut(position.y >> 4)?.set_block(block_to_local_position(position), block))
+    }
+
+    /// * `local_position`: The X and Z relative to the column, which must be
+    ///   less than [`SECTION_SIZE`].
+    pub const fn biome(&self, local_position: UVec2) -> BiomeId {
+        self.biomes[column_local_index(local_position)]
+    }
+
+    /// Sets the biome of the column of blocks at `local_position`, which is
+    /// the same as in [`Self::biome`].
+    pub const fn set_biome(&mut self, local_position: UVec2, biome: BiomeId) {
+        self.biomes[column_local_index(local_position)] = biome;
+    }
+
+    /// Iterates over the sections from the bottom to the top, along with the Y
+    /// of their section positions.
+    pub fn sections(&self) -> impl Iterator<Item = (i32, &ChunkSection)> {
+        (COLUMN_MIN_SECTION_Y..).zip(&self.sections)
+    }
+}
+
+impl Default for ChunkColumn {
+    /// Creates a column that only contains air, in the biome with the ID 0.
+    #[inline]
+    fn default() -> Self {
+        Self { sections: vec![ChunkSection::default(); COLUMN_SECTION_COUNT], biomes: [BiomeId::default(); COLUMN_AREA] }
+    }
+}
+
+/// The index of `local_position` in [`ChunkColumn::biomes`]. X changes the
+/// fastest, then Z.
+const fn column_local_index(local_position: UVec2) -> usize {
+    debug_assert!(local_position.x < SECTION_SIZE as u32 && local_position.y < SECTION_SIZE as u32,
+        "The local position must be inside the column.");
+    local_position.y as usize * SECTION_SIZE as usize + local_position.x as usize
+}
+
+fn section_y_to_index(section_y: i32) -> Option<usize> {
+    usize::try_from(section_y - COLUMN_MIN_SECTION_Y).ok().filter(|index| *index < COLUMN_SECTION_COUNT)
+}
+
+/// The systems that need to know which [`ChunkSection`]s have changed. Each of
+/// them consumes its own set of dirty sections, so they do not interfere with
+/// each other.
+#[derive(EnumCount, EnumIter, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(usize)]
+pub enum DirtySectionKind {
+    Mesh
+}
+
+/// The changes to a [`VoxelWorld`] that its light has not been updated for
+/// yet. See [`crate::lighting`].
+#[derive(Default)]
+pub struct LightUpdates {
+    /// The column positions of the columns that have been inserted, which
+    /// have to be lit from scratch.
+    pub columns: HashSet<IVec2>,
+    /// The block positions of the blocks that have been set.
+    pub blocks: HashSet<IVec3>
+}
+
+impl LightUpdates {
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty() && self.blocks.is_empty()
+    }
+}
+
+/// All the blocks of the loaded part of the world. See the module
+/// documentation for the coordinate systems used.
+#[derive(Default)]
+pub struct VoxelWorld {
+    columns: HashMap<IVec2, ChunkColumn>,
+    /// The section positions of the sections that have changed, indexed by
+    /// [`DirtySectionKind`].
+    dirty_sections: [HashSet<IVec3>; DirtySectionKind::COUNT],
+    /// The column positions of the columns that have been inserted or have
+    /// had blocks set since they were last saved.
+    unsaved_columns: HashSet<IVec2>,
+    light_updates: LightUpdates
+}
+
+impl VoxelWorld {
+    /// Returns [`None`] if the column containing `block_position` is not
+    /// loaded, or if `block_position` is above or below the world.
+    pub fn get_block(&self, block_position: IVec3) -> Option<BlockId> {
+        let section_position = block_to_section_position(block_position);
+        let section = self.columns.get(&section_to_column_position(section_position))?.section(section_position.y)?;
+        Some(section.get_block(block_to_local_position(block_position)))
+    }
+
+    /// Sets the block at `block_position` and returns the one that was
+    /// replaced. The section containing it is marked dirty, along with the
+    /// neighbouring sections if the block is on the boundary of its section,
+    /// because the faces of their blocks may become visible or hidden.
+    pub fn set_block(&mut self, block_position: IVec3, block: BlockId) -> Result<BlockId, SetBlockError> {
+        let section_position = block_to_section_position(block_position);
+        let column_position = section_to_column_position(section_position);
+        let column = self.columns.get_mut(&column_position).ok_or(SetBlockError::ColumnNotLoaded(column_position))?;
+        let section = column.section_mut(section_position.y).ok_or(SetBlockError::OutOfHeight(block_position.y))?;
+        let local_position = block_to_local_position(block_position);
+        let old_block = section.set_block(local_position, block);
+        if old_block != block {
+            self.unsaved_columns.insert(column_position);
+            self.light_updates.blocks.insert(block_position);
+            self.mark_section_dirty(section_position);
+            for (axis, neighbour_offset) in [IVec3::X, IVec3::Y, IVec3::Z].into_iter().enumerate() {
+                if local_position[axis] == 0 {
+                    self.mark_section_dirty(section_position - neighbour_offset);
+                }
+                if local_position[axis] == SECTION_SIZE as u32 - 1 {
+                    self.mark_section_dirty(section_position + neighbour_offset);
+                }
+            }
+        }
+        Ok(old_block)
+    }
+
+    /// Loads `column` at `column_position`, returning the column that was
+    /// replaced. All its sections and those of its horizontal neighbours are
+    /// marked dirty, since the faces on the boundaries between them may now
+    /// be hidden, and the column is marked unsaved.
+    pub fn insert_column(&mut self, column_position: IVec2, column: ChunkColumn) -> Option<ChunkColumn> {
+        let old_column = self.columns.insert(column_position, column);
+        self.unsaved_columns.insert(column_position);
+        self.light_updates.columns.insert(column_position);
+        self.mark_column_and_neighbours_dirty(column_position);
+        old_column
+    }
+
+    /// Unloads the column at `column_position`. Its dirty sections are
+    /// forgotten, as are whether it is unsaved and its light updates, and the
+    /// sections of its horizontal neighbours are marked dirty.
+    pub fn remove_column(&mut self, column_position: IVec2) -> Option<ChunkColumn> {
+        let old_column = self.columns.remove(&column_position)?;
+        self.unsaved_columns.remove(&column_position);
+        self.light_updates.columns.remove(&column_position);
+        self.light_updates.blocks.retain(|block_position| block_to_column_position(*block_position) != column_position);
+        self.mark_column_and_neighbours_dirty(column_position);
+        for dirty_sections in &mut self.dirty_sections {
+            dirty_sections.retain(|section_position| section_to_column_position(*section_position) != column_position);
+        }
+        Some(old_column)
+    }
+
+    pub fn column(&self, column_position: IVec2) -> Option<&ChunkColumn> {
+        self.columns.get(&column_position)
+    }
+
+    /// Returns [`None`] if the column containing `block_position` is not
+    /// loaded, or if `block_position` is above or below the world.
+    pub fn get_light(&self, kind: LightKind, block_position: IVec3) -> Option<u8> {
+        Some(self.section(block_to_section_position(block_position))?.get_light(kind, block_to_local_position(block_position)))
+    }
+
+    /// Sets the level of `kind` of light at `block_position`. Since the faces
+    /// of the blocks around it are lit by it, the section containing it is
+    /// marked dirty, along with the neighbouring sections if it is on the
+    /// boundary of its section. Nothing happens if the column containing it is
+    /// not loaded or if it is above or below the world.
+    pub fn set_light(&mut self, kind: LightKind, block_position: IVec3, level: u8) {
+        let section_position = block_to_section_position(block_position);
+        let Some(section) = self.columns.get_mut(&section_to_column_position(section_position)).and_then(|column| column.section_mut(section_position.y)) else {
+            return;
+        };
+        let local_position = block_to_local_position(block_position);
+        let index = local_index(local_position);
+        if section.light[kind as usize].get(index) == level {
+            return;
+        }
+        section.light[kind as usize].set(index, level);
+        self.mark_section_dirty(section_position);
+        for (axis, neighbour_offset) in [IVec3::X, IVec3::Y, IVec3::Z].into_iter().enumerate() {
+            if local_position[axis] == 0 {
+                self.mark_section_dirty(section_position - neighbour_offset);
+            }
+            if local_position[axis] == SECTION_SIZE as u32 - 1 {
+                self.mark_section_dirty(section_position + neighbour_offset);
+            }
+        }
+    }
+
+    /// Sets the level of `kind` of light of every block in the section at
+    /// `section_position`, which is marked dirty along with its neighbours.
+    /// Nothing happens if it is not loaded.
+    pub fn fill_section_light(&mut self, kind: LightKind, section_position: IVec3, level: u8) {
+        let Some(section) = self.columns.get_mut(&section_to_column_position(section_position)).and_then(|column| column.section_mut(section_position.y)) else {
+            return;
+        };
+        if section.light[kind as usize].uniform_level() == Some(level) {
+            return;
+        }
+        section.light[kind as usize].fill(level);
+        for offset in [IVec3::ZERO, IVec3::X, IVec3::NEG_X, IVec3::Y, IVec3::NEG_Y, IVec3::Z, IVec3::NEG_Z] {
+            self.mark_section_dirty(section_position + offset);
+        }
+    }
+
+    pub fn section(&self, section_position: IVec3) -> Option<&ChunkSection> {
+        self.columns.get(&section_to_column_position(section_position))?.section(section_position.y)
+    }
+
+    /// Iterates over the loaded columns in no particular order.
+    pub fn columns(&self) -> impl Iterator<Item = (IVec2, &ChunkColumn)> {
+        self.columns.iter().map(|(column_position, column)| (*column_position, column))
+    }
+
+    /// Iterates over every section of the loaded columns in no particular
+    /// order, along with their section positions.
+    pub fn sections(&self) -> impl Iterator<Item = (IVec3, &ChunkSection)> {
+        self.columns().flat_map(|(column_position, column)| {
+            column.sections().map(move |(section_y, section)| (IVec3::new(column_position.x, section_y, column_position.y), section))
+        })
+    }
+
+    /// Marks the section at `section_position` dirty for every
+    /// [`DirtySectionKind`]. Nothing happens if it is not loaded.
+    pub fn mark_section_dirty(&mut self, section_position: IVec3) {
+        if self.section(section_position).is_none() {
+            return;
+        }
+        for dirty_sections in &mut self.dirty_sections {
+            dirty_sections.insert(section_position);
+        }
+    }
+
+    /// Returns the section positions of the sections that have been marked
+    /// dirty for `kind` since the last call, and clears them.
+    pub fn take_dirty_sections(&mut self, kind: DirtySectionKind) -> HashSet<IVec3> {
+        mem::take(&mut self.dirty_sections[kind as usize])
+    }
+
+    /// Returns the changes that the light has to be updated for since the last
+    /// call, and clears them.
+    pub fn take_light_updates(&mut self) -> LightUpdates {
+        mem::take(&mut self.light_updates)
+    }
+
+    /// Returns the column positions of the columns that have been marked
+    /// unsaved since the last call, and marks them saved.
+    pub fn take_unsaved_columns(&mut self) -> HashSet<IVec2> {
+        mem::take(&mut self.unsaved_columns)
+    }
+
+    /// Marks the column at `column_position` saved, and returns `true` if it
+    /// was unsaved.
+    pub fn mark_column_saved(&mut self, column_position: IVec2) -> bool {
+        self.unsaved_columns.remove(&column_position)
+    }
+
+    fn mark_column_and_neighbours_dirty(&mut self, column_position: IVec2) {
+        for offset in [IVec2::ZERO, IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y] {
+            let neighbour_column_position = column_position + offset;
+            for section_y in (COLUMN_MIN_SECTION_Y..).take(COLUMN_SECTION_COUNT) {
+                self.mark_section_dirty(IVec3::new(neighbour_column_position.x, section_y, neighbour_column_position.y));
+            }
+        }
+    }
+}
+
+pub const fn block_to_section_position(block_position: IVec3) -> IVec3 {
+    IVec3::new(block_position.x >> 4, block_position.y >> 4, block_position.z >> 4)
+}
+
+/// The position of `block_position` inside its section.
+pub const fn block_to_local_position(block_position: IVec3) -> UVec3 {
+    UVec3::new((block_position.x & 15).cast_unsigned(), (block_position.y & 15).cast_unsigned(), (block_position.z & 15).cast_unsigned())
+}
+
+pub const fn section_to_column_position(section_position: IVec3) -> IVec2 {
+    IVec2::new(section_position.x, section_position.z)
+}
+
+pub const fn block_to_column_position(block_position: IVec3) -> IVec2 {
+    section_to_column_position(block_to_section_position(block_position))
+}
+
+/// The block position of the block with the smallest coordinates in the
+/// section at `section_position`.
+pub const fn section_to_block_position(section_position: IVec3) -> IVec3 {
+    IVec3::new(section_position.x * SECTION_SIZE, section_position.y * SECTION_SIZE, section_position.z * SECTION_SIZE)
+}
+
+#[derive(Debug, Error)]
+pub enum SetBlockError {
+    #[error("The column at {0} is not loaded.")]
+    ColumnNotLoaded(IVec2),
+
+    #[error("Y = {0} is above or below the world.")]
+    OutOfHeight(i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the index of the `n`th block that the tests set, spread over the
+    /// whole container so that repacking has to move entries across `u64`s.
+    const fn spread_index(n: usize) -> usize {
+        (n * 1543) % SECTION_VOLUME
+    }
+
+    #[test]
+    fn palette_growth_repacks_the_entries() {
+        let mut container = PalettedContainer::new(BlockId::AIR);
+        assert_eq!(container.bits_per_entry(), 0);
+        assert!(container.data().is_empty());
+        for n in 1..=256_u16 {
+            container.set(spread_index(n.into()), BlockId(n));
+            let palette_length = container.palette().len();
+            let expected_bits_per_entry = match palette_length {
+                2 => 1,
+                3..=4 => 2,
+                5..=8 => 3,
+                9..=16 => 4,
+                17..=32 => 5,
+                33..=64 => 6,
+                65..=128 => 7,
+                129..=256 => 8,
+                _ => 9
+            };
+            assert_eq!(container.bits_per_entry(), expected_bits_per_entry, "A palette of {palette_length} entries has the wrong number of bits per entry.");
+            assert_eq!(container.data().len(), data_length(expected_bits_per_entry));
+            for earlier_n in 1..=n {
+                assert_eq!(container.get(spread_index(earlier_n.into())), BlockId(earlier_n), "Growing the palette to {palette_length} entries lost a block.");
+            }
+        }
+        assert_eq!(container.palette().len(), 257);
+        assert_eq!(container.get(1), BlockId::AIR);
+    }
+
+    #[test]
+    fn compacting_shrinks_the_palette() {
+        let mut section = ChunkSection::default();
+        for n in 1..=16_u16 {
+            section.set_block(UVec3::new(n.into(), 0, 0) % SECTION_SIZE as u32, BlockId(n));
+        }
+        assert_eq!(section.blocks().bits_per_entry(), 5);
+        for n in 2..=16_u16 {
+            section.set_block(UVec3::new(n.into(), 0, 0) % SECTION_SIZE as u32, BlockId(1));
+        }
+        section.compact();
+        assert_eq!(section.blocks().palette(), [BlockId::AIR, BlockId(1)]);
+        assert_eq!(section.blocks().bits_per_entry(), 1);
+        assert_eq!(section.get_block(UVec3::new(0, 0, 0)), BlockId(1));
+        assert_eq!(section.get_block(UVec3::new(5, 0, 0)), BlockId(1));
+        assert_eq!(section.get_block(UVec3::new(5, 1, 0)), BlockId::AIR);
+
+        let mut container = section.blocks().clone();
+        for index in 0..SECTION_VOLUME {
+            container.set(index, BlockId(7));
+        }
+        container.compact();
+        assert_eq!(container.palette(), [BlockId(7)]);
+        assert_eq!(container.bits_per_entry(), 0);
+        assert!(container.data().is_empty());
+        assert_eq!(container.get(SECTION_VOLUME - 1), BlockId(7));
+    }
+
+    #[test]
+    fn inconsistent_raw_parts_are_rejected() {
+        let mut container = PalettedContainer::new(BlockId::AIR);
+        container.set(0, BlockId(1));
+        container.set(1, BlockId(2));
+        let (palette, bits_per_entry, data) = (container.palette().to_vec(), container.bits_per_entry(), container.data().to_vec());
+        let restored = PalettedContainer::from_raw_parts(palette.clone(), bits_per_entry, data.clone()).expect("The parts of a container should be consistent.");
+        assert_eq!((0..SECTION_VOLUME).map(|index| restored.get(index)).collect::<Vec<_>>(), (0..SECTION_VOLUME).map(|index| container.get(index)).collect::<Vec<_>>());
+        // More bits than needed are fine, as long as the data matches them.
+        assert!(PalettedContainer::from_raw_parts(palette.clone(), 4, vec![0; data_length(4)]).is_some());
+
+        assert!(PalettedContainer::from_raw_parts(Vec::new(), 0, Vec::new()).is_none(), "An empty palette should be rejected.");
+        assert!(PalettedContainer::from_raw_parts(palette.clone(), 1, vec![0; data_length(1)]).is_none(), "Too few bits for the palette should be rejected.");
+        assert!(PalettedContainer::from_raw_parts(palette.clone(), 17, vec![0; data_length(17)]).is_none(), "More than 16 bits per entry should be rejected.");
+        assert!(PalettedContainer::from_raw_parts(palette.clone(), bits_per_entry, data[1..].to_vec()).is_none(), "Too little data should be rejected.");
+        let mut out_of_palette = data;
+        // Palette index 3 in a palette of 3 entries.
+        out_of_palette[0] |= 0b11;
+        assert!(PalettedContainer::from_raw_parts(palette, bits_per_entry, out_of_palette).is_none(), "Entries outside the palette should be rejected.");
+    }
+
+    #[test]
+    fn blocks_are_stored_at_negative_positions_and_the_height_bounds() {
+        let mut world = VoxelWorld::default();
+        world.insert_column(IVec2::new(-1, -1), ChunkColumn::default());
+        world.insert_column(IVec2::ZERO, ChunkColumn::default());
+        let positions = [
+            IVec3::new(-1, COLUMN_MIN_Y, -1),
+            IVec3::new(-16, COLUMN_MAX_Y, -16),
+            IVec3::new(-1, -1, -16),
+            IVec3::new(0, COLUMN_MIN_Y, 0),
+            IVec3::new(15, COLUMN_MAX_Y, 15)
+        ];
+        for (n, position) in (1..).zip(positions) {
+            assert_eq!(world.set_block(position, BlockId(n)).expect("The block should be in a loaded column."), BlockId::AIR);
+        }
+        for (n, position) in (1..).zip(positions) {
+            assert_eq!(world.get_block(position), Some(BlockId(n)), "The block at {position} was not stored.");
+        }
+        assert_eq!(world.get_block(IVec3::new(-2, COLUMN_MIN_Y, -1)), Some(BlockId::AIR));
+        assert_eq!(world.get_block(IVec3::new(-1, COLUMN_MIN_Y + 1, -1)), Some(BlockId::AIR));
+
+        assert_eq!(world.get_block(IVec3::new(-1, COLUMN_MIN_Y - 1, -1)), None);
+        assert_eq!(world.get_block(IVec3::new(-1, COLUMN_MAX_Y + 1, -1)), None);
+        assert!(matches!(world.set_block(IVec3::new(-1, COLUMN_MIN_Y - 1, -1), BlockId(1)), Err(SetBlockError::OutOfHeight(y)) if y == COLUMN_MIN_Y - 1));
+        assert!(matches!(world.set_block(IVec3::new(0, COLUMN_MAX_Y + 1, 0), BlockId(1)), Err(SetBlockError::OutOfHeight(y)) if y == COLUMN_MAX_Y + 1));
+        assert_eq!(world.get_block(IVec3::new(-17, 0, 0)), None);
+        assert!(matches!(world.set_block(IVec3::new(-17, 0, 0), BlockId(1)), Err(SetBlockError::ColumnNotLoaded(column_position)) if column_position == IVec2::new(-2, 0)));
+    }
+
+    #[test]
+    fn setting_blocks_marks_sections_dirty() {
+        let mut world = VoxelWorld::default();
+        for x in -1..=1 {
+            for z in -1..=1 {
+                world.insert_column(IVec2::new(x, z), ChunkColumn::default());
+            }
+        }
+        world.take_dirty_sections(DirtySectionKind::Mesh);
+
+        world.set_block(IVec3::new(5, 5, 5), BlockId(1)).expect("The block should be in a loaded column.");
+        assert_eq!(world.take_dirty_sections(DirtySectionKind::Mesh), HashSet::from([IVec3::ZERO]));
+
+        world.set_block(IVec3::new(5, 5, 5), BlockId(1)).expect("The block should be in a loaded column.");
+        assert!(world.take_dirty_sections(DirtySectionKind::Mesh).is_empty(), "Setting a block to itself should not mark anything dirty.");
+
+        world.set_block(IVec3::new(0, 15, 8), BlockId(2)).expect("The block should be in a loaded column.");
+        assert_eq!(world.take_dirty_sections(DirtySectionKind::Mesh), HashSet::from([IVec3::ZERO, IVec3::NEG_X, IVec3::Y]));
+
+        world.set_block(IVec3::new(-1, -16, -1), BlockId(2)).expect("The block should be in a loaded column.");
+        assert_eq!(world.take_dirty_sections(DirtySectionKind::Mesh), HashSet::from([
+            IVec3::new(-1, -1, -1), IVec3::new(0, -1, -1), IVec3::new(-1, -2, -1), IVec3::new(-1, -1, 0)
+        ]));
+
+        // The neighbours above the world and in unloaded columns do not exist,
+        // so they are not marked.
+        world.set_block(IVec3::new(31, COLUMN_MAX_Y, 8), BlockId(2)).expect("The block should be in a loaded column.");
+        let top_section_y = COLUMN_MAX_Y >> 4;
+        assert_eq!(world.take_dirty_sections(DirtySectionKind::Mesh), HashSet::from([IVec3::new(1, top_section_y, 0)]));
+    }
+}
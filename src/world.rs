@@ -1,16 +1,61 @@
 //! Contains functions to create the [`World`]s that the app uses.
 
 use assets_manager::*;
+use assets_manager::Error as AssetCacheError;
 use bevy_ecs::world::*;
+use glam::*;
+use std::collections::*;
+use std::sync::*;
+use crate::anvil::*;
+use crate::asset::*;
+use crate::background_jobs::*;
+use crate::biome::*;
+use crate::block_registry::*;
+use crate::camera::*;
+use crate::chunk_mesher::*;
+use crate::chunk_streaming::*;
+use crate::components::core::*;
 use crate::components::egui::*;
+use crate::resources::block::*;
+use crate::resources::chunk_streaming::*;
 use crate::resources::core::*;
 use crate::resources::egui::*;
+use crate::resources::lighting::*;
+use crate::resources::terrain_generation::*;
+use crate::resources::voxel_world::*;
+use crate::resources::world_save::*;
 use crate::egui_renderer::*;
 use crate::egui_state::*;
+use crate::lighting::*;
+use crate::material::*;
 use crate::constants::*;
+use crate::materials::*;
+use crate::mesh::*;
+use crate::ore::*;
+use crate::region::*;
+use crate::shapes::*;
+use crate::structure::*;
+use crate::terrain_features::*;
+use crate::terrain_generator::*;
+use crate::terrain_passes::*;
+use crate::voxel_world::*;
+use crate::world_storage::*;
 use log::*;
+use std::fs;
 use std::io;
+use std::path::*;
 use thiserror::*;
+use tokio::runtime::{Handle as RuntimeHandle, TryCurrentError};
+
+/// The world seed of the main [`World`] when it is created for the first
+/// time. Afterwards, the seed saved in its [`WorldMetadata`] is used.
+const DEMO_SEED: u64 = 20_250_718;
+
+/// Where the camera starts the first time the main [`World`] is created.
+const DEMO_SPAWN_POINT: Vec3 = Vec3::new(0.0, 70.0, -90.0);
+
+/// How far around the camera columns are loaded and simulated.
+const DEMO_STREAMING_DISTANCES: StreamingDistances = StreamingDistances { render_distance: 8, simulation_distance: 4, unload_margin: 2 };
 
 pub fn create_main_world() -> Result<World, WorldInitializationError> {
     let mut world = World::new();
@@ -18,6 +63,9 @@ pub fn create_main_world() -> Result<World, WorldInitializationError> {
     if let Err(err) = add_asset_cache_resources(&mut world) {
         return Err(err.into())
     }
+    add_block_registry_resource(&mut world)?;
+    add_demo_voxel_world(&mut world)?;
+    add_demo_scene_entities(&mut world)?;
     Ok(world)
 }
 
@@ -26,7 +74,7 @@ fn add_egui_entities_and_resources(world: &mut World) {
     world.insert_resource(EguiStateResource { egui_state: Box::new(DefaultEguiState::new()) });
 }
 
-fn add_asset_cache_resources(world: &mut World) -> Result<(), io::Error> {
+pub fn add_asset_cache_resources(world: &mut World) -> Result<(), io::Error> {
     info!("Using assets path: {ASSETS_PATH}");
     let asset_cache = match AssetCache::new(ASSETS_PATH) {
         Ok(asset_cache) => asset_cache,
@@ -39,8 +87,209 @@ fn add_asset_cache_resources(world: &mut World) -> Result<(), io::Error> {
     Ok(())
 }
 
+/// Loads the block definitions using [`AssetCacheResource`], which must have
+/// been added already.
+pub fn add_block_registry_resource(world: &mut World) -> Result<(), BlockRegistryError> {
+    let asset_cache = &world.resource::<AssetCacheResource>().asset_cache;
+    let block_definitions: AssetHandle<BlockDefinitions> = match asset_cache.load::<BlockDefinitions>(BLOCKS_ASSET_ID) {
+        Ok(handle) => handle.strong().into(),
+        Err(err) => {
+            error!("The block definitions could not be loaded. {err:#?}");
+            return Err(err.into());
+        }
+    };
+    let block_registry = BlockRegistry::new(&block_definitions.read())?;
+    let definitions_reload_id = block_definitions.last_reload_id();
+    world.insert_resource(BlockRegistryResource { registry: block_registry, definitions: block_definitions, definitions_reload_id });
+    Ok(())
+}
+
+/// Adds an empty [`VoxelWorldResource`] with a [`LightingResource`] that lights
+/// it, a [`WorldSaveResource`] that saves it to [`WORLD_PATH`], a
+/// [`TerrainGenerationResource`] that loads the saved columns and generates the
+/// others with caves, ores and structures, using the biomes, ores and
+/// structures loaded using [`AssetCacheResource`], and a
+/// [`ChunkStreamingResource`] that loads the columns within
+/// [`DEMO_STREAMING_DISTANCES`] of the camera. [`BlockRegistryResource`] must
+/// have been added already, and this must be called from within the tokio
+/// runtime.
+fn add_demo_voxel_world(world: &mut World) -> Result<(), WorldInitializationError> {
+    let runtime = match RuntimeHandle::try_current() {
+        Ok(runtime) => runtime,
+        Err(err) => {
+            error!("Terrain cannot be generated because there is no tokio runtime to run the generation jobs on.");
+            return Err(err.into());
+        }
+    };
+    let asset_cache = &world.resource::<AssetCacheResource>().asset_cache;
+    let block_registry = &world.resource::<BlockRegistryResource>().registry;
+    let biome_definitions = load_definitions::<BiomeDefinitions>(asset_cache, BIOMES_ASSET_ID)?.read();
+    info!("Using world path: {WORLD_PATH}");
+    let storage = WorldStorage::open(WORLD_PATH, StorageNames::new(block_registry, &biome_definitions), runtime.clone())?;
+    let metadata = storage.read_metadata()?.unwrap_or_else(|| WorldMetadata {
+        seed: DEMO_SEED,
+        spawn_point: DEMO_SPAWN_POINT.into(),
+        time_of_day: 0.0,
+        player_position: DEMO_SPAWN_POINT.into()
+    });
+    let pending_writes = PendingBlockWrites::from_writes(storage.read_pending_writes()?);
+    let seed = metadata.seed;
+    let biomes = TerrainBiome::from_definitions(&biome_definitions, block_registry)?;
+    let ores = TerrainOre::from_definitions(&load_definitions::<OreDefinitions>(asset_cache, ORES_ASSET_ID)?.read(), block_registry)?;
+    let structure_templates = load_definitions::<StructureTemplates>(asset_cache, STRUCTURES_ASSET_ID)?.read();
+    let structures = BiomeStructureFeature::from_definitions(seed, &biome_definitions, &structure_templates, block_registry)?;
+    drop(structure_templates);
+    drop(biome_definitions);
+    let blocks = TerrainBlocks::from_registry(block_registry)?;
+    let lighting_table = BlockLightingTable::new(block_registry);
+    let generator = TerrainPipeline::new(LayeredNoiseTerrainGenerator::new(seed, blocks, biomes)?)
+        .with_pass(CheeseCavePass::new(seed, blocks))
+        .with_pass(WormCavePass::new(seed, blocks))
+        .with_pass(OreVeinPass::new(seed, blocks.stone, ores))
+        .with_feature(structures);
+    world.insert_resource(TerrainGenerationResource {
+        generator: Arc::new(generator),
+        requested_columns: VecDeque::new(),
+        generation_jobs: BackgroundJobs::with_default_limit(runtime),
+        pending_writes
+    });
+    world.insert_resource(WorldSaveResource::new(storage, metadata));
+    world.insert_resource(ChunkStreamingResource::new(DEMO_STREAMING_DISTANCES));
+    world.insert_resource(VoxelWorldResource { voxel_world: VoxelWorld::default(), meshing_mode: MeshingMode::Greedy });
+    world.insert_resource(LightingResource { lighting_table });
+    Ok(())
+}
+
+/// Imports the chunks in the region files of the Minecraft Java Edition world
+/// in `anvil_world_path` into the world saved in [`WORLD_PATH`], replacing the
+/// columns that are in both, and logs what could not be imported. Other
+/// columns are still generated when they are loaded. This must be called from
+/// within the tokio runtime.
+pub fn import_anvil_world(anvil_world_path: &Path) -> Result<(), WorldInitializationError> {
+    let mut world = World::new();
+    add_asset_cache_resources(&mut world)?;
+    add_block_registry_resource(&mut world)?;
+    let runtime = match RuntimeHandle::try_current() {
+        Ok(runtime) => runtime,
+        Err(err) => {
+            error!("The world cannot be saved because there is no tokio runtime to run the write jobs on.");
+            return Err(err.into());
+        }
+    };
+    let asset_cache = &world.resource::<AssetCacheResource>().asset_cache;
+    let block_registry = &world.resource::<BlockRegistryResource>().registry;
+    let biome_definitions = load_definitions::<BiomeDefinitions>(asset_cache, BIOMES_ASSET_ID)?.read();
+    let mappings = load_definitions::<AnvilBlockMappings>(asset_cache, ANVIL_BLOCK_MAPPINGS_ASSET_ID)?.read();
+    let mut importer = AnvilImporter::new(&mappings, block_registry, &biome_definitions);
+    drop(mappings);
+    let storage_names = StorageNames::new(block_registry, &biome_definitions);
+    drop(biome_definitions);
+    let mut storage = WorldStorage::open(WORLD_PATH, storage_names, runtime)?;
+    let regions_path = anvil_world_path.join("region");
+    info!("Importing the Minecraft world in {}", anvil_world_path.display());
+    let mut region_paths: Vec<_> = fs::read_dir(&regions_path)
+        .and_then(|entries| entries.map(|entry| Ok(entry?.path())).collect::<io::Result<Vec<_>>>())
+        .map_err(|err| {
+            error!("The region files in {} could not be listed. {err}", regions_path.display());
+            AnvilError::from(err)
+        })?;
+    region_paths.retain(|path| path.extension().is_some_and(|extension| extension == "mca"));
+    region_paths.sort_unstable();
+    for region_path in region_paths {
+        let columns = match fs::read(&region_path).map_err(AnvilError::from).and_then(|bytes| importer.import_region(&bytes)) {
+            Ok(columns) => columns,
+            Err(err) => {
+                error!("The region file {} could not be imported. {err}", region_path.display());
+                continue;
+            }
+        };
+        for (column_position, column) in columns {
+            storage.save_column(column_position, column);
+        }
+        // Writing each region before reading the next keeps only one region
+        // in memory at a time.
+        storage.flush_blocking();
+    }
+    importer.finish().log();
+    Ok(())
+}
+
+/// Loads definitions that terrain generation and importing need from
+/// `asset_cache`.
+fn load_definitions<'a, T: Asset>(asset_cache: &'a AssetCache, id: &str) -> Result<&'a Handle<T>, AssetCacheError> {
+    asset_cache.load::<T>(id).inspect_err(|err| error!("The definitions {id} could not be loaded. {err:#?}"))
+}
+
+/// Spawns a camera overlooking the generated terrain, with a cuboid floating in
+/// front of it between a row of the other shapes in [`crate::shapes`], so that
+/// they can all be checked by eye. The camera is placed where the player was
+/// when the world was last saved, if there is a [`WorldSaveResource`].
+fn add_demo_scene_entities(world: &mut World) -> Result<(), MeshCreationError> {
+    let camera_position = world.get_resource::<WorldSaveResource>()
+        .map_or(DEMO_SPAWN_POINT, |world_save_resource| Vec3::from(world_save_resource.metadata.player_position));
+    world.spawn((
+        CameraComponent {
+            projection_mode: ProjectionMode::Perspective(PerspectiveProjectionConfig {
+                horizontal_fov: 90_f32.to_radians(),
+                near_clipping_plane_distance: 0.1,
+                far_clipping_plane_distance: 1000.0
+            }),
+            reverse_z: true
+        },
+        TransformComponent {
+            position: camera_position,
+            rotation: Quat::from_rotation_x(0.5),
+            ..Default::default()
+        }
+    ));
+    let cuboid_mesh = Cuboid { dimensions: Vec3::ONE }.as_mesh_builder().build()?;
+    world.spawn((
+        MeshRendererComponent {
+            material: AssetHandle::Dynamic(Arc::new(UnlitMaterial { color: Vec4::new(0.2, 0.6, 0.3, 1.0) })),
+            mesh: AssetHandle::Dynamic(Arc::new(cuboid_mesh))
+        },
+        TransformComponent {
+            position: Vec3::new(0.0, 58.0, -72.0),
+            rotation: Quat::from_rotation_y(0.6),
+            ..Default::default()
+        }
+    ));
+    let shape_meshes = [
+        Plane { size: Vec2::ONE, subdivisions: UVec2::splat(2) }.as_mesh_builder().build()?,
+        Quad { size: Vec2::ONE, subdivisions: UVec2::splat(2) }.as_mesh_builder().build()?,
+        UvSphere { radius: 0.5, sectors: 16, rings: 8 }.as_mesh_builder().build()?,
+        IcoSphere { radius: 0.5, subdivisions: 2 }.as_mesh_builder().build()?,
+        Cylinder { radius: 0.5, height: 1.0, sectors: 16, segments: 1 }.as_mesh_builder().build()?,
+        Cone { radius: 0.5, height: 1.0, sectors: 16, segments: 1 }.as_mesh_builder().build()?,
+        Capsule { radius: 0.3, length: 0.4, sectors: 16, hemisphere_rings: 4, segments: 1 }.as_mesh_builder().build()?,
+        Torus { major_radius: 0.4, minor_radius: 0.15, sectors: 24, rings: 12 }.as_mesh_builder().build()?
+    ];
+    let shape_material: AssetHandle<dyn Material + Send + Sync> = AssetHandle::Dynamic(Arc::new(UnlitMaterial { color: Vec4::new(0.3, 0.4, 0.7, 1.0) }));
+    for (mesh, x) in shape_meshes.into_iter().zip([-10.0, -7.5, -5.0, -2.5, 2.5, 5.0, 7.5, 10.0]) {
+        world.spawn((
+            MeshRendererComponent {
+                material: shape_material.clone(),
+                mesh: AssetHandle::Dynamic(Arc::new(mesh))
+            },
+            TransformComponent {
+                position: Vec3::new(x, 58.0, -72.0),
+                rotation: Quat::from_rotation_x(-0.4),
+                ..Default::default()
+            }
+        ));
+    }
+    Ok(())
+}
+
 #[derive(Debug, Error)]
 #[error(transparent)]
 pub enum WorldInitializationError {
-    AssetsNotFound(#[from] io::Error)
+    AssetsNotFound(#[from] io::Error),
+    MeshCreation(#[from] MeshCreationError),
+    BlockRegistry(#[from] BlockRegistryError),
+    TerrainGenerator(#[from] TerrainGeneratorError),
+    AssetCache(#[from] AssetCacheError),
+    NoTokioRuntime(#[from] TryCurrentError),
+    WorldStorage(#[from] WorldStorageError),
+    AnvilImport(#[from] AnvilError)
 }
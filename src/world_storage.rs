@@ -0,0 +1,388 @@
+//! Contains [`WorldStorage`], which saves worlds to and loads them from a
+//! directory. The directory contains:
+//! - [`METADATA_FILE_NAME`], the [`WorldMetadata`] in RON.
+//! - [`PENDING_WRITES_FILE_NAME`], the blocks that features wrote into columns
+//!   that have never been loaded, in RON. See
+//!   [`crate::terrain_generator::PendingBlockWrites`].
+//! - [`REGIONS_DIRECTORY_NAME`], which contains the region files described in
+//!   [`crate::region`].
+//!
+//! Files are written in the background, and every file is written to a
+//! temporary file first and then renamed over the old one, so that a crash
+//! while saving leaves either the old or the new version of each file behind,
+//! never a mix of the two.
+
+use glam::*;
+use log::*;
+use ron::error::SpannedError;
+use ron::ser::{PrettyConfig, to_string_pretty};
+use serde::*;
+use std::collections::*;
+use std::fs::File;
+use std::fs;
+use std::io::{self, ErrorKind, Write as _};
+use std::path::*;
+use std::sync::*;
+use thiserror::*;
+use tokio::runtime::*;
+use crate::background_jobs::*;
+use crate::block::*;
+use crate::region::*;
+use crate::structure::*;
+use crate::terrain_generator::*;
+use crate::voxel_world::*;
+
+pub const METADATA_FILE_NAME: &str = "level.ron";
+
+pub const PENDING_WRITES_FILE_NAME: &str = "pending_writes.ron";
+
+pub const REGIONS_DIRECTORY_NAME: &str = "region";
+
+/// Everything about a world that is not stored in its columns.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WorldMetadata {
+    pub seed: u64,
+    /// Where players start the first time they join the world.
+    pub spawn_point: (f32, f32, f32),
+    /// How far into the current day the world is, from 0 to 1. Nothing
+    /// advances it yet.
+    pub time_of_day: f32,
+    /// Where the player was when the world was last saved.
+    pub player_position: (f32, f32, f32)
+}
+
+/// A [`BlockWrite`] with its block stored by name, since [`BlockId`]s are
+/// only stable for a single run.
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedBlockWrite {
+    block_position: (i32, i32, i32),
+    block: BlockName,
+    replace_mode: ReplaceMode
+}
+
+/// Identifies a file that [`WorldStorage`] writes in the background.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum WriteKey {
+    /// The region file of the region at this position.
+    Region(IVec2),
+    /// One of the files directly in the world directory.
+    File(&'static str)
+}
+
+/// The columns that a write job wrote, along with whether it succeeded. The
+/// columns are kept so that they can be written again if it failed.
+type WriteResult = (Vec<(IVec2, Arc<ChunkColumn>)>, Result<(), WorldStorageError>);
+
+/// Saves a world to a directory and loads it back. Saving only queues what to
+/// write, and [`Self::flush`] writes the queued files in the background. Each
+/// file is written by at most one job at a time, so that an older version of a
+/// file can never be renamed over a newer one.
+pub struct WorldStorage {
+    directory: PathBuf,
+    names: Arc<StorageNames>,
+    /// Columns that have been saved but not handed to a write job yet, keyed by
+    /// column position.
+    unwritten_columns: HashMap<IVec2, Arc<ChunkColumn>>,
+    /// Columns that a write job is writing, keyed by column position.
+    writing_columns: HashMap<IVec2, Arc<ChunkColumn>>,
+    /// The contents of files that have been saved but not handed to a write
+    /// job yet, keyed by file name.
+    unwritten_files: HashMap<&'static str, Vec<u8>>,
+    writes: BackgroundJobs<WriteKey, WriteResult>
+}
+
+impl WorldStorage {
+    /// Opens the world saved in `directory`, creating the directory if it does
+    /// not exist. Files are written on `runtime`.
+    pub fn open(directory_path: impl Into<PathBuf>, names: StorageNames, runtime: Handle) -> Result<Self, WorldStorageError> {
+        let directory = directory_path.into();
+        if let Err(err) = fs::create_dir_all(directory.join(REGIONS_DIRECTORY_NAME)) {
+            error!("The world directory {} could not be created. {err}", directory.display());
+            return Err(err.into());
+        }
+        Ok(Self {
+            directory,
+            names: Arc::new(names),
+            unwritten_columns: HashMap::new(),
+            writing_columns: HashMap::new(),
+            unwritten_files: HashMap::new(),
+            writes: BackgroundJobs::with_default_limit(runtime)
+        })
+    }
+
+    /// Returns [`None`] if the world has never been saved.
+    pub fn read_metadata(&self) -> Result<Option<WorldMetadata>, WorldStorageError> {
+        let Some(text) = self.read_file(METADATA_FILE_NAME)? else {
+            return Ok(None);
+        };
+        ron::from_str(&text).map(Some).map_err(|err| {
+            error!("The world metadata could not be parsed. {err}");
+            err.into()
+        })
+    }
+
+    /// Reads the block writes saved by [`Self::save_pending_writes`]. Writes of
+    /// blocks that no longer exist are left out.
+    pub fn read_pending_writes(&self) -> Result<Vec<BlockWrite>, WorldStorageError> {
+        let Some(text) = self.read_file(PENDING_WRITES_FILE_NAME)? else {
+            return Ok(Vec::new());
+        };
+        let saved_writes: Vec<SavedBlockWrite> = ron::from_str(&text).map_err(|err| {
+            error!("The pending block writes could not be parsed. {err}");
+            WorldStorageError::from(err)
+        })?;
+        Ok(saved_writes.into_iter().filter_map(|saved_write| {
+            let Some(block) = self.names.block_id(&saved_write.block) else {
+                warn!("The saved block {} no longer exists, so it is not placed.", saved_write.block);
+                return None;
+            };
+            Some(BlockWrite { block_position: IVec3::from(saved_write.block_position), block, replace_mode: saved_write.replace_mode })
+        }).collect())
+    }
+
+    /// Returns a [`ColumnLoader`] that can load the saved columns of this world
+    /// from another thread.
+    pub fn column_loader(&self) -> ColumnLoader {
+        ColumnLoader { regions_directory: self.directory.join(REGIONS_DIRECTORY_NAME), names: Arc::clone(&self.names) }
+    }
+
+    /// Returns the column at `column_position` if it has been saved but is not
+    /// in its region file yet, in which case [`ColumnLoader::load`] would load
+    /// an older version of it.
+    pub fn unwritten_column(&self, column_position: IVec2) -> Option<Arc<ChunkColumn>> {
+        self.unwritten_columns.get(&column_position).or_else(|| self.writing_columns.get(&column_position)).cloned()
+    }
+
+    /// Queues `column` to be written to the region file of `column_position`.
+    pub fn save_column(&mut self, column_position: IVec2, column: ChunkColumn) {
+        self.unwritten_columns.insert(column_position, Arc::new(column));
+    }
+
+    pub fn save_metadata(&mut self, metadata: &WorldMetadata) {
+        match to_string_pretty(metadata, PrettyConfig::default()) {
+            Ok(text) => {
+                self.unwritten_files.insert(METADATA_FILE_NAME, text.into_bytes());
+            }
+            Err(err) => error!("The world metadata could not be serialized. {err}")
+        }
+    }
+
+    pub fn save_pending_writes(&mut self, pending_writes: &PendingBlockWrites) {
+        let saved_writes: Vec<_> = pending_writes.iter()
+            .filter_map(|write| Some(SavedBlockWrite {
+                block_position: write.block_position.into(),
+                block: self.names.block_name(write.block)?.clone(),
+                replace_mode: write.replace_mode
+            }))
+            .collect();
+        match to_string_pretty(&saved_writes, PrettyConfig::default()) {
+            Ok(text) => {
+                self.unwritten_files.insert(PENDING_WRITES_FILE_NAME, text.into_bytes());
+            }
+            Err(err) => error!("The pending block writes could not be serialized. {err}")
+        }
+    }
+
+    /// Starts writing the saved columns and files in the background, except
+    /// for the files that are already being written, which are written by a
+    /// later call once their jobs have been collected by
+    /// [`Self::collect_finished_writes`].
+    pub fn flush(&mut self) {
+        let file_names: Vec<_> = self.unwritten_files.keys().copied().collect();
+        for file_name in file_names {
+            if self.writes.free_slots() == 0 {
+                return;
+            }
+            if self.writes.contains(&WriteKey::File(file_name)) {
+                continue;
+            }
+            let Some(bytes) = self.unwritten_files.remove(file_name) else {
+                continue;
+            };
+            let path = self.directory.join(file_name);
+            self.writes.spawn(WriteKey::File(file_name), move || (Vec::new(), write_atomically(&path, &bytes).map_err(Into::into)));
+        }
+        let mut regions: HashMap<IVec2, Vec<IVec2>> = HashMap::new();
+        for column_position in self.unwritten_columns.keys() {
+            regions.entry(column_to_region_position(*column_position)).or_default().push(*column_position);
+        }
+        for (region_position, column_positions) in regions {
+            if self.writes.free_slots() == 0 {
+                return;
+            }
+            if self.writes.contains(&WriteKey::Region(region_position)) {
+                continue;
+            }
+            let columns: Vec<_> = column_positions.into_iter()
+                .filter_map(|column_position| Some((column_position, self.unwritten_columns.remove(&column_position)?)))
+                .collect();
+            for (column_position, column) in &columns {
+                self.writing_columns.insert(*column_position, Arc::clone(column));
+            }
+            let path = self.directory.join(REGIONS_DIRECTORY_NAME).join(region_file_name(region_position));
+            let names = Arc::clone(&self.names);
+            self.writes.spawn(WriteKey::Region(region_position), move || {
+                let result = write_region(&path, &columns, &names);
+                (columns, result)
+            });
+        }
+    }
+
+    /// Collects the write jobs that have finished. The columns of the region
+    /// files that could not be written are queued to be written again, unless
+    /// they have been saved again since.
+    pub fn collect_finished_writes(&mut self) {
+        for (key, result) in self.writes.take_finished() {
+            self.handle_finished_write(key, result);
+        }
+    }
+
+    /// Writes everything that has been saved, and blocks until it is written
+    /// or a write fails. This should be called before exiting, after saving.
+    pub fn flush_blocking(&mut self) {
+        loop {
+            self.flush();
+            if self.writes.is_empty() {
+                return;
+            }
+            let mut succeeded = true;
+            for (key, result) in self.writes.wait_all() {
+                succeeded &= self.handle_finished_write(key, result);
+            }
+            if !succeeded {
+                error!("The world could not be fully saved.");
+                return;
+            }
+        }
+    }
+
+    /// Returns `true` if the write succeeded.
+    fn handle_finished_write(&mut self, key: WriteKey, (columns, result): WriteResult) -> bool {
+        for (column_position, column) in columns {
+            if self.writing_columns.get(&column_position).is_some_and(|writing_column| Arc::ptr_eq(writing_column, &column)) {
+                self.writing_columns.remove(&column_position);
+            }
+            if result.is_err() {
+                self.unwritten_columns.entry(column_position).or_insert(column);
+            }
+        }
+        match result {
+            Ok(()) => true,
+            Err(err) => {
+                error!("{key:?} could not be written. {err}");
+                false
+            }
+        }
+    }
+
+    /// Returns [`None`] if the file does not exist.
+    fn read_file(&self, file_name: &str) -> Result<Option<String>, WorldStorageError> {
+        let path = self.directory.join(file_name);
+        match fs::read_to_string(&path) {
+            Ok(text) => Ok(Some(text)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => {
+                error!("{} could not be read. {err}", path.display());
+                Err(err.into())
+            }
+        }
+    }
+}
+
+/// Loads saved columns from the region files of a [`WorldStorage`]. It can be
+/// sent to other threads, so that columns can be loaded in background jobs.
+#[derive(Clone)]
+pub struct ColumnLoader {
+    regions_directory: PathBuf,
+    names: Arc<StorageNames>
+}
+
+impl ColumnLoader {
+    /// Returns [`None`] if the column at `column_position` has never been
+    /// written.
+    pub fn load(&self, column_position: IVec2) -> Result<Option<ChunkColumn>, RegionError> {
+        let path = self.regions_directory.join(region_file_name(column_to_region_position(column_position)));
+        RegionFile::read_column(&path, column_position)?
+            .map(|bytes| decode_column(&bytes, &self.names))
+            .transpose()
+    }
+}
+
+/// Adds `columns` to the region file at `path`, keeping the other columns in
+/// it.
+fn write_region(path: &Path, columns: &[(IVec2, Arc<ChunkColumn>)], names: &StorageNames) -> Result<(), WorldStorageError> {
+    let mut region = RegionFile::read(path)?;
+    for (column_position, column) in columns {
+        region.set_column(*column_position, encode_column(column, names)?);
+    }
+    write_atomically(path, &region.to_bytes())?;
+    Ok(())
+}
+
+/// Writes `bytes` to a temporary file next to `path`, makes sure they reach
+/// the disk, and then renames the temporary file to `path`. Renaming replaces
+/// the old file in one step, so `path` never contains a partially written
+/// file.
+fn write_atomically(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let mut temporary_path = path.as_os_str().to_owned();
+    temporary_path.push(".tmp");
+    let mut file = File::create(&temporary_path)?;
+    file.write_all(bytes)?;
+    file.sync_all()?;
+    fs::rename(&temporary_path, path)
+}
+
+#[derive(Debug, Error)]
+pub enum WorldStorageError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    #[error(transparent)]
+    Region(#[from] RegionError),
+
+    #[error(transparent)]
+    Parse(#[from] SpannedError)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    const STONE: BlockId = BlockId(1);
+
+    fn test_names() -> StorageNames {
+        StorageNames::from_names(vec![BlockName::air(), "mycraft:stone".parse().expect("The name should be valid.")], vec!["plains".to_owned()])
+    }
+
+    #[test]
+    fn saved_worlds_are_loaded_back() {
+        let directory = env::temp_dir().join(format!("mycraft-world-storage-test-{}", process::id()));
+        let runtime = Runtime::new().expect("The runtime should be created.");
+        let metadata = WorldMetadata { seed: 7, spawn_point: (1.0, 2.0, 3.0), time_of_day: 0.25, player_position: (-40.0, 70.0, 12.5) };
+        let mut column = ChunkColumn::default();
+        column.set_block(IVec3::new(3, 5, 7), STONE);
+        let column_positions = [IVec2::new(-1, 0), IVec2::new(40, 3)];
+        let write = BlockWrite { block_position: IVec3::new(100, 10, -3), block: STONE, replace_mode: ReplaceMode::Any };
+        let mut storage = WorldStorage::open(&directory, test_names(), runtime.handle().clone()).expect("The storage should be opened.");
+        assert_eq!(storage.read_metadata().expect("The metadata should be read."), None);
+        for column_position in column_positions {
+            storage.save_column(column_position, column.clone());
+        }
+        storage.save_metadata(&metadata);
+        storage.save_pending_writes(&PendingBlockWrites::from_writes([write]));
+        storage.flush_blocking();
+        drop(storage);
+        let reopened_storage = WorldStorage::open(&directory, test_names(), runtime.handle().clone()).expect("The storage should be opened.");
+        assert_eq!(reopened_storage.read_metadata().expect("The metadata should be read."), Some(metadata));
+        assert_eq!(reopened_storage.read_pending_writes().expect("The pending writes should be read."), vec![write]);
+        let column_loader = reopened_storage.column_loader();
+        for column_position in column_positions {
+            let loaded_column = column_loader.load(column_position).expect("The column should be loaded.").expect("The column should have been saved.");
+            assert_eq!(loaded_column.get_block(IVec3::new(3, 5, 7)), Some(STONE));
+        }
+        assert!(column_loader.load(IVec2::new(-2, 0)).expect("The region file should be read.").is_none());
+        drop(fs::remove_dir_all(&directory));
+    }
+}